@@ -0,0 +1,173 @@
+//! Zero-copy archived descriptors, behind the `rkyv` feature.
+//!
+//! Archived descriptors can be accessed in place, e.g. from a memory-mapped
+//! file, after a structural validation of the bytes. Data type codes are the
+//! same as in the binary encoding, and are validated when accessed or when
+//! converting back to a `Descriptor`.
+
+use rkyv::rancor;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use super::binary::{
+    data_type_code, data_type_from_code, NAME_DATA_TYPE, NAME_IDENT, NAME_UNKNOWN_DATA_TYPE,
+};
+use super::{
+    BuildError, Component, ComponentName, CustomIdent, DataType, DecodeError, DefaultImpl,
+    Descriptor, Multiplier,
+};
+
+const MULTIPLIER_NONE: u8 = 0;
+const MULTIPLIER_SPACE: u8 = 1;
+const MULTIPLIER_COMMA: u8 = 2;
+
+#[derive(Archive, Deserialize, Serialize)]
+#[rkyv(archived = ArchivedComponent)]
+pub struct ComponentRepr {
+    kind: u8,
+    data_type: u8,
+    name: String,
+    multiplier: u8,
+}
+
+#[derive(Archive, Deserialize, Serialize)]
+#[rkyv(archived = ArchivedDescriptor)]
+pub struct DescriptorRepr {
+    components: Vec<ComponentRepr>,
+}
+
+impl ArchivedComponent {
+    /// Returns the data type, if this is a known data type name.
+    pub fn data_type(&self) -> Option<DataType> {
+        if self.kind != NAME_DATA_TYPE {
+            return None;
+        }
+        data_type_from_code(self.data_type)
+    }
+
+    /// Returns the identifier, if this is an identifier.
+    pub fn ident(&self) -> Option<&str> {
+        if self.kind != NAME_IDENT {
+            return None;
+        }
+        Some(&self.name)
+    }
+
+    /// Returns the name of the data type, if this is an unknown data type.
+    pub fn unknown_data_type(&self) -> Option<&str> {
+        if self.kind != NAME_UNKNOWN_DATA_TYPE {
+            return None;
+        }
+        Some(&self.name)
+    }
+
+    /// Returns the multiplier of this component.
+    pub fn multiplier(&self) -> Option<Multiplier> {
+        match self.multiplier {
+            MULTIPLIER_SPACE => Some(Multiplier::Space),
+            MULTIPLIER_COMMA => Some(Multiplier::Comma),
+            _ => None,
+        }
+    }
+
+    fn to_component(&self) -> Result<Component<DefaultImpl>, DecodeError> {
+        let name = match self.kind {
+            NAME_DATA_TYPE => ComponentName::DataType(
+                self.data_type().ok_or(DecodeError::InvalidDataType(self.data_type))?,
+            ),
+            NAME_IDENT => ComponentName::Ident(CustomIdent::from_ident(&self.name).ok_or_else(
+                || DecodeError::Invalid(BuildError::InvalidCustomIdent(self.name.as_str().into())),
+            )?),
+            NAME_UNKNOWN_DATA_TYPE => ComponentName::UnknownDataType(self.name.as_str().into()),
+            kind => return Err(DecodeError::InvalidTag(kind)),
+        };
+        let multiplier = match self.multiplier {
+            MULTIPLIER_NONE | MULTIPLIER_SPACE | MULTIPLIER_COMMA => self.multiplier(),
+            other => return Err(DecodeError::InvalidTag(other)),
+        };
+        Component::new(name, multiplier).map_err(DecodeError::Invalid)
+    }
+}
+
+impl ArchivedDescriptor {
+    /// Validates the structure of `bytes`, and returns the archived
+    /// descriptor they contain, without copying.
+    pub fn access(bytes: &[u8]) -> Result<&Self, rancor::Error> {
+        rkyv::access::<Self, rancor::Error>(bytes)
+    }
+
+    /// Returns the archived components.
+    #[inline]
+    pub fn components(&self) -> &[ArchivedComponent] {
+        &self.components
+    }
+
+    /// Returns whether this is the universal syntax descriptor.
+    #[inline]
+    pub fn is_universal(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Converts this archived descriptor back into a `Descriptor`, checking
+    /// the same invariants as the parser.
+    pub fn to_descriptor(&self) -> Result<Descriptor<DefaultImpl>, DecodeError> {
+        let components = self
+            .components
+            .iter()
+            .map(|c| c.to_component())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Descriptor(components))
+    }
+}
+
+impl Descriptor<DefaultImpl> {
+    /// Returns an archive of this descriptor, which can be accessed with
+    /// `ArchivedDescriptor::access`.
+    pub fn to_archive(&self) -> AlignedVec {
+        let repr = DescriptorRepr {
+            components: self
+                .iter()
+                .map(|component| {
+                    let (kind, data_type, name) = match component.name {
+                        ComponentName::DataType(ty) => (NAME_DATA_TYPE, data_type_code(ty), ""),
+                        ComponentName::Ident(ref ident) => (NAME_IDENT, 0, ident.as_str()),
+                        ComponentName::UnknownDataType(ref name) => {
+                            (NAME_UNKNOWN_DATA_TYPE, 0, &**name)
+                        }
+                    };
+                    ComponentRepr {
+                        kind,
+                        data_type,
+                        name: name.to_owned(),
+                        multiplier: match component.multiplier {
+                            None => MULTIPLIER_NONE,
+                            Some(Multiplier::Space) => MULTIPLIER_SPACE,
+                            Some(Multiplier::Comma) => MULTIPLIER_COMMA,
+                        },
+                    }
+                })
+                .collect(),
+        };
+        rkyv::to_bytes::<rancor::Error>(&repr).expect("Serializing to memory can't fail")
+    }
+}
+
+#[test]
+fn archive_test() {
+    use super::parse_descriptor;
+
+    let descriptor = parse_descriptor("<length># | auto | <transform-list>").unwrap();
+    let bytes = descriptor.to_archive();
+    let archived = ArchivedDescriptor::access(&bytes).unwrap();
+    assert_eq!(archived.components().len(), 3);
+    assert_eq!(archived.components()[0].data_type(), Some(DataType::Length));
+    assert_eq!(archived.components()[0].multiplier(), Some(Multiplier::Comma));
+    assert_eq!(archived.components()[1].ident(), Some("auto"));
+    assert_eq!(archived.components()[1].data_type(), None);
+    assert_eq!(archived.to_descriptor(), Ok(descriptor));
+
+    let universal = parse_descriptor("*").unwrap().to_archive();
+    assert!(ArchivedDescriptor::access(&universal).unwrap().is_universal());
+
+    assert!(ArchivedDescriptor::access(&bytes[..bytes.len() - 1]).is_err());
+}
@@ -0,0 +1,371 @@
+//! A compact, versioned binary encoding of syntax descriptors.
+//!
+//! The format is:
+//!
+//!  * One byte with the format version, currently `1`.
+//!  * The number of components, as an unsigned LEB128 integer.
+//!  * For each component, a tag byte, whose two lowest bits are the kind of
+//!    name (`0` for a data type, `1` for an identifier, `2` for an unknown
+//!    data type), and the next two bits are the multiplier (`0` for none,
+//!    `1` for `+`, `2` for `#`). Data types follow as a single byte code,
+//!    and identifiers and unknown data type names as a LEB128 length and
+//!    their UTF-8 bytes.
+//!
+//! The universal descriptor is encoded with zero components.
+//!
+//! On top of that, the cache format produced by `Descriptor::encode_cached`
+//! wraps that encoding as follows, so that blobs persisted by an older version
+//! of the crate, or corrupted, are rejected rather than misinterpreted:
+//!
+//!  * The magic bytes `CTOS`.
+//!  * The length of the version of this crate that encoded the blob, as one
+//!    byte, followed by the version itself.
+//!  * The binary encoding of the descriptor.
+//!  * A 32-bit FNV-1a checksum of all the preceding bytes, in little endian.
+
+use std::fmt;
+
+use super::{
+    BuildError, Component, ComponentName, CustomIdent, DataType, DefaultImpl, Descriptor,
+    Multiplier,
+};
+
+/// The current version of the binary format.
+pub const BINARY_FORMAT_VERSION: u8 = 1;
+
+/// An error decoding a binary-encoded descriptor.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeError {
+    /// The data was encoded with an unsupported version of the format.
+    UnsupportedVersion(u8),
+    /// The data ended prematurely.
+    UnexpectedEnd,
+    /// There was data left after the descriptor.
+    TrailingBytes,
+    /// A length or count didn't fit in a `usize`.
+    Overflow,
+    /// A component had an invalid tag byte.
+    InvalidTag(u8),
+    /// A data type code wasn't known.
+    InvalidDataType(u8),
+    /// A string wasn't valid UTF-8.
+    InvalidUtf8,
+    /// A decoded component or identifier was invalid.
+    Invalid(BuildError),
+    /// The data didn't start with the magic bytes of the cache format.
+    InvalidMagic,
+    /// The cached data was encoded by a different version of this crate.
+    StaleCache,
+    /// The checksum of the cached data didn't match.
+    ChecksumMismatch,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            DecodeError::UnexpectedEnd => f.write_str("unexpected end of data"),
+            DecodeError::TrailingBytes => f.write_str("trailing bytes after descriptor"),
+            DecodeError::Overflow => f.write_str("length out of range"),
+            DecodeError::InvalidTag(tag) => write!(f, "invalid component tag {:#x}", tag),
+            DecodeError::InvalidDataType(code) => write!(f, "invalid data type code {}", code),
+            DecodeError::InvalidUtf8 => f.write_str("invalid UTF-8 string"),
+            DecodeError::Invalid(ref e) => e.fmt(f),
+            DecodeError::InvalidMagic => f.write_str("not a cached descriptor"),
+            DecodeError::StaleCache => {
+                f.write_str("cached descriptor was encoded by another version of the crate")
+            }
+            DecodeError::ChecksumMismatch => f.write_str("checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+pub(crate) const NAME_DATA_TYPE: u8 = 0;
+pub(crate) const NAME_IDENT: u8 = 1;
+pub(crate) const NAME_UNKNOWN_DATA_TYPE: u8 = 2;
+
+const MULTIPLIER_SHIFT: u8 = 2;
+const MULTIPLIER_SPACE: u8 = 1;
+const MULTIPLIER_COMMA: u8 = 2;
+
+/// These codes are part of the format, so new data types must get new codes.
+pub(crate) fn data_type_code(ty: DataType) -> u8 {
+    match ty {
+        DataType::Length => 0,
+        DataType::Number => 1,
+        DataType::Percentage => 2,
+        DataType::LengthPercentage => 3,
+        DataType::Color => 4,
+        DataType::Image => 5,
+        DataType::Url => 6,
+        DataType::Integer => 7,
+        DataType::Angle => 8,
+        DataType::Time => 9,
+        DataType::Resolution => 10,
+        DataType::TransformFunction => 11,
+        DataType::TransformList => 12,
+        DataType::CustomIdent => 13,
+        #[cfg(feature = "experimental-position")]
+        DataType::Position => 14,
+    }
+}
+
+pub(crate) fn data_type_from_code(code: u8) -> Option<DataType> {
+    DataType::ALL.iter().cloned().find(|ty| data_type_code(*ty) == code)
+}
+
+fn write_varint(mut value: usize, dest: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            dest.push(byte);
+            return;
+        }
+        dest.push(byte | 0x80);
+    }
+}
+
+fn write_str(s: &str, dest: &mut Vec<u8>) {
+    write_varint(s.len(), dest);
+    dest.extend_from_slice(s.as_bytes());
+}
+
+const CACHE_MAGIC: &[u8; 4] = b"CTOS";
+const CACHE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function
+fn fnv1a(bytes: &[u8]) -> u32 {
+    let mut hash = 0x811c_9dc5u32;
+    for byte in bytes {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let (first, rest) = self.bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+        self.bytes = rest;
+        Ok(*first)
+    }
+
+    fn varint(&mut self) -> Result<usize, DecodeError> {
+        let mut value = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7f) as usize;
+            if shift >= usize::BITS || (bits << shift) >> shift != bits {
+                return Err(DecodeError::Overflow);
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn str(&mut self) -> Result<&'a str, DecodeError> {
+        let len = self.varint()?;
+        if len > self.bytes.len() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (s, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        std::str::from_utf8(s).map_err(|_| DecodeError::InvalidUtf8)
+    }
+
+    fn component(&mut self) -> Result<Component<DefaultImpl>, DecodeError> {
+        let tag = self.byte()?;
+        let multiplier = match tag >> MULTIPLIER_SHIFT {
+            0 => None,
+            MULTIPLIER_SPACE => Some(Multiplier::Space),
+            MULTIPLIER_COMMA => Some(Multiplier::Comma),
+            _ => return Err(DecodeError::InvalidTag(tag)),
+        };
+        let name = match tag & ((1 << MULTIPLIER_SHIFT) - 1) {
+            NAME_DATA_TYPE => {
+                let code = self.byte()?;
+                let ty = data_type_from_code(code).ok_or(DecodeError::InvalidDataType(code))?;
+                ComponentName::DataType(ty)
+            }
+            NAME_IDENT => {
+                let ident = self.str()?;
+                let ident = CustomIdent::from_ident(ident).ok_or_else(|| {
+                    DecodeError::Invalid(BuildError::InvalidCustomIdent(ident.into()))
+                })?;
+                ComponentName::Ident(ident)
+            }
+            NAME_UNKNOWN_DATA_TYPE => ComponentName::UnknownDataType(self.str()?.into()),
+            _ => return Err(DecodeError::InvalidTag(tag)),
+        };
+        Component::new(name, multiplier).map_err(DecodeError::Invalid)
+    }
+}
+
+impl Descriptor<DefaultImpl> {
+    /// Appends the binary encoding of this descriptor to `dest`.
+    pub fn encode(&self, dest: &mut Vec<u8>) {
+        dest.push(BINARY_FORMAT_VERSION);
+        write_varint(self.len(), dest);
+        for component in self.iter() {
+            let multiplier = match component.multiplier {
+                None => 0,
+                Some(Multiplier::Space) => MULTIPLIER_SPACE,
+                Some(Multiplier::Comma) => MULTIPLIER_COMMA,
+            } << MULTIPLIER_SHIFT;
+            match component.name {
+                ComponentName::DataType(ty) => {
+                    dest.push(NAME_DATA_TYPE | multiplier);
+                    dest.push(data_type_code(ty));
+                }
+                ComponentName::Ident(ref ident) => {
+                    dest.push(NAME_IDENT | multiplier);
+                    write_str(ident, dest);
+                }
+                ComponentName::UnknownDataType(ref name) => {
+                    dest.push(NAME_UNKNOWN_DATA_TYPE | multiplier);
+                    write_str(name, dest);
+                }
+            }
+        }
+    }
+
+    /// Returns the binary encoding of this descriptor.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.encode(&mut bytes);
+        bytes
+    }
+
+    /// Decodes a descriptor from its binary encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes };
+        let version = reader.byte()?;
+        if version != BINARY_FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let count = reader.varint()?;
+        // Each component takes at least two bytes, so don't trust the count
+        // for the initial allocation.
+        let mut components = Vec::with_capacity(count.min(reader.bytes.len() / 2));
+        for _ in 0..count {
+            components.push(reader.component()?);
+        }
+        if !reader.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        Ok(Descriptor(components))
+    }
+
+    /// Returns the encoding of this descriptor in the cache format, which is
+    /// tagged with the version of this crate and checksummed.
+    pub fn encode_cached(&self) -> Vec<u8> {
+        let mut bytes = CACHE_MAGIC.to_vec();
+        bytes.push(CACHE_VERSION.len() as u8);
+        bytes.extend_from_slice(CACHE_VERSION.as_bytes());
+        self.encode(&mut bytes);
+        let checksum = fnv1a(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a descriptor encoded with `encode_cached`, rejecting data that
+    /// is corrupt or was encoded by another version of this crate.
+    pub fn decode_cached(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() < CACHE_MAGIC.len() + 4 {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        if !bytes.starts_with(CACHE_MAGIC) {
+            return Err(DecodeError::InvalidMagic);
+        }
+        let (data, checksum) = bytes.split_at(bytes.len() - 4);
+        let checksum = u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        if fnv1a(data) != checksum {
+            return Err(DecodeError::ChecksumMismatch);
+        }
+        let mut reader = Reader {
+            bytes: &data[CACHE_MAGIC.len()..],
+        };
+        let version_len = reader.byte()? as usize;
+        if reader.bytes.get(..version_len) != Some(CACHE_VERSION.as_bytes()) {
+            return Err(DecodeError::StaleCache);
+        }
+        Self::from_bytes(&reader.bytes[version_len..])
+    }
+}
+
+#[test]
+fn binary_test() {
+    use super::{parse_descriptor, parse_descriptor_with_options, ParseOptions};
+
+    for syntax in &["*", "<length>", "foo | <length># | \\31 bar+ | <transform-list>"] {
+        let descriptor = parse_descriptor(syntax).unwrap();
+        let bytes = descriptor.to_bytes();
+        assert_eq!(Descriptor::from_bytes(&bytes), Ok(descriptor));
+    }
+    let unknown = parse_descriptor_with_options("<foo>#", &ParseOptions::compat()).unwrap();
+    assert_eq!(Descriptor::from_bytes(&unknown.to_bytes()), Ok(unknown));
+
+    assert_eq!(parse_descriptor("<length>#").unwrap().to_bytes(), [1, 1, 0b1000, 0]);
+    assert_eq!(parse_descriptor("*").unwrap().to_bytes(), [1, 0]);
+
+    assert_eq!(Descriptor::from_bytes(&[]), Err(DecodeError::UnexpectedEnd));
+    assert_eq!(Descriptor::from_bytes(&[2, 0]), Err(DecodeError::UnsupportedVersion(2)));
+    assert_eq!(Descriptor::from_bytes(&[1, 0, 0]), Err(DecodeError::TrailingBytes));
+    assert_eq!(Descriptor::from_bytes(&[1, 1, 0, 99]), Err(DecodeError::InvalidDataType(99)));
+    assert_eq!(Descriptor::from_bytes(&[1, 1, 0b1100, 0]), Err(DecodeError::InvalidTag(0b1100)));
+    assert_eq!(
+        Descriptor::from_bytes(&[1, 1, 0b0100, 12]),
+        Err(DecodeError::Invalid(BuildError::MultiplierOnPremultipliedType)),
+    );
+    assert_eq!(
+        Descriptor::from_bytes(&[1, 1, 1, 7, b'i', b'n', b'h', b'e', b'r', b'i', b't']),
+        Err(DecodeError::Invalid(BuildError::InvalidCustomIdent("inherit".into()))),
+    );
+    assert_eq!(Descriptor::from_bytes(&[1, 1, 1, 5, b'a']), Err(DecodeError::UnexpectedEnd));
+    let mut too_long = vec![1];
+    too_long.extend_from_slice(&[0xff; 10]);
+    too_long.push(0x7f);
+    assert_eq!(Descriptor::from_bytes(&too_long), Err(DecodeError::Overflow));
+}
+
+#[test]
+fn cache_test() {
+    use super::parse_descriptor;
+
+    let descriptor = parse_descriptor("<length># | auto").unwrap();
+    let cached = descriptor.encode_cached();
+    assert!(cached.starts_with(b"CTOS"));
+    assert_eq!(Descriptor::decode_cached(&cached), Ok(descriptor.clone()));
+
+    let mut corrupt = cached.clone();
+    let last_payload_byte = corrupt.len() - 5;
+    corrupt[last_payload_byte] ^= 1;
+    assert_eq!(Descriptor::decode_cached(&corrupt), Err(DecodeError::ChecksumMismatch));
+
+    assert_eq!(Descriptor::decode_cached(b"CTOS"), Err(DecodeError::UnexpectedEnd));
+    assert_eq!(
+        Descriptor::decode_cached(&descriptor.to_bytes()),
+        Err(DecodeError::InvalidMagic),
+    );
+    assert_eq!(Descriptor::decode_cached(b"nope, not a cache"), Err(DecodeError::InvalidMagic));
+
+    // Re-checksum a blob claiming to be from another version.
+    let mut stale = b"CTOS".to_vec();
+    stale.push(5);
+    stale.extend_from_slice(b"0.0.1");
+    descriptor.encode(&mut stale);
+    let checksum = fnv1a(&stale);
+    stale.extend_from_slice(&checksum.to_le_bytes());
+    assert_eq!(Descriptor::decode_cached(&stale), Err(DecodeError::StaleCache));
+}
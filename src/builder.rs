@@ -0,0 +1,195 @@
+use std::fmt;
+
+use super::{Component, ComponentName, Descriptor, Impl, Multiplier};
+
+/// An error that makes a programmatically-built descriptor or component
+/// invalid.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuildError {
+    /// No component was added, and the descriptor wasn't marked as universal.
+    Empty,
+    /// The universal descriptor was combined with other components.
+    UniversalNotAlone,
+    /// A multiplier was given without a component to apply it to.
+    MultiplierWithoutComponent,
+    /// A multiplier was given for a component that already had one.
+    DuplicateMultiplier,
+    /// A multiplier was given for a pre-multiplied data type name.
+    MultiplierOnPremultipliedType,
+    /// The given string isn't a valid custom identifier.
+    InvalidCustomIdent(Box<str>),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BuildError::Empty => f.write_str("syntax descriptor has no components"),
+            BuildError::UniversalNotAlone => f.write_str(
+                "the universal syntax descriptor '*' can't be combined with other components",
+            ),
+            BuildError::MultiplierWithoutComponent => {
+                f.write_str("multiplier given without a syntax component")
+            }
+            BuildError::DuplicateMultiplier => {
+                f.write_str("syntax component already has a multiplier")
+            }
+            BuildError::MultiplierOnPremultipliedType => {
+                f.write_str("pre-multiplied data type names can't have a multiplier")
+            }
+            BuildError::InvalidCustomIdent(ref ident) => {
+                write!(f, "'{}' is not a valid custom identifier", ident)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// A builder to construct syntax descriptors component by component, with
+/// the same invariants the parser enforces.
+///
+/// Errors are deferred until `build()`, so calls can be chained. The first
+/// error found is the one reported.
+#[derive(Debug)]
+pub struct DescriptorBuilder<I: Impl> {
+    components: Vec<Component<I>>,
+    universal: bool,
+    error: Option<BuildError>,
+}
+
+impl<I: Impl> Default for DescriptorBuilder<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: Impl> DescriptorBuilder<I> {
+    /// Creates a builder with no components.
+    pub fn new() -> Self {
+        Self {
+            components: vec![],
+            universal: false,
+            error: None,
+        }
+    }
+
+    fn fail(&mut self, error: BuildError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    /// Marks the descriptor as the universal syntax descriptor, `*`.
+    pub fn universal(mut self) -> Self {
+        self.universal = true;
+        self
+    }
+
+    /// Adds a component with the given name.
+    pub fn component(mut self, name: ComponentName<I>) -> Self {
+        self.components.push(Component {
+            name,
+            multiplier: None,
+        });
+        self
+    }
+
+    /// Adds a data type name component.
+    pub fn data_type(self, data_type: I::DataType) -> Self {
+        self.component(ComponentName::DataType(data_type))
+    }
+
+    /// Adds an identifier component.
+    pub fn ident(mut self, ident: &str) -> Self {
+        match I::custom_ident_from_ident(ident) {
+            Some(ident) => self.component(ComponentName::Ident(ident)),
+            None => {
+                self.fail(BuildError::InvalidCustomIdent(ident.into()));
+                self
+            }
+        }
+    }
+
+    /// Sets the multiplier of the last added component.
+    pub fn multiplier(mut self, multiplier: Multiplier) -> Self {
+        let error = match self.components.last_mut() {
+            None => Some(BuildError::MultiplierWithoutComponent),
+            Some(ref c) if c.multiplier.is_some() => Some(BuildError::DuplicateMultiplier),
+            Some(ref c) if c.name.is_pre_multiplied() => {
+                Some(BuildError::MultiplierOnPremultipliedType)
+            }
+            Some(c) => {
+                c.multiplier = Some(multiplier);
+                None
+            }
+        };
+        if let Some(error) = error {
+            self.fail(error);
+        }
+        self
+    }
+
+    /// Builds the descriptor, or returns the first error found.
+    pub fn build(self) -> Result<Descriptor<I>, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.universal {
+            if !self.components.is_empty() {
+                return Err(BuildError::UniversalNotAlone);
+            }
+            return Ok(Descriptor::universal());
+        }
+        if self.components.is_empty() {
+            return Err(BuildError::Empty);
+        }
+        Ok(Descriptor(self.components))
+    }
+}
+
+#[test]
+fn builder_test() {
+    use super::{parse_descriptor, DataType, DefaultImpl};
+
+    fn builder() -> DescriptorBuilder<DefaultImpl> {
+        DescriptorBuilder::new()
+    }
+
+    assert_eq!(
+        builder()
+            .data_type(DataType::Length)
+            .multiplier(Multiplier::Comma)
+            .ident("auto")
+            .build(),
+        parse_descriptor("<length># | auto").map_err(|_| BuildError::Empty),
+    );
+    assert_eq!(builder().universal().build(), Ok(Descriptor::universal()));
+    assert_eq!(builder().build(), Err(BuildError::Empty));
+    assert_eq!(
+        builder().universal().ident("auto").build(),
+        Err(BuildError::UniversalNotAlone),
+    );
+    assert_eq!(
+        builder().multiplier(Multiplier::Space).build(),
+        Err(BuildError::MultiplierWithoutComponent),
+    );
+    assert_eq!(
+        builder()
+            .data_type(DataType::Length)
+            .multiplier(Multiplier::Space)
+            .multiplier(Multiplier::Comma)
+            .build(),
+        Err(BuildError::DuplicateMultiplier),
+    );
+    assert_eq!(
+        builder()
+            .data_type(DataType::TransformList)
+            .multiplier(Multiplier::Space)
+            .build(),
+        Err(BuildError::MultiplierOnPremultipliedType),
+    );
+    assert_eq!(
+        builder().ident("inherit").ident("unset").build(),
+        Err(BuildError::InvalidCustomIdent("inherit".into())),
+    );
+}
@@ -0,0 +1,726 @@
+//! Math functions in values.
+//!
+//! https://drafts.csswg.org/css-values-4/#math
+
+use std::fmt;
+
+use cssparser::{Parser, Token};
+
+use super::matching::Error;
+use super::typed_om::{BaseType, CssUnit, NumericType};
+use super::units::{AngleUnit, LengthUnit, ResolutionUnit, TimeUnit};
+use super::value::{write_dimension, write_number, write_percentage};
+
+/// A node of a math expression, like the argument of a `calc()` function.
+///
+/// https://drafts.csswg.org/css-values-4/#calc-syntax
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum CalcNode {
+    Number(f64),
+    /// A percentage, where `1.0` is `100%`.
+    Percentage(f64),
+    Length(f64, LengthUnit),
+    Angle(f64, AngleUnit),
+    Time(f64, TimeUnit),
+    Resolution(f64, ResolutionUnit),
+    /// The sum of the nodes. Subtraction is represented as the sum of a
+    /// `Negate` node.
+    Sum(Vec<CalcNode>),
+    /// The product of the nodes. Division is represented as the product of
+    /// an `Invert` node.
+    Product(Vec<CalcNode>),
+    Negate(Box<CalcNode>),
+    Invert(Box<CalcNode>),
+    /// A math function other than `calc()`, with its arguments.
+    Function(MathFunction, Vec<CalcNode>),
+}
+
+/// https://drafts.csswg.org/css-values-4/#round-func
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RoundingStrategy {
+    Nearest,
+    Up,
+    Down,
+    ToZero,
+}
+
+impl RoundingStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            RoundingStrategy::Nearest => "nearest",
+            RoundingStrategy::Up => "up",
+            RoundingStrategy::Down => "down",
+            RoundingStrategy::ToZero => "to-zero",
+        }
+    }
+
+    fn from_ident(ident: &str) -> Option<Self> {
+        match_ignore_ascii_case(ident, &[
+            ("nearest", RoundingStrategy::Nearest),
+            ("up", RoundingStrategy::Up),
+            ("down", RoundingStrategy::Down),
+            ("to-zero", RoundingStrategy::ToZero),
+        ])
+    }
+}
+
+/// A math function other than `calc()`.
+///
+/// https://drafts.csswg.org/css-values-4/#math-function
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MathFunction {
+    Min,
+    Max,
+    Clamp,
+    Round(RoundingStrategy),
+    Mod,
+    Rem,
+    Abs,
+    Sign,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Pow,
+    Sqrt,
+    Hypot,
+    Log,
+    Exp,
+}
+
+impl MathFunction {
+    fn from_name(name: &str) -> Option<Self> {
+        match_ignore_ascii_case(name, &[
+            ("min", MathFunction::Min),
+            ("max", MathFunction::Max),
+            ("clamp", MathFunction::Clamp),
+            ("round", MathFunction::Round(RoundingStrategy::Nearest)),
+            ("mod", MathFunction::Mod),
+            ("rem", MathFunction::Rem),
+            ("abs", MathFunction::Abs),
+            ("sign", MathFunction::Sign),
+            ("sin", MathFunction::Sin),
+            ("cos", MathFunction::Cos),
+            ("tan", MathFunction::Tan),
+            ("asin", MathFunction::Asin),
+            ("acos", MathFunction::Acos),
+            ("atan", MathFunction::Atan),
+            ("atan2", MathFunction::Atan2),
+            ("pow", MathFunction::Pow),
+            ("sqrt", MathFunction::Sqrt),
+            ("hypot", MathFunction::Hypot),
+            ("log", MathFunction::Log),
+            ("exp", MathFunction::Exp),
+        ])
+    }
+
+    /// Returns the name of the function.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MathFunction::Min => "min",
+            MathFunction::Max => "max",
+            MathFunction::Clamp => "clamp",
+            MathFunction::Round(..) => "round",
+            MathFunction::Mod => "mod",
+            MathFunction::Rem => "rem",
+            MathFunction::Abs => "abs",
+            MathFunction::Sign => "sign",
+            MathFunction::Sin => "sin",
+            MathFunction::Cos => "cos",
+            MathFunction::Tan => "tan",
+            MathFunction::Asin => "asin",
+            MathFunction::Acos => "acos",
+            MathFunction::Atan => "atan",
+            MathFunction::Atan2 => "atan2",
+            MathFunction::Pow => "pow",
+            MathFunction::Sqrt => "sqrt",
+            MathFunction::Hypot => "hypot",
+            MathFunction::Log => "log",
+            MathFunction::Exp => "exp",
+        }
+    }
+
+    /// Returns the minimum and maximum number of arguments of the function.
+    fn arity(&self) -> (usize, usize) {
+        match *self {
+            MathFunction::Min | MathFunction::Max | MathFunction::Hypot => (1, usize::MAX),
+            MathFunction::Clamp => (3, 3),
+            MathFunction::Round(..) | MathFunction::Log => (1, 2),
+            MathFunction::Mod | MathFunction::Rem | MathFunction::Atan2 | MathFunction::Pow => (2, 2),
+            MathFunction::Abs |
+            MathFunction::Sign |
+            MathFunction::Sin |
+            MathFunction::Cos |
+            MathFunction::Tan |
+            MathFunction::Asin |
+            MathFunction::Acos |
+            MathFunction::Atan |
+            MathFunction::Sqrt |
+            MathFunction::Exp => (1, 1),
+        }
+    }
+}
+
+/// Returns the value associated with `name` in `values`, ASCII
+/// case-insensitively.
+fn match_ignore_ascii_case<T: Copy>(name: &str, values: &[(&str, T)]) -> Option<T> {
+    values.iter().find(|v| name.eq_ignore_ascii_case(v.0)).map(|v| v.1)
+}
+
+/// What a math expression resolves to.
+///
+/// https://drafts.csswg.org/css-values-4/#calc-type-checking
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CalcType {
+    Number,
+    Percentage,
+    Length,
+    /// A mix of lengths and percentages, like `calc(100% - 10px)`.
+    LengthPercentage,
+    Angle,
+    Time,
+    Resolution,
+}
+
+impl CalcType {
+    /// Returns what an expression of the given numeric type resolves to, if
+    /// it's one of the types we support.
+    ///
+    /// https://drafts.csswg.org/css-values-4/#css-determine-the-type-of-a-calculation
+    pub fn from_numeric_type(ty: NumericType) -> Option<Self> {
+        if ty.is_number() {
+            return Some(CalcType::Number);
+        }
+        if ty.matches(BaseType::Length, true) && ty.percent_hint().is_some() {
+            return Some(CalcType::LengthPercentage);
+        }
+        let base = *BaseType::ALL.iter().find(|&&base| ty.matches(base, false))?;
+        Some(match base {
+            BaseType::Length => CalcType::Length,
+            BaseType::Percent => CalcType::Percentage,
+            BaseType::Angle => CalcType::Angle,
+            BaseType::Time => CalcType::Time,
+            BaseType::Resolution => CalcType::Resolution,
+            BaseType::Frequency | BaseType::Flex => return None,
+        })
+    }
+}
+
+/// Returns whether `name` is the name of a math function we support.
+pub(crate) fn is_math_function(name: &str) -> bool {
+    name.eq_ignore_ascii_case("calc") || MathFunction::from_name(name).is_some()
+}
+
+impl CalcNode {
+    /// Parses the arguments of the math function `name`, whose opening token
+    /// has just been consumed.
+    pub(crate) fn parse_function<'i, 't>(name: &str, input: &mut Parser<'i, 't>) -> Result<Self, Error<'i>> {
+        debug_assert!(is_math_function(name));
+        let function = match MathFunction::from_name(name) {
+            Some(function) => function,
+            None => return input.parse_nested_block(Self::parse_sum),
+        };
+        input.parse_nested_block(|input| {
+            let mut function = function;
+            if let MathFunction::Round(ref mut strategy) = function {
+                let parsed = input.try_parse(|input| -> Result<_, Error<'i>> {
+                    let location = input.current_source_location();
+                    let ident = input.expect_ident()?;
+                    let strategy = RoundingStrategy::from_ident(ident)
+                        .ok_or_else(|| location.new_unexpected_token_error(Token::Ident(ident.clone())))?;
+                    input.expect_comma()?;
+                    Ok(strategy)
+                });
+                if let Ok(parsed) = parsed {
+                    *strategy = parsed;
+                }
+            }
+            let location = input.current_source_location();
+            let arguments = input.parse_comma_separated(Self::parse_sum)?;
+            let (min, max) = function.arity();
+            if arguments.len() < min || arguments.len() > max {
+                return Err(location.new_custom_error(()));
+            }
+            Ok(CalcNode::Function(function, arguments))
+        })
+    }
+
+    /// https://drafts.csswg.org/css-values-4/#typedef-calc-sum
+    fn parse_sum<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, Error<'i>> {
+        let mut terms = vec![Self::parse_product(input)?];
+        loop {
+            let start = input.state();
+            // The operators must be surrounded by whitespace, but the
+            // tokenizer would have merged a sign without whitespace after it
+            // into the following number anyway.
+            match input.next_including_whitespace() {
+                Ok(&Token::WhiteSpace(..)) => {}
+                _ => {
+                    input.reset(&start);
+                    break;
+                }
+            }
+            let location = input.current_source_location();
+            let token = match input.next() {
+                Ok(token) => token,
+                // Trailing whitespace.
+                Err(..) => break,
+            };
+            match *token {
+                Token::Delim('+') => {
+                    input.expect_whitespace()?;
+                    terms.push(Self::parse_product(input)?);
+                }
+                Token::Delim('-') => {
+                    input.expect_whitespace()?;
+                    terms.push(CalcNode::Negate(Box::new(Self::parse_product(input)?)));
+                }
+                ref token => return Err(location.new_unexpected_token_error(token.clone())),
+            }
+        }
+        Ok(if terms.len() == 1 { terms.pop().unwrap() } else { CalcNode::Sum(terms) })
+    }
+
+    /// https://drafts.csswg.org/css-values-4/#typedef-calc-product
+    fn parse_product<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, Error<'i>> {
+        let mut factors = vec![Self::parse_value(input)?];
+        loop {
+            let start = input.state();
+            match input.next() {
+                Ok(&Token::Delim('*')) => factors.push(Self::parse_value(input)?),
+                Ok(&Token::Delim('/')) => {
+                    factors.push(CalcNode::Invert(Box::new(Self::parse_value(input)?)))
+                }
+                _ => {
+                    input.reset(&start);
+                    break;
+                }
+            }
+        }
+        Ok(if factors.len() == 1 { factors.pop().unwrap() } else { CalcNode::Product(factors) })
+    }
+
+    /// https://drafts.csswg.org/css-values-4/#typedef-calc-value
+    fn parse_value<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, Error<'i>> {
+        let location = input.current_source_location();
+        let token = input.next()?.clone();
+        let node = match token {
+            Token::Number { value, .. } => Some(CalcNode::Number(value.into())),
+            Token::Percentage { unit_value, .. } => Some(CalcNode::Percentage(unit_value.into())),
+            Token::Dimension { value, ref unit, .. } => Self::dimension(value.into(), unit),
+            Token::Ident(ref ident) => Self::constant(ident),
+            Token::ParenthesisBlock => return input.parse_nested_block(Self::parse_sum),
+            Token::Function(ref name) if is_math_function(name) => {
+                return Self::parse_function(name, input)
+            }
+            _ => None,
+        };
+        node.ok_or_else(|| location.new_unexpected_token_error(token))
+    }
+
+    /// Returns the value of a numeric constant, like `pi`.
+    ///
+    /// https://drafts.csswg.org/css-values-4/#calc-constants
+    fn constant(ident: &str) -> Option<Self> {
+        let value = match_ignore_ascii_case(ident, &[
+            ("e", std::f64::consts::E),
+            ("pi", std::f64::consts::PI),
+            ("infinity", f64::INFINITY),
+            ("-infinity", f64::NEG_INFINITY),
+            ("nan", f64::NAN),
+        ])?;
+        Some(CalcNode::Number(value))
+    }
+
+    /// Returns the node for a dimension with the given unit, if it's a unit
+    /// we know about.
+    fn dimension(value: f64, unit: &str) -> Option<Self> {
+        Some(if let Some(unit) = LengthUnit::from_str(unit) {
+            CalcNode::Length(value, unit)
+        } else if let Some(unit) = AngleUnit::from_str(unit) {
+            CalcNode::Angle(value, unit)
+        } else if let Some(unit) = TimeUnit::from_str(unit) {
+            CalcNode::Time(value, unit)
+        } else {
+            CalcNode::Resolution(value, ResolutionUnit::from_str(unit)?)
+        })
+    }
+
+    /// Returns what this expression resolves to, or `None` if it's not
+    /// valid, like `calc(1px + 1s)`, or resolves to a type we don't support,
+    /// like `calc(1px * 1px)`.
+    pub fn calc_type(&self) -> Option<CalcType> {
+        CalcType::from_numeric_type(self.numeric_type()?)
+    }
+
+    /// Returns the numeric type of this expression, or `None` if it's not
+    /// valid, like `calc(1px + 1s)`. Intermediate results can have any type,
+    /// so `calc(1px * 1px / 1px)` is a length.
+    ///
+    /// https://drafts.csswg.org/css-values-4/#determine-the-type-of-a-calculation
+    pub fn numeric_type(&self) -> Option<NumericType> {
+        let all = |nodes: &[CalcNode], combine: fn(NumericType, NumericType) -> Option<NumericType>| {
+            let mut ty = nodes[0].numeric_type()?;
+            for node in &nodes[1..] {
+                ty = combine(ty, node.numeric_type()?)?;
+            }
+            Some(ty)
+        };
+        let of_unit = |unit| Some(NumericType::of_unit(unit));
+        let number = NumericType::default();
+        let angle = NumericType::of_unit(CssUnit::Angle(AngleUnit::CANONICAL));
+        match *self {
+            CalcNode::Number(..) => Some(number),
+            CalcNode::Percentage(..) => of_unit(CssUnit::Percent),
+            CalcNode::Length(_, unit) => of_unit(CssUnit::Length(unit)),
+            CalcNode::Angle(_, unit) => of_unit(CssUnit::Angle(unit)),
+            CalcNode::Time(_, unit) => of_unit(CssUnit::Time(unit)),
+            CalcNode::Resolution(_, unit) => of_unit(CssUnit::Resolution(unit)),
+            CalcNode::Negate(ref node) => node.numeric_type(),
+            CalcNode::Invert(ref node) => Some(node.numeric_type()?.invert()),
+            CalcNode::Sum(ref terms) => all(terms, NumericType::add),
+            CalcNode::Product(ref factors) => all(factors, NumericType::multiply),
+            CalcNode::Function(function, ref arguments) => {
+                let ty = all(arguments, NumericType::add)?;
+                match function {
+                    MathFunction::Min |
+                    MathFunction::Max |
+                    MathFunction::Clamp |
+                    MathFunction::Mod |
+                    MathFunction::Rem |
+                    MathFunction::Abs |
+                    MathFunction::Hypot => Some(ty),
+                    // The step can only be omitted for numbers.
+                    MathFunction::Round(..) if arguments.len() == 1 && !ty.is_number() => None,
+                    MathFunction::Round(..) => Some(ty),
+                    MathFunction::Sign => Some(number),
+                    MathFunction::Sin | MathFunction::Cos | MathFunction::Tan => {
+                        if ty.is_number() || ty.matches(BaseType::Angle, false) {
+                            Some(number)
+                        } else {
+                            None
+                        }
+                    }
+                    MathFunction::Atan2 => Some(angle),
+                    MathFunction::Asin | MathFunction::Acos | MathFunction::Atan => {
+                        if ty.is_number() {
+                            Some(angle)
+                        } else {
+                            None
+                        }
+                    }
+                    MathFunction::Pow | MathFunction::Sqrt | MathFunction::Log | MathFunction::Exp => {
+                        if ty.is_number() {
+                            Some(number)
+                        } else {
+                            None
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the value of this expression, if it only involves numbers.
+    ///
+    /// https://drafts.csswg.org/css-values-4/#calc-simplification
+    pub fn to_number(&self) -> Option<f64> {
+        if self.calc_type()? != CalcType::Number {
+            return None;
+        }
+        self.to_canonical()
+    }
+
+    /// Returns the value of this expression in the canonical unit of its
+    /// type, if it doesn't involve relative lengths or percentages.
+    pub(crate) fn to_canonical(&self) -> Option<f64> {
+        self.calc_type()?;
+        self.evaluate(&mut |leaf| match *leaf {
+            CalcNode::Number(value) => Some(value),
+            CalcNode::Angle(value, unit) => Some(value * unit.to_canonical()),
+            CalcNode::Time(value, unit) => Some(value * unit.to_canonical()),
+            CalcNode::Resolution(value, unit) => Some(value * unit.to_canonical()),
+            CalcNode::Length(value, unit) => Some(value * unit.to_px()?),
+            _ => None,
+        })
+    }
+
+    /// Evaluates this expression, which must be valid, resolving its leaves
+    /// with `leaf`, which must return them in the canonical unit of their
+    /// type (`px`, `deg`, `s` or `dppx`).
+    ///
+    /// The result is in the canonical unit of the type of the expression.
+    pub(crate) fn evaluate(&self, leaf: &mut dyn FnMut(&CalcNode) -> Option<f64>) -> Option<f64> {
+        Some(match *self {
+            CalcNode::Number(..) |
+            CalcNode::Percentage(..) |
+            CalcNode::Length(..) |
+            CalcNode::Angle(..) |
+            CalcNode::Time(..) |
+            CalcNode::Resolution(..) => leaf(self)?,
+            CalcNode::Negate(ref node) => -node.evaluate(leaf)?,
+            CalcNode::Invert(ref node) => 1. / node.evaluate(leaf)?,
+            CalcNode::Sum(ref nodes) => {
+                let mut sum = 0.;
+                for node in nodes {
+                    sum += node.evaluate(leaf)?;
+                }
+                sum
+            }
+            CalcNode::Product(ref nodes) => {
+                let mut product = 1.;
+                for node in nodes {
+                    product *= node.evaluate(leaf)?;
+                }
+                product
+            }
+            CalcNode::Function(function, ref arguments) => {
+                let mut values = Vec::with_capacity(arguments.len());
+                for argument in arguments {
+                    values.push(argument.evaluate(leaf)?);
+                }
+                // Trigonometric functions take and return radians, but our
+                // canonical angle unit is degrees.
+                let is_angle = arguments[0].calc_type() == Some(CalcType::Angle);
+                let radians = |value: f64| if is_angle { value.to_radians() } else { value };
+                match function {
+                    MathFunction::Min => values.iter().cloned().fold(f64::INFINITY, f64::min),
+                    MathFunction::Max => values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+                    MathFunction::Clamp => values[0].max(values[1].min(values[2])),
+                    MathFunction::Round(strategy) => {
+                        let step = values.get(1).cloned().unwrap_or(1.);
+                        let ratio = values[0] / step;
+                        let rounded = match strategy {
+                            RoundingStrategy::Nearest => (ratio + 0.5).floor(),
+                            RoundingStrategy::Up => ratio.ceil(),
+                            RoundingStrategy::Down => ratio.floor(),
+                            RoundingStrategy::ToZero => ratio.trunc(),
+                        };
+                        rounded * step
+                    }
+                    MathFunction::Mod => values[0] - values[1] * (values[0] / values[1]).floor(),
+                    MathFunction::Rem => values[0] % values[1],
+                    MathFunction::Abs => values[0].abs(),
+                    MathFunction::Sign => {
+                        if values[0] == 0. || values[0].is_nan() {
+                            values[0]
+                        } else {
+                            values[0].signum()
+                        }
+                    }
+                    MathFunction::Sin => radians(values[0]).sin(),
+                    MathFunction::Cos => radians(values[0]).cos(),
+                    MathFunction::Tan => radians(values[0]).tan(),
+                    MathFunction::Asin => values[0].asin().to_degrees(),
+                    MathFunction::Acos => values[0].acos().to_degrees(),
+                    MathFunction::Atan => values[0].atan().to_degrees(),
+                    MathFunction::Atan2 => values[0].atan2(values[1]).to_degrees(),
+                    MathFunction::Pow => values[0].powf(values[1]),
+                    MathFunction::Sqrt => values[0].sqrt(),
+                    MathFunction::Hypot => values.iter().map(|v| v * v).sum::<f64>().sqrt(),
+                    MathFunction::Log => match values.get(1) {
+                        Some(base) => values[0].log(*base),
+                        None => values[0].ln(),
+                    },
+                    MathFunction::Exp => values[0].exp(),
+                }
+            }
+        })
+    }
+
+    /// Serializes this expression, wrapped in a `calc()` unless it's another
+    /// math function. The expression isn't simplified first.
+    ///
+    /// https://drafts.csswg.org/css-values-4/#calc-simplification
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        if let CalcNode::Function(..) = *self {
+            return self.write_expression(dest);
+        }
+        dest.write_str("calc(")?;
+        self.write_expression(dest)?;
+        dest.write_char(')')
+    }
+
+    /// Serializes this expression as an operand of a sum or, if
+    /// `in_product`, of a product, with parentheses if needed.
+    fn write_operand<W: fmt::Write>(&self, in_product: bool, dest: &mut W) -> fmt::Result {
+        let parenthesize = match *self {
+            CalcNode::Sum(..) => true,
+            CalcNode::Product(..) => in_product,
+            _ => false,
+        };
+        if !parenthesize {
+            return self.write_expression(dest);
+        }
+        dest.write_char('(')?;
+        self.write_expression(dest)?;
+        dest.write_char(')')
+    }
+
+    /// Serializes this expression without a surrounding `calc()`.
+    fn write_expression<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            CalcNode::Number(value) => write_number(value, dest),
+            CalcNode::Percentage(value) => write_percentage(value, dest),
+            CalcNode::Length(value, unit) => write_dimension(value, unit.as_str(), dest),
+            CalcNode::Angle(value, unit) => write_dimension(value, unit.as_str(), dest),
+            CalcNode::Time(value, unit) => write_dimension(value, unit.as_str(), dest),
+            CalcNode::Resolution(value, unit) => write_dimension(value, unit.as_str(), dest),
+            CalcNode::Sum(ref terms) => {
+                for (i, term) in terms.iter().enumerate() {
+                    match *term {
+                        CalcNode::Negate(ref term) if i > 0 => {
+                            dest.write_str(" - ")?;
+                            term.write_operand(false, dest)?;
+                        }
+                        _ => {
+                            if i > 0 {
+                                dest.write_str(" + ")?;
+                            }
+                            term.write_operand(false, dest)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            CalcNode::Product(ref factors) => {
+                for (i, factor) in factors.iter().enumerate() {
+                    match *factor {
+                        CalcNode::Invert(ref factor) if i > 0 => {
+                            dest.write_str(" / ")?;
+                            factor.write_operand(true, dest)?;
+                        }
+                        _ => {
+                            if i > 0 {
+                                dest.write_str(" * ")?;
+                            }
+                            factor.write_operand(true, dest)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            CalcNode::Negate(ref node) => {
+                dest.write_str("(-1 * ")?;
+                node.write_operand(true, dest)?;
+                dest.write_char(')')
+            }
+            CalcNode::Invert(ref node) => {
+                dest.write_str("(1 / ")?;
+                node.write_operand(true, dest)?;
+                dest.write_char(')')
+            }
+            CalcNode::Function(function, ref arguments) => {
+                dest.write_str(function.name())?;
+                dest.write_char('(')?;
+                if let MathFunction::Round(strategy) = function {
+                    if strategy != RoundingStrategy::Nearest {
+                        dest.write_str(strategy.as_str())?;
+                        dest.write_str(", ")?;
+                    }
+                }
+                for (i, argument) in arguments.iter().enumerate() {
+                    if i > 0 {
+                        dest.write_str(", ")?;
+                    }
+                    argument.write_expression(dest)?;
+                }
+                dest.write_char(')')
+            }
+        }
+    }
+
+    /// Returns whether this expression contains a percentage.
+    pub fn has_percentage(&self) -> bool {
+        match *self {
+            CalcNode::Percentage(..) => true,
+            CalcNode::Number(..) |
+            CalcNode::Length(..) |
+            CalcNode::Angle(..) |
+            CalcNode::Time(..) |
+            CalcNode::Resolution(..) => false,
+            CalcNode::Negate(ref node) | CalcNode::Invert(ref node) => node.has_percentage(),
+            CalcNode::Sum(ref nodes) | CalcNode::Product(ref nodes) | CalcNode::Function(_, ref nodes) => {
+                nodes.iter().any(CalcNode::has_percentage)
+            }
+        }
+    }
+}
+
+impl cssparser::ToCss for CalcNode {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        CalcNode::to_css(self, dest)
+    }
+}
+
+#[test]
+fn calc_test() {
+    fn parse(input: &str) -> Result<CalcNode, ()> {
+        let mut input = cssparser::ParserInput::new(input);
+        let mut input = Parser::new(&mut input);
+        input
+            .parse_entirely(|input| {
+                let name = input.expect_function()?.clone();
+                CalcNode::parse_function(&name, input)
+            })
+            .map_err(|_| ())
+    }
+
+    assert_eq!(
+        parse("calc(100% - 10px)"),
+        Ok(CalcNode::Sum(vec![
+            CalcNode::Percentage(1.),
+            CalcNode::Negate(Box::new(CalcNode::Length(10., LengthUnit::Px))),
+        ])),
+    );
+    assert_eq!(
+        parse("calc(2 * (1em + 1px) / 4)").unwrap().calc_type(),
+        Some(CalcType::Length),
+    );
+    assert_eq!(parse("calc(1px + 50%)").unwrap().calc_type(), Some(CalcType::LengthPercentage));
+    assert_eq!(parse("calc(1px + 1s)").unwrap().calc_type(), None);
+    assert_eq!(parse("calc(1px * 1px)").unwrap().calc_type(), None);
+    assert_eq!(parse("calc(1 / 1px)").unwrap().calc_type(), None);
+    assert_eq!(parse("calc(1px * 2px / 4px)").unwrap().calc_type(), Some(CalcType::Length));
+    assert_eq!(parse("calc(10px / 5px)").unwrap().to_number(), Some(2.));
+    assert_eq!(parse("calc(1s * 1 / 1ms)").unwrap().to_number(), Some(1000.));
+    assert_eq!(parse("calc(50% * 2px / 1px)").unwrap().calc_type(), Some(CalcType::Percentage));
+    assert_eq!(parse("calc(1px + 50% * 1s / 1s)").unwrap().calc_type(), Some(CalcType::LengthPercentage));
+    assert_eq!(parse("calc(1deg + 50%)").unwrap().calc_type(), None);
+    assert_eq!(parse("calc(1px * 1px)").unwrap().numeric_type().map(|ty| ty.length()), Some(2));
+    assert!(!parse("calc( 1px + 2px )").unwrap().has_percentage());
+    assert!(parse("calc(1px - calc(2 * 5%))").unwrap().has_percentage());
+    assert_eq!(parse("calc(1 + 2 * 3 / 4)").unwrap().to_number(), Some(2.5));
+    assert_eq!(parse("calc(1 - (2 - 3))").unwrap().to_number(), Some(2.));
+    assert_eq!(parse("calc(1px + 2px)").unwrap().to_number(), None);
+
+    assert_eq!(parse("min(1px, 2em)").unwrap().calc_type(), Some(CalcType::Length));
+    assert_eq!(parse("max(1rem, 2vw)").unwrap().calc_type(), Some(CalcType::Length));
+    assert_eq!(parse("clamp(1px, 50%, 10px)").unwrap().calc_type(), Some(CalcType::LengthPercentage));
+    assert_eq!(parse("max(1px, 2s)").unwrap().calc_type(), None);
+    assert_eq!(parse("min(3, 1, 2)").unwrap().to_number(), Some(1.));
+    assert_eq!(parse("clamp(1, 5, 3)").unwrap().to_number(), Some(3.));
+    assert_eq!(parse("round(2.5)").unwrap().to_number(), Some(3.));
+    assert_eq!(parse("round(down, 7, 2)").unwrap().to_number(), Some(6.));
+    assert_eq!(parse("round(to-zero, -7, 2)").unwrap().to_number(), Some(-6.));
+    assert_eq!(parse("round(10px)").unwrap().calc_type(), None);
+    assert_eq!(parse("mod(-7, 3)").unwrap().to_number(), Some(2.));
+    assert_eq!(parse("rem(-7, 3)").unwrap().to_number(), Some(-1.));
+    assert_eq!(parse("sign(-2px)").unwrap().to_number(), Some(-1.));
+    assert_eq!(parse("calc(sin(90deg) * pow(2, 3))").unwrap().to_number(), Some(8.));
+    assert_eq!(parse("atan2(1px, 1px)").unwrap().calc_type(), Some(CalcType::Angle));
+    assert_eq!(parse("hypot(3, 4)").unwrap().to_number(), Some(5.));
+    assert_eq!(parse("calc(-infinity)").unwrap().to_number(), Some(f64::NEG_INFINITY));
+    assert_eq!(parse("calc(1px * e)").unwrap().calc_type(), Some(CalcType::Length));
+    assert!(parse("clamp(1px, 2px)").is_err());
+    assert!(parse("round(sideways, 1, 2)").is_err());
+    assert!(parse("calc(foo)").is_err());
+    assert!(parse("calc(1px +2px)").is_err());
+    assert!(parse("calc(1px+ 2px)").is_err());
+    assert!(parse("calc(1px 2px)").is_err());
+    assert!(parse("calc()").is_err());
+}
@@ -0,0 +1,279 @@
+//! Generation of Rust value enums from descriptors, behind the `codegen`
+//! feature.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+
+use super::{ComponentName, DefaultImpl, Descriptor, Multiplier};
+
+/// An error generating a Rust enum from a descriptor.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CodegenError {
+    /// The universal syntax descriptor accepts any token stream, so it has no
+    /// typed representation.
+    Universal,
+    /// Two components map to the same variant name, like `auto | <auto>`,
+    /// `<length> | <length>+` or `foo-bar | foo_bar`.
+    DuplicateVariant(Box<str>),
+    /// A component maps to a name that's reserved in Rust, like `self`, which
+    /// would be `Self`.
+    ReservedName(Box<str>),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CodegenError::Universal => f.write_str("the universal syntax descriptor has no typed representation"),
+            CodegenError::DuplicateVariant(ref name) => write!(f, "duplicate variant name '{}'", name),
+            CodegenError::ReservedName(ref name) => write!(f, "'{}' is reserved in Rust", name),
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+/// Converts a CSS name like `length-percentage` into a Rust type or variant
+/// name like `LengthPercentage`. Characters that can't appear in Rust
+/// identifiers act as word separators.
+fn pascal_case(name: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize = true;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            capitalize = true;
+            continue;
+        }
+        if capitalize {
+            result.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            result.push(c);
+        }
+    }
+    if result.is_empty() || result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, 'V');
+    }
+    result
+}
+
+/// Returns whether `name`, as returned by `pascal_case`, can't be used as a
+/// variant or type name. Other Rust keywords are all lowercase.
+fn is_reserved(name: &str) -> bool {
+    name == "Self"
+}
+
+fn separator(multiplier: Multiplier) -> &'static str {
+    match multiplier {
+        Multiplier::Space => " ",
+        Multiplier::Comma => ", ",
+    }
+}
+
+impl Descriptor<DefaultImpl> {
+    /// Generates a Rust enum named `name` with one variant per alternative of
+    /// this descriptor, plus `parse` and `cssparser::ToCss` implementations.
+    ///
+    /// Keywords become unit variants, and data type names become variants
+    /// holding a value of the type with the same name in PascalCase (e.g.
+    /// `<length-percentage>` holds a `LengthPercentage`), which must be in
+    /// scope where the code is used, implement `cssparser::ToCss`, and have an
+    /// inherent `parse` function with the same signature as the generated one.
+    /// Multiplied components hold a `Vec` of values, or for keywords the
+    /// number of repetitions.
+    pub fn to_rust_enum(&self, name: &Ident) -> Result<TokenStream, CodegenError> {
+        if self.is_universal() {
+            return Err(CodegenError::Universal);
+        }
+
+        let mut seen = HashSet::new();
+        let mut variants = vec![];
+        let mut parse_arms = vec![];
+        let mut serialize_arms = vec![];
+        for component in self.iter() {
+            let (variant_name, payload) = match *component.name() {
+                ComponentName::DataType(ty) => (pascal_case(ty.as_str()), Some(pascal_case(ty.as_str()))),
+                ComponentName::UnknownDataType(ref ty) => (pascal_case(ty), Some(pascal_case(ty))),
+                ComponentName::Ident(ref ident) => (pascal_case(ident), None),
+            };
+            if is_reserved(&variant_name) {
+                return Err(CodegenError::ReservedName(variant_name.into()));
+            }
+            if !seen.insert(variant_name.clone()) {
+                return Err(CodegenError::DuplicateVariant(variant_name.into()));
+            }
+            let variant = Ident::new(&variant_name, Span::call_site());
+
+            match (payload, component.multiplier()) {
+                (Some(payload), None) => {
+                    let payload = Ident::new(&payload, Span::call_site());
+                    variants.push(quote! { #variant(#payload) });
+                    parse_arms.push(quote! { #payload::parse(input).map(#name::#variant) });
+                    serialize_arms.push(quote! { #name::#variant(ref value) => value.to_css(dest) });
+                }
+                (Some(payload), Some(multiplier)) => {
+                    let payload = Ident::new(&payload, Span::call_site());
+                    variants.push(quote! { #variant(Vec<#payload>) });
+                    parse_arms.push(match multiplier {
+                        Multiplier::Space => quote! {{
+                            let mut values = vec![#payload::parse(input)?];
+                            while let Ok(value) = input.try_parse(#payload::parse) {
+                                values.push(value);
+                            }
+                            Ok(#name::#variant(values))
+                        }},
+                        Multiplier::Comma => quote! {
+                            input.parse_comma_separated(#payload::parse).map(#name::#variant)
+                        },
+                    });
+                    let separator = separator(multiplier);
+                    serialize_arms.push(quote! {
+                        #name::#variant(ref values) => {
+                            for (i, value) in values.iter().enumerate() {
+                                if i != 0 {
+                                    dest.write_str(#separator)?;
+                                }
+                                value.to_css(dest)?;
+                            }
+                            Ok(())
+                        }
+                    });
+                }
+                (None, multiplier) => {
+                    let ident = match *component.name() {
+                        ComponentName::Ident(ref ident) => ident.as_str(),
+                        _ => unreachable!(),
+                    };
+                    let mut serialized = String::new();
+                    cssparser::serialize_identifier(ident, &mut serialized).unwrap();
+                    let ident = Literal::string(ident);
+                    let serialized = Literal::string(&serialized);
+                    match multiplier {
+                        None => {
+                            variants.push(quote! { #variant });
+                            parse_arms.push(quote! {
+                                input.expect_ident_matching(#ident).map(|_| #name::#variant).map_err(Into::into)
+                            });
+                            serialize_arms.push(quote! { #name::#variant => dest.write_str(#serialized) });
+                        }
+                        Some(multiplier) => {
+                            let next = match multiplier {
+                                Multiplier::Space => quote! {},
+                                Multiplier::Comma => quote! { input.expect_comma()?; },
+                            };
+                            variants.push(quote! { #variant(usize) });
+                            parse_arms.push(quote! {{
+                                input.expect_ident_matching(#ident)?;
+                                let mut count = 1;
+                                while input.try_parse(|input| -> Result<(), ::cssparser::ParseError<'i, ()>> {
+                                    #next
+                                    input.expect_ident_matching(#ident)?;
+                                    Ok(())
+                                }).is_ok() {
+                                    count += 1;
+                                }
+                                Ok(#name::#variant(count))
+                            }});
+                            let separator = separator(multiplier);
+                            serialize_arms.push(quote! {
+                                #name::#variant(count) => {
+                                    for i in 0..count {
+                                        if i != 0 {
+                                            dest.write_str(#separator)?;
+                                        }
+                                        dest.write_str(#serialized)?;
+                                    }
+                                    Ok(())
+                                }
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(quote! {
+            #[derive(Clone, Debug, PartialEq)]
+            pub enum #name {
+                #(#variants,)*
+            }
+
+            impl #name {
+                /// Parses a value, trying each alternative in order.
+                pub fn parse<'i, 't>(
+                    input: &mut ::cssparser::Parser<'i, 't>,
+                ) -> Result<Self, ::cssparser::ParseError<'i, ()>> {
+                    #(
+                        if let Ok(value) = input.try_parse(|input| {
+                            input.parse_entirely(|input| -> Result<Self, ::cssparser::ParseError<'i, ()>> {
+                                #parse_arms
+                            })
+                        }) {
+                            return Ok(value);
+                        }
+                    )*
+                    Err(input.new_custom_error(()))
+                }
+            }
+
+            impl ::cssparser::ToCss for #name {
+                fn to_css<W: ::std::fmt::Write>(&self, dest: &mut W) -> ::std::fmt::Result {
+                    match *self {
+                        #(#serialize_arms,)*
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[test]
+fn rust_enum_test() {
+    use super::{parse_descriptor, parse_descriptor_with_options, ParseOptions};
+
+    assert_eq!(pascal_case("length-percentage"), "LengthPercentage");
+    assert_eq!(pascal_case("x_large"), "XLarge");
+    assert_eq!(pascal_case("1st"), "V1st");
+
+    let name = Ident::new("FontSize", Span::call_site());
+    let code = parse_descriptor("small | medium | <length>")
+        .unwrap()
+        .to_rust_enum(&name)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("pub enum FontSize { Small , Medium , Length (Length) , }"), "{}", code);
+    assert!(code.contains("FontSize :: Small => dest . write_str (\"small\")"), "{}", code);
+    assert!(code.contains("Length :: parse (input) . map (FontSize :: Length)"), "{}", code);
+
+    let code = parse_descriptor("<length-percentage># | foo+")
+        .unwrap()
+        .to_rust_enum(&name)
+        .unwrap()
+        .to_string();
+    assert!(code.contains("LengthPercentage (Vec < LengthPercentage >) , Foo (usize)"), "{}", code);
+    assert!(code.contains("parse_comma_separated (LengthPercentage :: parse)"), "{}", code);
+
+    assert_eq!(
+        Descriptor::universal().to_rust_enum(&name).unwrap_err(),
+        CodegenError::Universal,
+    );
+    let options = ParseOptions::compat();
+    assert_eq!(
+        parse_descriptor_with_options("auto | <auto>", &options).unwrap().to_rust_enum(&name).unwrap_err(),
+        CodegenError::DuplicateVariant("Auto".into()),
+    );
+    assert_eq!(
+        parse_descriptor("foo-bar | foo_bar").unwrap().to_rust_enum(&name).unwrap_err(),
+        CodegenError::DuplicateVariant("FooBar".into()),
+    );
+    assert_eq!(
+        parse_descriptor("auto | self").unwrap().to_rust_enum(&name).unwrap_err(),
+        CodegenError::ReservedName("Self".into()),
+    );
+    assert_eq!(
+        parse_descriptor_with_options("<self>", &options).unwrap().to_rust_enum(&name).unwrap_err(),
+        CodegenError::ReservedName("Self".into()),
+    );
+}
@@ -0,0 +1,316 @@
+//! Matching of `<color>` values.
+//!
+//! https://drafts.csswg.org/css-color-4/#color-syntax
+
+use std::fmt;
+
+use cssparser::{Parser, ToCss, Token};
+
+use super::matching::Error;
+use super::units::AngleUnit;
+use super::value::write_number;
+
+/// A color space that colors can be specified in, other than through the
+/// legacy sRGB syntaxes.
+///
+/// https://drafts.csswg.org/css-color-4/#predefined
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ColorSpace {
+    Lab,
+    Lch,
+    Oklab,
+    Oklch,
+    Srgb,
+    SrgbLinear,
+    DisplayP3,
+    A98Rgb,
+    ProphotoRgb,
+    Rec2020,
+    XyzD50,
+    XyzD65,
+}
+
+impl ColorSpace {
+    /// The color spaces that can be used in the `color()` function.
+    const PREDEFINED: &'static [ColorSpace] = &[
+        ColorSpace::Srgb,
+        ColorSpace::SrgbLinear,
+        ColorSpace::DisplayP3,
+        ColorSpace::A98Rgb,
+        ColorSpace::ProphotoRgb,
+        ColorSpace::Rec2020,
+        ColorSpace::XyzD50,
+        ColorSpace::XyzD65,
+    ];
+
+    /// Returns the name of the color space, which for the predefined ones is
+    /// how they're written in `color()`, and for the others is the name of
+    /// their function.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ColorSpace::Lab => "lab",
+            ColorSpace::Lch => "lch",
+            ColorSpace::Oklab => "oklab",
+            ColorSpace::Oklch => "oklch",
+            ColorSpace::Srgb => "srgb",
+            ColorSpace::SrgbLinear => "srgb-linear",
+            ColorSpace::DisplayP3 => "display-p3",
+            ColorSpace::A98Rgb => "a98-rgb",
+            ColorSpace::ProphotoRgb => "prophoto-rgb",
+            ColorSpace::Rec2020 => "rec2020",
+            ColorSpace::XyzD50 => "xyz-d50",
+            ColorSpace::XyzD65 => "xyz-d65",
+        }
+    }
+
+    /// Returns the predefined color space with the given `color()` name.
+    fn predefined_from_ident(ident: &str) -> Option<Self> {
+        // `xyz` is an alias of `xyz-d65`.
+        if ident.eq_ignore_ascii_case("xyz") {
+            return Some(ColorSpace::XyzD65);
+        }
+        Self::PREDEFINED.iter().find(|s| ident.eq_ignore_ascii_case(s.as_str())).cloned()
+    }
+
+    /// Returns what `100%` means for each component of the color space.
+    fn percentage_references(&self) -> [f32; 3] {
+        match *self {
+            ColorSpace::Lab => [100., 125., 125.],
+            ColorSpace::Lch => [100., 150., 0.],
+            ColorSpace::Oklab => [1., 0.4, 0.4],
+            ColorSpace::Oklch => [1., 0.4, 0.],
+            _ => [1., 1., 1.],
+        }
+    }
+}
+
+/// A color specified in a color space other than with the legacy sRGB
+/// syntaxes. Hues are in degrees, and `none` components are zero.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AbsoluteColor {
+    pub space: ColorSpace,
+    pub components: [f32; 3],
+    /// The alpha channel, between 0 and 1.
+    pub alpha: f32,
+}
+
+/// A matched `<color>`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Color {
+    /// The `currentcolor` keyword.
+    CurrentColor,
+    /// A color given by a named color, a hex color, or the `rgb()`, `hsl()`
+    /// or `hwb()` functions.
+    Rgba(cssparser::RGBA),
+    /// A color given by the `lab()`, `lch()`, `oklab()`, `oklch()` or
+    /// `color()` functions.
+    Absolute(AbsoluteColor),
+}
+
+impl AbsoluteColor {
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match self.space {
+            ColorSpace::Lab | ColorSpace::Lch | ColorSpace::Oklab | ColorSpace::Oklch => {
+                dest.write_str(self.space.as_str())?;
+                dest.write_char('(')?;
+            }
+            space => {
+                dest.write_str("color(")?;
+                dest.write_str(space.as_str())?;
+                dest.write_char(' ')?;
+            }
+        }
+        for (i, component) in self.components.iter().enumerate() {
+            if i > 0 {
+                dest.write_char(' ')?;
+            }
+            write_number((*component).into(), dest)?;
+        }
+        if self.alpha != 1. {
+            dest.write_str(" / ")?;
+            write_number(self.alpha.into(), dest)?;
+        }
+        dest.write_char(')')
+    }
+}
+
+impl Color {
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            Color::CurrentColor => dest.write_str("currentcolor"),
+            Color::Rgba(ref rgba) => rgba.to_css(dest),
+            Color::Absolute(ref color) => color.to_css(dest),
+        }
+    }
+}
+
+impl ToCss for Color {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        Color::to_css(self, dest)
+    }
+}
+
+impl From<cssparser::Color> for Color {
+    fn from(color: cssparser::Color) -> Self {
+        match color {
+            cssparser::Color::CurrentColor => Color::CurrentColor,
+            cssparser::Color::RGBA(rgba) => Color::Rgba(rgba),
+        }
+    }
+}
+
+/// Consumes a number, a percentage, which is resolved against `reference`,
+/// or `none`, which is zero.
+fn parse_component<'i, 't>(input: &mut Parser<'i, 't>, reference: f32) -> Result<f32, Error<'i>> {
+    let location = input.current_source_location();
+    match *input.next()? {
+        Token::Number { value, .. } => Ok(value),
+        Token::Percentage { unit_value, .. } => Ok(unit_value * reference),
+        Token::Ident(ref ident) if ident.eq_ignore_ascii_case("none") => Ok(0.),
+        ref token => Err(location.new_unexpected_token_error(token.clone())),
+    }
+}
+
+/// Consumes a hue, in degrees.
+///
+/// https://drafts.csswg.org/css-color-4/#hue-syntax
+fn parse_hue<'i, 't>(input: &mut Parser<'i, 't>) -> Result<f32, Error<'i>> {
+    let location = input.current_source_location();
+    let token = input.next()?.clone();
+    let hue = match token {
+        Token::Number { value, .. } => Some(value),
+        Token::Dimension { value, ref unit, .. } => {
+            AngleUnit::from_str(unit).map(|unit| (f64::from(value) * unit.to_canonical()) as f32)
+        }
+        Token::Ident(ref ident) if ident.eq_ignore_ascii_case("none") => Some(0.),
+        _ => None,
+    };
+    hue.ok_or_else(|| location.new_unexpected_token_error(token))
+}
+
+/// Consumes an optional `/ <alpha-value>` at the end of a color function.
+fn parse_alpha<'i, 't>(input: &mut Parser<'i, 't>) -> Result<f32, Error<'i>> {
+    if input.try_parse(|input| input.expect_delim('/')).is_err() {
+        return Ok(1.);
+    }
+    Ok(parse_component(input, 1.)?.clamp(0., 1.))
+}
+
+/// https://drafts.csswg.org/css-color-4/#the-hwb-notation
+fn hwb_to_rgba(hue: f32, whiteness: f32, blackness: f32, alpha: f32) -> cssparser::RGBA {
+    let (whiteness, blackness) = if whiteness + blackness >= 1. {
+        let gray = whiteness / (whiteness + blackness);
+        (gray, 1. - gray)
+    } else {
+        (whiteness, blackness)
+    };
+    let hue = hue.rem_euclid(360.) / 60.;
+    let channel = |n: f32| {
+        let k = (n + hue) % 6.;
+        let rgb = 1. - k.min(4. - k).clamp(0., 1.);
+        rgb * (1. - whiteness - blackness) + whiteness
+    };
+    cssparser::RGBA::from_floats(channel(5.), channel(3.), channel(1.), alpha)
+}
+
+/// Consumes the arguments of a color function that cssparser doesn't know
+/// about.
+fn parse_color_function<'i, 't>(name: &str, input: &mut Parser<'i, 't>) -> Result<Color, Error<'i>> {
+    let location = input.current_source_location();
+    let space = if name.eq_ignore_ascii_case("hwb") {
+        let hue = parse_hue(input)?;
+        let whiteness = parse_component(input, 1.)?.clamp(0., 1.);
+        let blackness = parse_component(input, 1.)?.clamp(0., 1.);
+        let alpha = parse_alpha(input)?;
+        return Ok(Color::Rgba(hwb_to_rgba(hue, whiteness, blackness, alpha)));
+    } else if name.eq_ignore_ascii_case("lab") {
+        ColorSpace::Lab
+    } else if name.eq_ignore_ascii_case("lch") {
+        ColorSpace::Lch
+    } else if name.eq_ignore_ascii_case("oklab") {
+        ColorSpace::Oklab
+    } else if name.eq_ignore_ascii_case("oklch") {
+        ColorSpace::Oklch
+    } else if name.eq_ignore_ascii_case("color") {
+        let ident = input.expect_ident()?;
+        match ColorSpace::predefined_from_ident(ident) {
+            Some(space) => space,
+            None => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone()))),
+        }
+    } else {
+        return Err(location.new_unexpected_token_error(Token::Function(name.to_owned().into())));
+    };
+    let references = space.percentage_references();
+    let mut components = [0.; 3];
+    for (i, component) in components.iter_mut().enumerate() {
+        let is_hue = i == 2 && matches!(space, ColorSpace::Lch | ColorSpace::Oklch);
+        *component = if is_hue { parse_hue(input)? } else { parse_component(input, references[i])? };
+    }
+    let alpha = parse_alpha(input)?;
+    Ok(Color::Absolute(AbsoluteColor { space, components, alpha }))
+}
+
+/// Consumes a `<color>`.
+pub(crate) fn parse_color<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Color, Error<'i>> {
+    if let Ok(color) = input.try_parse(cssparser::Color::parse) {
+        return Ok(color.into());
+    }
+    let location = input.current_source_location();
+    let name = match *input.next()? {
+        Token::Function(ref name) => name.clone(),
+        ref token => return Err(location.new_unexpected_token_error(token.clone())),
+    };
+    input.parse_nested_block(|input| parse_color_function(&name, input))
+}
+
+#[test]
+fn color_test() {
+    fn parse(input: &str) -> Result<Color, ()> {
+        let mut input = cssparser::ParserInput::new(input);
+        Parser::new(&mut input).parse_entirely(parse_color).map_err(|_| ())
+    }
+
+    assert_eq!(parse("currentColor"), Ok(Color::CurrentColor));
+    assert_eq!(parse("red"), Ok(Color::Rgba(cssparser::RGBA::new(255, 0, 0, 255))));
+    assert_eq!(parse("#00ff0080"), Ok(Color::Rgba(cssparser::RGBA::new(0, 255, 0, 128))));
+    assert_eq!(parse("rgb(0 0 255 / 50%)"), Ok(Color::Rgba(cssparser::RGBA::new(0, 0, 255, 128))));
+    assert_eq!(parse("hsl(120deg, 100%, 50%)"), Ok(Color::Rgba(cssparser::RGBA::new(0, 255, 0, 255))));
+    assert_eq!(parse("hwb(0 0% 0%)"), Ok(Color::Rgba(cssparser::RGBA::new(255, 0, 0, 255))));
+    assert_eq!(parse("hwb(0 60% 60%)"), Ok(Color::Rgba(cssparser::RGBA::new(128, 128, 128, 255))));
+    assert_eq!(
+        parse("lab(50% -20 none / 0.5)"),
+        Ok(Color::Absolute(AbsoluteColor {
+            space: ColorSpace::Lab,
+            components: [50., -20., 0.],
+            alpha: 0.5,
+        })),
+    );
+    assert_eq!(
+        parse("oklch(0.5 50% 0.5turn)"),
+        Ok(Color::Absolute(AbsoluteColor {
+            space: ColorSpace::Oklch,
+            components: [0.5, 0.2, 180.],
+            alpha: 1.,
+        })),
+    );
+    assert_eq!(
+        parse("color(display-p3 1 0.5 0)"),
+        Ok(Color::Absolute(AbsoluteColor {
+            space: ColorSpace::DisplayP3,
+            components: [1., 0.5, 0.],
+            alpha: 1.,
+        })),
+    );
+    assert_eq!(
+        parse("color(xyz 0 0 0 / 200%)"),
+        Ok(Color::Absolute(AbsoluteColor {
+            space: ColorSpace::XyzD65,
+            components: [0., 0., 0.],
+            alpha: 1.,
+        })),
+    );
+    for invalid in &["foo", "#12", "lab(1 2)", "lch(1 2 3px)", "color(foo 1 2 3)", "rgb(1 2 3 4)", "1px"] {
+        assert_eq!(parse(invalid), Err(()), "{}", invalid);
+    }
+}
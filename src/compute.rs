@@ -0,0 +1,406 @@
+//! Computation of matched values, as done for registered custom properties.
+//!
+//! https://drafts.css-houdini.org/css-properties-values-api-1/#calculation-of-computed-values
+
+use super::calc::{CalcNode, CalcType};
+use super::image::{Gradient, Image, ImageSetOption};
+use super::matching::round_to_integer;
+#[cfg(feature = "experimental-position")]
+use super::position::{Position, PositionComponent};
+use super::transform::TransformFunction;
+use super::units::{AngleUnit, LengthUnit, LengthUnitKind, ResolutionUnit, TimeUnit};
+use super::value::MatchedValue;
+
+/// What relative lengths are resolved against when computing a value.
+///
+/// All the lengths are in `px`.
+pub trait ComputeContext {
+    /// The computed font size of the element, which `em` is relative to.
+    fn font_size(&self) -> f64;
+
+    /// The computed font size of the root element, which `rem` is relative
+    /// to.
+    fn root_font_size(&self) -> f64;
+
+    /// The width and height of the viewport, which the viewport-percentage
+    /// units are relative to. The small, large and dynamic viewport units
+    /// all resolve against it, and so do the container units, as if there
+    /// was no query container.
+    fn viewport_size(&self) -> (f64, f64);
+
+    /// The computed line height of the element, which `lh` is relative to.
+    /// Defaults to that of `line-height: normal`.
+    fn line_height(&self) -> f64 {
+        self.font_size() * NORMAL_LINE_HEIGHT
+    }
+
+    /// The computed line height of the root element, which `rlh` is relative
+    /// to.
+    fn root_line_height(&self) -> f64 {
+        self.root_font_size() * NORMAL_LINE_HEIGHT
+    }
+}
+
+/// The usual ratio of `line-height: normal` to the font size.
+const NORMAL_LINE_HEIGHT: f64 = 1.2;
+
+/// Returns how many `px` `value` of `unit` is.
+///
+/// Font metrics other than the font size aren't known, so `ex`, `cap`, `ch`
+/// and `ic` use the fallbacks of the specification: half the font size for
+/// `ex` and `ch`, and the font size for `cap` and `ic`.
+///
+/// https://drafts.csswg.org/css-values-4/#font-relative-lengths
+pub(crate) fn length_to_px(value: f64, unit: LengthUnit, context: &dyn ComputeContext) -> f64 {
+    if let Some(px) = unit.to_px() {
+        return value * px;
+    }
+    let (width, height) = context.viewport_size();
+    let base = match unit {
+        LengthUnit::Em | LengthUnit::Cap | LengthUnit::Ic => context.font_size(),
+        LengthUnit::Ex | LengthUnit::Ch => context.font_size() / 2.,
+        LengthUnit::Rem | LengthUnit::Rcap | LengthUnit::Ric => context.root_font_size(),
+        LengthUnit::Rex | LengthUnit::Rch => context.root_font_size() / 2.,
+        LengthUnit::Lh => context.line_height(),
+        LengthUnit::Rlh => context.root_line_height(),
+        LengthUnit::Vw |
+        LengthUnit::Vi |
+        LengthUnit::Svw |
+        LengthUnit::Svi |
+        LengthUnit::Lvw |
+        LengthUnit::Lvi |
+        LengthUnit::Dvw |
+        LengthUnit::Dvi |
+        LengthUnit::Cqw |
+        LengthUnit::Cqi => width / 100.,
+        LengthUnit::Vh |
+        LengthUnit::Vb |
+        LengthUnit::Svh |
+        LengthUnit::Svb |
+        LengthUnit::Lvh |
+        LengthUnit::Lvb |
+        LengthUnit::Dvh |
+        LengthUnit::Dvb |
+        LengthUnit::Cqh |
+        LengthUnit::Cqb => height / 100.,
+        LengthUnit::Vmin | LengthUnit::Svmin | LengthUnit::Lvmin | LengthUnit::Dvmin | LengthUnit::Cqmin => {
+            width.min(height) / 100.
+        }
+        LengthUnit::Vmax | LengthUnit::Svmax | LengthUnit::Lvmax | LengthUnit::Dvmax | LengthUnit::Cqmax => {
+            width.max(height) / 100.
+        }
+        _ => unreachable!("absolute units are handled above"),
+    };
+    value * base
+}
+
+/// Returns the value of a leaf of a math expression in the canonical unit of
+/// its type, with percentages resolved as `percentage_basis` times their
+/// value.
+fn leaf_value(leaf: &CalcNode, percentage_basis: f64, context: &dyn ComputeContext) -> Option<f64> {
+    Some(match *leaf {
+        CalcNode::Number(value) => value,
+        CalcNode::Percentage(value) => value * percentage_basis,
+        CalcNode::Length(value, unit) => length_to_px(value, unit, context),
+        CalcNode::Angle(value, unit) => value * unit.to_canonical(),
+        CalcNode::Time(value, unit) => value * unit.to_canonical(),
+        CalcNode::Resolution(value, unit) => value * unit.to_canonical(),
+        _ => return None,
+    })
+}
+
+/// Returns whether the value of `node` is a linear function of the
+/// percentages in it, which is the case unless they're the argument of a
+/// math function like `min()`, multiplied together, or divided by.
+fn is_linear(node: &CalcNode) -> bool {
+    match *node {
+        CalcNode::Sum(ref nodes) => nodes.iter().all(is_linear),
+        CalcNode::Product(ref nodes) => {
+            nodes.iter().filter(|node| node.has_percentage()).count() <= 1 && nodes.iter().all(is_linear)
+        }
+        CalcNode::Negate(ref node) => is_linear(node),
+        CalcNode::Invert(ref node) => !node.has_percentage(),
+        CalcNode::Function(..) => !node.has_percentage(),
+        _ => true,
+    }
+}
+
+/// Returns `node` with its lengths in `px`.
+fn absolutize(node: &CalcNode, context: &dyn ComputeContext) -> CalcNode {
+    let all = |nodes: &[CalcNode]| nodes.iter().map(|node| absolutize(node, context)).collect();
+    match *node {
+        CalcNode::Length(value, unit) => CalcNode::Length(length_to_px(value, unit, context), LengthUnit::Px),
+        CalcNode::Sum(ref nodes) => CalcNode::Sum(all(nodes)),
+        CalcNode::Product(ref nodes) => CalcNode::Product(all(nodes)),
+        CalcNode::Negate(ref node) => CalcNode::Negate(Box::new(absolutize(node, context))),
+        CalcNode::Invert(ref node) => CalcNode::Invert(Box::new(absolutize(node, context))),
+        CalcNode::Function(function, ref arguments) => CalcNode::Function(function, all(arguments)),
+        ref leaf => leaf.clone(),
+    }
+}
+
+/// Computes a math function, which is simplified to a single value unless it
+/// mixes lengths and percentages, or resolves to a number from percentages,
+/// like `sign(10%)`.
+fn compute_calc(node: &CalcNode, context: &dyn ComputeContext) -> MatchedValue {
+    let ty = match node.calc_type() {
+        Some(ty) => ty,
+        None => return MatchedValue::Calc(Box::new(node.clone())),
+    };
+    let evaluate = |percentage_basis: f64| {
+        node.evaluate(&mut |leaf| leaf_value(leaf, percentage_basis, context))
+    };
+    let simplified = match ty {
+        CalcType::Number if node.has_percentage() => None,
+        CalcType::Number => evaluate(0.).map(MatchedValue::Number),
+        CalcType::Percentage => evaluate(1.).map(MatchedValue::Percentage),
+        CalcType::Length => evaluate(0.).map(|value| MatchedValue::Length { value, unit: LengthUnit::Px }),
+        CalcType::Angle => {
+            evaluate(0.).map(|value| MatchedValue::Angle { value, unit: AngleUnit::CANONICAL })
+        }
+        CalcType::Time => evaluate(0.).map(|value| MatchedValue::Time { value, unit: TimeUnit::CANONICAL }),
+        CalcType::Resolution => {
+            evaluate(0.).map(|value| MatchedValue::Resolution { value, unit: ResolutionUnit::CANONICAL })
+        }
+        CalcType::LengthPercentage if is_linear(node) => (|| {
+            let length = evaluate(0.)?;
+            let percentage = evaluate(1.)? - length;
+            Some(if percentage == 0. {
+                MatchedValue::Length { value: length, unit: LengthUnit::Px }
+            } else {
+                MatchedValue::Calc(Box::new(CalcNode::Sum(vec![
+                    CalcNode::Percentage(percentage),
+                    CalcNode::Length(length, LengthUnit::Px),
+                ])))
+            })
+        })(),
+        CalcType::LengthPercentage => None,
+    };
+    simplified.unwrap_or_else(|| MatchedValue::Calc(Box::new(absolutize(node, context))))
+}
+
+fn compute_transform_function(
+    function: &TransformFunction,
+    context: &dyn ComputeContext,
+) -> TransformFunction {
+    TransformFunction {
+        name: function.name,
+        arguments: function.arguments.iter().map(|argument| argument.compute(context)).collect(),
+    }
+}
+
+#[cfg(feature = "experimental-position")]
+fn compute_position(position: &Position, context: &dyn ComputeContext) -> Position {
+    let compute_component = |component: &PositionComponent| PositionComponent {
+        keyword: component.keyword,
+        offset: component.offset.as_ref().map(|offset| Box::new(offset.compute(context))),
+    };
+    Position {
+        horizontal: compute_component(&position.horizontal),
+        vertical: compute_component(&position.vertical),
+    }
+}
+
+fn compute_image(image: &Image, context: &dyn ComputeContext) -> Image {
+    match *image {
+        Image::Url(..) => image.clone(),
+        Image::Gradient(ref gradient) => {
+            let mut gradient: Gradient = (**gradient).clone();
+            for stop in &mut gradient.stops {
+                for position in &mut stop.positions {
+                    *position = position.compute(context);
+                }
+            }
+            Image::Gradient(Box::new(gradient))
+        }
+        Image::ImageSet(ref options) => Image::ImageSet(
+            options
+                .iter()
+                .map(|option| ImageSetOption {
+                    image: compute_image(&option.image, context),
+                    resolution: option.resolution.map(|(value, unit)| {
+                        (value * unit.to_canonical(), ResolutionUnit::CANONICAL)
+                    }),
+                    mime_type: option.mime_type.clone(),
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Returns whether a length in `unit` can be computed without knowing
+/// anything about the element, like its font size or its container.
+fn is_independent_length_unit(unit: LengthUnit) -> bool {
+    matches!(unit.kind(), LengthUnitKind::Absolute | LengthUnitKind::ViewportPercentage)
+}
+
+fn is_calc_computationally_independent(node: &CalcNode) -> bool {
+    match *node {
+        CalcNode::Length(_, unit) => is_independent_length_unit(unit),
+        CalcNode::Number(..) |
+        CalcNode::Percentage(..) |
+        CalcNode::Angle(..) |
+        CalcNode::Time(..) |
+        CalcNode::Resolution(..) => true,
+        CalcNode::Negate(ref node) | CalcNode::Invert(ref node) => is_calc_computationally_independent(node),
+        CalcNode::Sum(ref nodes) | CalcNode::Product(ref nodes) | CalcNode::Function(_, ref nodes) => {
+            nodes.iter().all(is_calc_computationally_independent)
+        }
+    }
+}
+
+fn is_image_computationally_independent(image: &Image) -> bool {
+    match *image {
+        Image::Url(..) => true,
+        Image::Gradient(ref gradient) => gradient
+            .stops
+            .iter()
+            .all(|stop| stop.positions.iter().all(MatchedValue::is_computationally_independent)),
+        Image::ImageSet(ref options) => {
+            options.iter().all(|option| is_image_computationally_independent(&option.image))
+        }
+    }
+}
+
+impl MatchedValue {
+    /// Returns whether this value can be computed without knowing anything
+    /// about the element, which is required of the initial values of
+    /// registered custom properties. Relative lengths other than viewport
+    /// lengths, and `var()` and `env()` references, are not.
+    ///
+    /// https://drafts.css-houdini.org/css-properties-values-api-1/#computationally-independent
+    pub fn is_computationally_independent(&self) -> bool {
+        match *self {
+            MatchedValue::Length { unit, .. } => is_independent_length_unit(unit),
+            MatchedValue::Calc(ref node) | MatchedValue::IntegerCalc(ref node) => {
+                is_calc_computationally_independent(node)
+            }
+            MatchedValue::Image(ref image) => is_image_computationally_independent(image),
+            MatchedValue::TransformFunction(ref function) => {
+                function.arguments.iter().all(MatchedValue::is_computationally_independent)
+            }
+            MatchedValue::TransformList(ref functions) => functions
+                .iter()
+                .all(|function| function.arguments.iter().all(MatchedValue::is_computationally_independent)),
+            #[cfg(feature = "experimental-position")]
+            MatchedValue::Position(ref position) => [&position.horizontal, &position.vertical]
+                .iter()
+                .filter_map(|component| component.offset.as_ref())
+                .all(|offset| offset.is_computationally_independent()),
+            MatchedValue::List(ref items, ..) => items.iter().all(MatchedValue::is_computationally_independent),
+            MatchedValue::PendingSubstitution(..) => false,
+            _ => true,
+        }
+    }
+
+    /// Returns the computed value of this value, as that of a registered
+    /// custom property.
+    ///
+    /// Dimensions are converted to their canonical unit, with lengths made
+    /// absolute using `context`, and math functions are simplified as far as
+    /// possible, so that only those mixing lengths and percentages remain.
+    /// Identifiers, colors and URLs are kept as specified, and so are values
+    /// with pending substitutions, which need to be substituted and matched
+    /// again first.
+    ///
+    /// https://drafts.css-houdini.org/css-properties-values-api-1/#calculation-of-computed-values
+    pub fn compute(&self, context: &dyn ComputeContext) -> MatchedValue {
+        match *self {
+            MatchedValue::Length { value, unit } => {
+                MatchedValue::Length { value: length_to_px(value, unit, context), unit: LengthUnit::Px }
+            }
+            MatchedValue::Angle { value, unit } => {
+                MatchedValue::Angle { value: value * unit.to_canonical(), unit: AngleUnit::CANONICAL }
+            }
+            MatchedValue::Time { value, unit } => {
+                MatchedValue::Time { value: value * unit.to_canonical(), unit: TimeUnit::CANONICAL }
+            }
+            MatchedValue::Resolution { value, unit } => MatchedValue::Resolution {
+                value: value * unit.to_canonical(),
+                unit: ResolutionUnit::CANONICAL,
+            },
+            MatchedValue::Calc(ref node) => compute_calc(node, context),
+            MatchedValue::IntegerCalc(ref node) => match compute_calc(node, context) {
+                MatchedValue::Number(value) => MatchedValue::Integer(round_to_integer(value)),
+                MatchedValue::Calc(node) => MatchedValue::IntegerCalc(node),
+                value => value,
+            },
+            MatchedValue::Image(ref image) => MatchedValue::Image(compute_image(image, context)),
+            MatchedValue::TransformFunction(ref function) => {
+                MatchedValue::TransformFunction(compute_transform_function(function, context))
+            }
+            MatchedValue::TransformList(ref functions) => MatchedValue::TransformList(
+                functions.iter().map(|function| compute_transform_function(function, context)).collect(),
+            ),
+            #[cfg(feature = "experimental-position")]
+            MatchedValue::Position(ref position) => {
+                MatchedValue::Position(compute_position(position, context))
+            }
+            MatchedValue::List(ref items, multiplier) => {
+                MatchedValue::List(items.iter().map(|item| item.compute(context)).collect(), multiplier)
+            }
+            _ => self.clone(),
+        }
+    }
+}
+
+#[test]
+fn compute_test() {
+    struct Context;
+
+    impl ComputeContext for Context {
+        fn font_size(&self) -> f64 {
+            20.
+        }
+
+        fn root_font_size(&self) -> f64 {
+            10.
+        }
+
+        fn viewport_size(&self) -> (f64, f64) {
+            (800., 600.)
+        }
+    }
+
+    fn compute(syntax: &str, value: &str) -> String {
+        let descriptor = super::parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        let value = descriptor.parse_value(&mut cssparser::Parser::new(&mut input)).unwrap();
+        value.compute(&Context).to_css_string()
+    }
+
+    for &(syntax, value, computed) in &[
+        ("<length>", "2em", "40px"),
+        ("<length>", "1in", "96px"),
+        ("<length>", "3rem", "30px"),
+        ("<length>", "1ex", "10px"),
+        ("<length>", "1lh", "24px"),
+        ("<length>", "10vmin", "60px"),
+        ("<length>", "10dvw", "80px"),
+        ("<length>", "calc(1em + 1vh)", "26px"),
+        ("<length-percentage>", "calc(1em + 2 * (10% + 1px))", "calc(20% + 22px)"),
+        ("<length-percentage>", "calc(50% + 1em - 50%)", "20px"),
+        ("<length-percentage>", "calc(50%)", "50%"),
+        ("<length-percentage>", "min(10%, 1em)", "min(10%, 20px)"),
+        ("<percentage>", "calc(10% * 3)", "30%"),
+        ("<number>", "calc(1em / 1px)", "20"),
+        ("<number>", "calc(1em / 3px)", "6.6666665"),
+        ("<integer>", "calc(1em / 3px)", "7"),
+        ("<integer>", "round(up, 1.5em / 1em)", "2"),
+        ("<number>", "sign(10%)", "sign(10%)"),
+        ("<integer>", "sign(10%)", "sign(10%)"),
+        ("<angle>", "0.5turn", "180deg"),
+        ("<angle>", "calc(1turn - 90deg)", "270deg"),
+        ("<time>", "250ms", "0.25s"),
+        ("<resolution>", "96dpi", "1dppx"),
+        ("<length>+", "1em 2px", "20px 2px"),
+        ("<transform-list>", "translate(1em, 10%) rotate(1turn)", "translate(20px, 10%) rotate(360deg)"),
+        ("<image>", "linear-gradient(red 1em, blue)", "linear-gradient(rgb(255, 0, 0) 20px, rgb(0, 0, 255))"),
+        ("<color>", "currentcolor", "currentcolor"),
+        ("<custom-ident>", "Foo", "Foo"),
+        ("<length>", "var(--x)", "var(--x)"),
+    ] {
+        assert_eq!(compute(syntax, value), computed, "{:?} as {:?}", value, syntax);
+    }
+    assert_eq!(Context.line_height(), 24.);
+}
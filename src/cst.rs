@@ -0,0 +1,157 @@
+//! A lossless representation of syntax descriptors, for formatters.
+
+use std::fmt;
+
+use super::{ascii, parse_descriptor_internal, DefaultImpl, Descriptor, Impl, ParseError, ParseOptions};
+
+/// A node of the concrete syntax tree of a syntax descriptor.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CstNode<'i> {
+    /// A run of whitespace, as written.
+    Whitespace(&'i str),
+    /// A `|` separating two components.
+    Pipe,
+    /// The `*` of the universal syntax descriptor.
+    Universal,
+    /// A component, including its multiplier if any.
+    Component {
+        /// The index of the component in the parsed descriptor.
+        index: usize,
+        /// The component as written, including escapes.
+        source: &'i str,
+    },
+}
+
+impl<'i> CstNode<'i> {
+    fn to_source<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            CstNode::Whitespace(s) | CstNode::Component { source: s, .. } => dest.write_str(s),
+            CstNode::Pipe => dest.write_char('|'),
+            CstNode::Universal => dest.write_char('*'),
+        }
+    }
+}
+
+/// A parsed syntax descriptor that remembers exactly how it was written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConcreteDescriptor<'i, I: Impl> {
+    descriptor: Descriptor<I>,
+    nodes: Vec<CstNode<'i>>,
+}
+
+impl<'i, I: Impl> ConcreteDescriptor<'i, I> {
+    /// Returns the parsed descriptor.
+    #[inline]
+    pub fn descriptor(&self) -> &Descriptor<I> {
+        &self.descriptor
+    }
+
+    /// Returns the nodes of the syntax tree, in source order.
+    #[inline]
+    pub fn nodes(&self) -> &[CstNode<'i>] {
+        &self.nodes
+    }
+
+    /// Writes back the original source, byte by byte.
+    pub fn to_source<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        for node in &self.nodes {
+            node.to_source(dest)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the original source.
+    pub fn to_source_string(&self) -> String {
+        let mut s = String::new();
+        self.to_source(&mut s).unwrap();
+        s
+    }
+
+    /// Writes the source with leading and trailing whitespace removed and
+    /// exactly one space around each `|`, keeping the spelling of each
+    /// component as written.
+    pub fn to_normalized_source<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        for node in &self.nodes {
+            match *node {
+                CstNode::Whitespace(..) => {}
+                CstNode::Pipe => dest.write_str(" | ")?,
+                CstNode::Universal | CstNode::Component { .. } => node.to_source(dest)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pushes the nodes for the source between two components. Since the
+/// descriptor parsed, this can only contain whitespace and pipes.
+fn push_gap<'i>(nodes: &mut Vec<CstNode<'i>>, gap: &'i str) {
+    for (i, piece) in gap.split('|').enumerate() {
+        if i != 0 {
+            nodes.push(CstNode::Pipe);
+        }
+        if !piece.is_empty() {
+            nodes.push(CstNode::Whitespace(piece));
+        }
+    }
+}
+
+/// Parse a syntax descriptor with the default implementation, keeping track
+/// of how it was written.
+#[inline]
+pub fn parse_descriptor_lossless(input: &str) -> Result<ConcreteDescriptor<'_, DefaultImpl>, ParseError> {
+    parse_descriptor_lossless_with::<DefaultImpl>(input)
+}
+
+/// Parse a syntax descriptor, keeping track of how it was written.
+pub fn parse_descriptor_lossless_with<I: Impl>(input: &str) -> Result<ConcreteDescriptor<'_, I>, ParseError> {
+    let mut spans = vec![];
+    let descriptor = parse_descriptor_internal(input, &ParseOptions::default(), None, Some(&mut spans))?;
+
+    let mut nodes = vec![];
+    if descriptor.is_universal() {
+        let trimmed = ascii::trim_ascii_whitespace(input);
+        let start = trimmed.as_ptr() as usize - input.as_ptr() as usize;
+        push_gap(&mut nodes, &input[..start]);
+        nodes.push(CstNode::Universal);
+        push_gap(&mut nodes, &input[start + 1..]);
+    } else {
+        let mut end = 0;
+        for (index, span) in spans.into_iter().enumerate() {
+            push_gap(&mut nodes, &input[end..span.start]);
+            nodes.push(CstNode::Component {
+                index,
+                source: &input[span.clone()],
+            });
+            end = span.end;
+        }
+        push_gap(&mut nodes, &input[end..]);
+    }
+
+    Ok(ConcreteDescriptor { descriptor, nodes })
+}
+
+#[test]
+fn lossless_test() {
+    fn test(input: &str, normalized: &str) {
+        let concrete = parse_descriptor_lossless(input).unwrap();
+        assert_eq!(concrete.to_source_string(), input);
+        let mut s = String::new();
+        concrete.to_normalized_source(&mut s).unwrap();
+        assert_eq!(s, normalized);
+        assert_eq!(*concrete.descriptor(), super::parse_descriptor(input).unwrap());
+    }
+
+    test("  * ", "*");
+    test("<length>", "<length>");
+    test(" foo|<length>#\t|\n  \\31 bar+ ", "foo | <length># | \\31 bar+");
+
+    let concrete = parse_descriptor_lossless(" a |<color>").unwrap();
+    assert_eq!(concrete.nodes(), &[
+        CstNode::Whitespace(" "),
+        CstNode::Component { index: 0, source: "a" },
+        CstNode::Whitespace(" "),
+        CstNode::Pipe,
+        CstNode::Component { index: 1, source: "<color>" },
+    ]);
+    assert!(parse_descriptor_lossless("<foo>").is_err());
+}
@@ -1,6 +1,12 @@
-use super::{Impl, Component, ComponentName, Multiplier};
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+use super::{Impl, Component, ComponentName, Multiplier, ParseErrorKind};
+use super::keyword::CssWideKeyword;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
 pub enum DataType {
     Length,
     Number,
@@ -16,9 +22,32 @@ pub enum DataType {
     TransformFunction,
     TransformList,
     CustomIdent,
+    /// `<position>`, which is not in the specification.
+    #[cfg(feature = "experimental-position")]
+    Position,
 }
 
 impl DataType {
+    /// All the data types, in the order the spec lists them.
+    pub const ALL: &'static [DataType] = &[
+        DataType::Length,
+        DataType::Number,
+        DataType::Percentage,
+        DataType::LengthPercentage,
+        DataType::Color,
+        DataType::Image,
+        DataType::Url,
+        DataType::Integer,
+        DataType::Angle,
+        DataType::Time,
+        DataType::Resolution,
+        DataType::TransformFunction,
+        DataType::CustomIdent,
+        DataType::TransformList,
+        #[cfg(feature = "experimental-position")]
+        DataType::Position,
+    ];
+
     pub fn unpremultiply<I: Impl<DataType = Self>>(&self) -> Option<Component<I>> {
         match *self {
             DataType::TransformList => Some(Component {
@@ -29,6 +58,30 @@ impl DataType {
         }
     }
 
+    /// Returns the name of this data type as written in a syntax descriptor,
+    /// without the angle brackets.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            DataType::Length => "length",
+            DataType::Number => "number",
+            DataType::Percentage => "percentage",
+            DataType::LengthPercentage => "length-percentage",
+            DataType::Color => "color",
+            DataType::Image => "image",
+            DataType::Url => "url",
+            DataType::Integer => "integer",
+            DataType::Angle => "angle",
+            DataType::Time => "time",
+            DataType::Resolution => "resolution",
+            DataType::TransformFunction => "transform-function",
+            DataType::CustomIdent => "custom-ident",
+            DataType::TransformList => "transform-list",
+            #[cfg(feature = "experimental-position")]
+            DataType::Position => "position",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(ty: &str) -> Option<Self> {
         Some(match ty.as_bytes() {
             b"length" => DataType::Length,
@@ -45,28 +98,109 @@ impl DataType {
             b"transform-function" => DataType::TransformFunction,
             b"custom-ident" => DataType::CustomIdent,
             b"transform-list" => DataType::TransformList,
+            #[cfg(feature = "experimental-position")]
+            b"position" => DataType::Position,
             _ => return None,
         })
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl cssparser::ToCss for DataType {
+    /// Serializes the data type name with its angle brackets, like `<length>`.
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_char('<')?;
+        dest.write_str(self.as_str())?;
+        dest.write_char('>')
+    }
+}
+
+impl<'a> TryFrom<&'a str> for DataType {
+    type Error = ParseErrorKind;
+
+    fn try_from(name: &'a str) -> Result<Self, ParseErrorKind> {
+        DataType::from_str(name).ok_or_else(|| ParseErrorKind::UnknownDataTypeName(name.into()))
+    }
+}
+
+/// The reason a string can't be used as a custom identifier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CustomIdentError {
+    /// The identifier is a CSS-wide keyword, like `inherit`.
+    CssWideKeyword,
+    /// The identifier is a reserved word, i.e. `default`.
+    ReservedWord,
+    /// The string is not a valid `<ident>` token.
+    InvalidIdent,
+}
+
+impl fmt::Display for CustomIdentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            CustomIdentError::CssWideKeyword => "CSS-wide keywords can't be custom identifiers",
+            CustomIdentError::ReservedWord => "'default' can't be a custom identifier",
+            CustomIdentError::InvalidIdent => "not a valid identifier",
+        })
+    }
+}
+
+impl std::error::Error for CustomIdentError {}
+
+/// https://drafts.csswg.org/css-values-4/#identifier-value
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct CustomIdent(Box<str>);
 
 impl CustomIdent {
-    pub fn from_ident(ident: &str) -> Option<Self> {
-        if ident.eq_ignore_ascii_case("inherit") ||
-            ident.eq_ignore_ascii_case("reset") ||
-            ident.eq_ignore_ascii_case("revert") ||
-            ident.eq_ignore_ascii_case("unset") ||
-            ident.eq_ignore_ascii_case("default") {
-            return None;
+    /// Parses a custom identifier from CSS source, that is, `input` must be a
+    /// single `<ident>` token, possibly with escapes.
+    pub fn new(input: &str) -> Result<Self, CustomIdentError> {
+        let mut input = cssparser::ParserInput::new(input);
+        let mut input = cssparser::Parser::new(&mut input);
+        let ident = input
+            .expect_ident()
+            .map_err(|_| CustomIdentError::InvalidIdent)?
+            .clone();
+        if !input.is_exhausted() {
+            return Err(CustomIdentError::InvalidIdent);
         }
+        Self::check(&ident)?;
+        Ok(CustomIdent(ident.as_ref().to_owned().into_boxed_str()))
+    }
+
+    fn check(ident: &str) -> Result<(), CustomIdentError> {
+        match CssWideKeyword::from_ident(ident) {
+            None => Ok(()),
+            Some(CssWideKeyword::Default) => Err(CustomIdentError::ReservedWord),
+            Some(..) => Err(CustomIdentError::CssWideKeyword),
+        }
+    }
+
+    /// Creates a custom identifier from the value of an `<ident>` token.
+    pub fn from_ident(ident: &str) -> Option<Self> {
+        Self::check(ident).ok()?;
         Some(CustomIdent(ident.to_owned().into_boxed_str()))
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for CustomIdent {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct DefaultImpl;
 
 impl Impl for DefaultImpl {
@@ -84,4 +218,45 @@ impl Impl for DefaultImpl {
     fn unpremultiply_data_type(ty: &DataType) -> Option<Component<Self>> {
         ty.unpremultiply()
     }
+
+    fn serialize_data_type_name<W: fmt::Write>(ty: &DataType, dest: &mut W) -> fmt::Result {
+        dest.write_str(ty.as_str())
+    }
+
+    fn serialize_custom_ident<W: fmt::Write>(ident: &CustomIdent, dest: &mut W) -> fmt::Result {
+        cssparser::serialize_identifier(ident.as_str(), dest)
+    }
+}
+
+#[test]
+fn data_type_name_test() {
+    for name in &["length", "length-percentage", "transform-list", "custom-ident"] {
+        let ty = DataType::try_from(*name).unwrap();
+        assert_eq!(ty.as_str(), *name);
+        assert_eq!(ty.to_string(), *name);
+    }
+    assert_eq!(
+        DataType::try_from("Length"),
+        Err(ParseErrorKind::UnknownDataTypeName("Length".into())),
+    );
+}
+
+#[test]
+fn all_data_types_test() {
+    assert_eq!(DataType::ALL.len(), if cfg!(feature = "experimental-position") { 15 } else { 14 });
+    for ty in DataType::ALL {
+        assert_eq!(DataType::from_str(ty.as_str()), Some(*ty));
+    }
+}
+
+#[test]
+fn custom_ident_test() {
+    assert_eq!(CustomIdent::new("foo").unwrap().as_str(), "foo");
+    assert_eq!(&*CustomIdent::new("\\31 foo").unwrap(), "1foo");
+    assert_eq!(CustomIdent::new("Initial"), Err(CustomIdentError::CssWideKeyword));
+    assert_eq!(CustomIdent::new("revert-layer"), Err(CustomIdentError::CssWideKeyword));
+    assert_eq!(CustomIdent::new("DEFAULT"), Err(CustomIdentError::ReservedWord));
+    for invalid in &["", "1foo", "foo bar", "<length>", "foo|"] {
+        assert_eq!(CustomIdent::new(invalid), Err(CustomIdentError::InvalidIdent));
+    }
 }
@@ -0,0 +1,129 @@
+use super::{Component, ComponentName, DataType, DefaultImpl, Descriptor, Multiplier};
+
+impl DataType {
+    /// The singular English name of this data type, with its article.
+    fn describe_one(&self) -> &'static str {
+        match *self {
+            DataType::Length => "a length",
+            DataType::Number => "a number",
+            DataType::Percentage => "a percentage",
+            DataType::LengthPercentage => "a length or percentage",
+            DataType::Color => "a color",
+            DataType::Image => "an image",
+            DataType::Url => "a URL",
+            DataType::Integer => "an integer",
+            DataType::Angle => "an angle",
+            DataType::Time => "a time",
+            DataType::Resolution => "a resolution",
+            DataType::TransformFunction => "a transform function",
+            DataType::TransformList => "a list of transform functions",
+            DataType::CustomIdent => "an identifier",
+            #[cfg(feature = "experimental-position")]
+            DataType::Position => "a position",
+        }
+    }
+
+    /// The plural English name of this data type.
+    fn describe_many(&self) -> &'static str {
+        match *self {
+            DataType::Length => "lengths",
+            DataType::Number => "numbers",
+            DataType::Percentage => "percentages",
+            DataType::LengthPercentage => "lengths or percentages",
+            DataType::Color => "colors",
+            DataType::Image => "images",
+            DataType::Url => "URLs",
+            DataType::Integer => "integers",
+            DataType::Angle => "angles",
+            DataType::Time => "times",
+            DataType::Resolution => "resolutions",
+            DataType::TransformFunction => "transform functions",
+            DataType::TransformList => "lists of transform functions",
+            DataType::CustomIdent => "identifiers",
+            #[cfg(feature = "experimental-position")]
+            DataType::Position => "positions",
+        }
+    }
+}
+
+impl Component<DefaultImpl> {
+    fn describe_to(&self, dest: &mut String) {
+        let multiplier = match self.multiplier() {
+            None => {
+                match *self.name() {
+                    ComponentName::DataType(ty) => dest.push_str(ty.describe_one()),
+                    ComponentName::Ident(ref ident) => {
+                        dest.push_str("the keyword '");
+                        dest.push_str(ident);
+                        dest.push('\'');
+                    }
+                    ComponentName::UnknownDataType(ref name) => {
+                        dest.push_str("a <");
+                        dest.push_str(name);
+                        dest.push_str("> value");
+                    }
+                }
+                return;
+            }
+            Some(multiplier) => multiplier,
+        };
+        dest.push_str(match multiplier {
+            Multiplier::Space => "a space-separated list of ",
+            Multiplier::Comma => "a comma-separated list of ",
+        });
+        match *self.name() {
+            ComponentName::DataType(ty) => dest.push_str(ty.describe_many()),
+            ComponentName::Ident(ref ident) => {
+                dest.push('\'');
+                dest.push_str(ident);
+                dest.push_str("' keywords");
+            }
+            ComponentName::UnknownDataType(ref name) => {
+                dest.push('<');
+                dest.push_str(name);
+                dest.push_str("> values");
+            }
+        }
+    }
+}
+
+impl Descriptor<DefaultImpl> {
+    /// Returns an English description of the values this descriptor accepts,
+    /// like "a length, or a comma-separated list of colors, or the keyword
+    /// 'auto'", for devtools and documentation.
+    pub fn describe(&self) -> String {
+        if self.is_universal() {
+            return "any value".to_owned();
+        }
+        let mut s = String::new();
+        for (i, component) in self.iter().enumerate() {
+            if i != 0 {
+                s.push_str(", or ");
+            }
+            component.describe_to(&mut s);
+        }
+        s
+    }
+}
+
+#[test]
+fn describe_test() {
+    use super::{parse_descriptor, parse_descriptor_with_options, ParseOptions};
+
+    assert_eq!(
+        parse_descriptor("<length> | <color># | auto").unwrap().describe(),
+        "a length, or a comma-separated list of colors, or the keyword 'auto'",
+    );
+    assert_eq!(
+        parse_descriptor("<image>+ | <transform-list> | foo#").unwrap().describe(),
+        "a space-separated list of images, or a list of transform functions, \
+         or a comma-separated list of 'foo' keywords",
+    );
+    assert_eq!(Descriptor::universal().describe(), "any value");
+
+    let options = ParseOptions::compat();
+    assert_eq!(
+        parse_descriptor_with_options("<foo>+ | <bar>", &options).unwrap().describe(),
+        "a space-separated list of <foo> values, or a <bar> value",
+    );
+}
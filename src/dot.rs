@@ -0,0 +1,89 @@
+use std::fmt;
+
+use super::{ComponentName, Descriptor, Impl, Multiplier};
+
+/// Writes `s` as a quoted Graphviz string.
+fn write_dot_string<W: fmt::Write>(s: &str, dest: &mut W) -> fmt::Result {
+    dest.write_char('"')?;
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            dest.write_char('\\')?;
+        }
+        dest.write_char(c)?;
+    }
+    dest.write_char('"')
+}
+
+impl<I: Impl> Descriptor<I> {
+    /// Writes a Graphviz graph of this descriptor, going left to right from a
+    /// start point to an end point through each alternative.
+    ///
+    /// Data type names are drawn as boxes and identifiers as ellipses.
+    /// Multipliers are drawn as an edge looping back to the component,
+    /// labelled with the separator between repetitions.
+    pub fn write_dot<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_str("digraph descriptor {\n")?;
+        dest.write_str("    rankdir=LR;\n")?;
+        dest.write_str("    start [shape=point];\n")?;
+        dest.write_str("    end [shape=point];\n")?;
+        if self.is_universal() {
+            dest.write_str("    c0 [label=\"*\", shape=box];\n")?;
+            dest.write_str("    start -> c0;\n")?;
+            dest.write_str("    c0 -> end;\n")?;
+            return dest.write_str("}\n");
+        }
+        let mut label = String::new();
+        for (i, component) in self.iter().enumerate() {
+            label.clear();
+            component.name().to_css(&mut label)?;
+            let shape = match *component.name() {
+                ComponentName::Ident(..) => "ellipse",
+                ComponentName::DataType(..) | ComponentName::UnknownDataType(..) => "box",
+            };
+            write!(dest, "    c{} [label=", i)?;
+            write_dot_string(&label, dest)?;
+            writeln!(dest, ", shape={}];", shape)?;
+            writeln!(dest, "    start -> c{};", i)?;
+            writeln!(dest, "    c{} -> end;", i)?;
+            if let Some(multiplier) = component.multiplier() {
+                let separator = match multiplier {
+                    Multiplier::Space => "space",
+                    Multiplier::Comma => ",",
+                };
+                writeln!(dest, "    c{0} -> c{0} [label=\"{1}\"];", i, separator)?;
+            }
+        }
+        dest.write_str("}\n")
+    }
+
+    /// Returns a Graphviz graph of this descriptor, see `write_dot`.
+    pub fn to_dot(&self) -> String {
+        let mut s = String::new();
+        self.write_dot(&mut s).unwrap();
+        s
+    }
+}
+
+#[test]
+fn dot_test() {
+    use super::{parse_descriptor, DefaultImpl};
+
+    let descriptor = parse_descriptor("<length># | \\\"quoted").unwrap();
+    assert_eq!(descriptor.to_dot(), "\
+digraph descriptor {
+    rankdir=LR;
+    start [shape=point];
+    end [shape=point];
+    c0 [label=\"<length>\", shape=box];
+    start -> c0;
+    c0 -> end;
+    c0 -> c0 [label=\",\"];
+    c1 [label=\"\\\\\\\"quoted\", shape=ellipse];
+    start -> c1;
+    c1 -> end;
+}
+");
+
+    let universal = Descriptor::<DefaultImpl>::universal().to_dot();
+    assert!(universal.contains("c0 [label=\"*\", shape=box];"));
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+use super::{Descriptor, Impl};
+
+/// Options for pretty-printing syntax descriptors with `Descriptor::format`.
+///
+/// The default options produce the same output as `Descriptor::to_css`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatOptions {
+    /// Whether `|` is surrounded by spaces.
+    pub spaces_around_pipe: bool,
+    /// If set, descriptors whose single-line form is longer than this many
+    /// characters are written with one alternative per line, each line after
+    /// the first starting with `|`.
+    pub max_line_width: Option<usize>,
+    /// Whether, when writing one alternative per line, the first alternative
+    /// is indented so that all of them line up after their `|`.
+    pub align_alternatives: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            spaces_around_pipe: true,
+            max_line_width: None,
+            align_alternatives: false,
+        }
+    }
+}
+
+impl<I: Impl> Descriptor<I> {
+    /// Writes this descriptor formatted according to `options`.
+    pub fn format_to<W: fmt::Write>(&self, options: &FormatOptions, dest: &mut W) -> fmt::Result {
+        let separator = if options.spaces_around_pipe { " | " } else { "|" };
+        let single_line = {
+            let mut s = String::new();
+            self.to_css_with_separator(separator, &mut s)?;
+            s
+        };
+        let too_long = options
+            .max_line_width
+            .is_some_and(|width| single_line.chars().count() > width);
+        if !too_long || self.len() < 2 {
+            return dest.write_str(&single_line);
+        }
+
+        let prefix = if options.spaces_around_pipe { "| " } else { "|" };
+        for (i, component) in self.iter().enumerate() {
+            if i != 0 {
+                dest.write_char('\n')?;
+                dest.write_str(prefix)?;
+            } else if options.align_alternatives {
+                for _ in 0..prefix.len() {
+                    dest.write_char(' ')?;
+                }
+            }
+            component.to_css(dest)?;
+        }
+        Ok(())
+    }
+
+    /// Returns this descriptor formatted according to `options`.
+    pub fn format(&self, options: &FormatOptions) -> String {
+        let mut s = String::new();
+        self.format_to(options, &mut s).unwrap();
+        s
+    }
+}
+
+#[test]
+fn format_test() {
+    use super::parse_descriptor;
+
+    let descriptor = parse_descriptor("none|auto|<length>#").unwrap();
+    assert_eq!(descriptor.format(&FormatOptions::default()), "none | auto | <length>#");
+
+    let mut options = FormatOptions {
+        spaces_around_pipe: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(descriptor.format(&options), "none|auto|<length>#");
+
+    options.max_line_width = Some(10);
+    assert_eq!(descriptor.format(&options), "none\n|auto\n|<length>#");
+    options.align_alternatives = true;
+    assert_eq!(descriptor.format(&options), " none\n|auto\n|<length>#");
+
+    options.spaces_around_pipe = true;
+    assert_eq!(descriptor.format(&options), "  none\n| auto\n| <length>#");
+    options.max_line_width = Some(40);
+    assert_eq!(descriptor.format(&options), "none | auto | <length>#");
+
+    let universal = Descriptor::<super::DefaultImpl>::universal();
+    options.max_line_width = Some(0);
+    assert_eq!(universal.format(&options), "*");
+}
@@ -0,0 +1,455 @@
+//! Matching of `<image>` values.
+//!
+//! https://drafts.csswg.org/css-images-4/#image-values
+
+use std::fmt;
+
+use cssparser::{Parser, Token};
+
+use super::color::{self, Color};
+use super::matching::{self, Error};
+use super::units::ResolutionUnit;
+use super::url::{self, Url};
+use super::value::{write_dimension, MatchedValue};
+use super::DataType;
+
+/// https://drafts.csswg.org/css-images-4/#gradients
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GradientKind {
+    Linear,
+    Radial,
+    Conic,
+}
+
+/// A color stop or a color transition hint of a gradient.
+///
+/// https://drafts.csswg.org/css-images-4/#color-stop-syntax
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorStop {
+    /// The color of the stop, or `None` if this is a transition hint.
+    pub color: Option<Color>,
+    /// The zero, one or two positions of the stop. These are
+    /// `<length-percentage>`s, or `<angle-percentage>`s for conic gradients.
+    pub positions: Vec<MatchedValue>,
+}
+
+/// https://drafts.csswg.org/css-images-4/#gradients
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    /// Whether this is a `repeating-*` gradient.
+    pub repeating: bool,
+    /// The arguments before the color stops, that define the line, shape or
+    /// center of the gradient, as written. Empty if they were omitted.
+    pub prelude: Box<str>,
+    pub stops: Vec<ColorStop>,
+}
+
+/// An option of an `image-set()`.
+///
+/// https://drafts.csswg.org/css-images-4/#image-set-notation
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageSetOption {
+    pub image: Image,
+    /// The resolution the image is meant for, if given.
+    pub resolution: Option<(f64, ResolutionUnit)>,
+    /// The MIME type given in the `type()` function, if any.
+    pub mime_type: Option<Box<str>>,
+}
+
+/// A matched `<image>`.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Image {
+    /// An image given by a URL, unresolved.
+    Url(Url),
+    Gradient(Box<Gradient>),
+    ImageSet(Vec<ImageSetOption>),
+}
+
+impl GradientKind {
+    /// Returns the name of the non-repeating gradient function.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            GradientKind::Linear => "linear-gradient",
+            GradientKind::Radial => "radial-gradient",
+            GradientKind::Conic => "conic-gradient",
+        }
+    }
+}
+
+impl ColorStop {
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        if let Some(ref color) = self.color {
+            color.to_css(dest)?;
+        }
+        for (i, position) in self.positions.iter().enumerate() {
+            if i > 0 || self.color.is_some() {
+                dest.write_char(' ')?;
+            }
+            position.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+impl Gradient {
+    /// Serializes the gradient, with the prelude as written.
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        if self.repeating {
+            dest.write_str("repeating-")?;
+        }
+        dest.write_str(self.kind.name())?;
+        dest.write_char('(')?;
+        if !self.prelude.is_empty() {
+            dest.write_str(&self.prelude)?;
+            dest.write_str(", ")?;
+        }
+        for (i, stop) in self.stops.iter().enumerate() {
+            if i > 0 {
+                dest.write_str(", ")?;
+            }
+            stop.to_css(dest)?;
+        }
+        dest.write_char(')')
+    }
+}
+
+impl ImageSetOption {
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        self.image.to_css(dest)?;
+        if let Some((value, unit)) = self.resolution {
+            dest.write_char(' ')?;
+            write_dimension(value, unit.as_str(), dest)?;
+        }
+        if let Some(ref mime_type) = self.mime_type {
+            dest.write_str(" type(")?;
+            cssparser::serialize_string(mime_type, dest)?;
+            dest.write_char(')')?;
+        }
+        Ok(())
+    }
+}
+
+impl Image {
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            Image::Url(ref url) => url.to_css(dest),
+            Image::Gradient(ref gradient) => gradient.to_css(dest),
+            Image::ImageSet(ref options) => {
+                dest.write_str("image-set(")?;
+                for (i, option) in options.iter().enumerate() {
+                    if i > 0 {
+                        dest.write_str(", ")?;
+                    }
+                    option.to_css(dest)?;
+                }
+                dest.write_char(')')
+            }
+        }
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+impl cssparser::ToCss for Image {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        Image::to_css(self, dest)
+    }
+}
+
+const SIDES: &[&str] = &["left", "right", "top", "bottom"];
+
+/// Consumes an identifier that is one of `idents`, ASCII case-insensitively.
+fn expect_one_of<'i, 't>(input: &mut Parser<'i, 't>, idents: &[&str]) -> Result<(), Error<'i>> {
+    let location = input.current_source_location();
+    match *input.next()? {
+        Token::Ident(ref ident) if idents.iter().any(|i| ident.eq_ignore_ascii_case(i)) => Ok(()),
+        ref token => Err(location.new_unexpected_token_error(token.clone())),
+    }
+}
+
+/// Consumes as many of the given keywords or `<length-percentage>`s as
+/// possible, up to `max`, and returns how many were consumed.
+fn parse_keywords_or_lengths<'i, 't>(input: &mut Parser<'i, 't>, keywords: &[&str], max: usize) -> usize {
+    let mut count = 0;
+    while count < max {
+        let parsed = input.try_parse(|input| expect_one_of(input, keywords)).is_ok() ||
+            input.try_parse(|input| {
+                matching::parse_data_type::<()>(DataType::LengthPercentage, input)
+            }).is_ok();
+        if !parsed {
+            break;
+        }
+        count += 1;
+    }
+    count
+}
+
+/// Consumes a `<position>`, loosely: up to four keywords or
+/// `<length-percentage>`s.
+///
+/// https://drafts.csswg.org/css-values-4/#position
+fn parse_position<'i, 't>(input: &mut Parser<'i, 't>) -> Result<(), Error<'i>> {
+    if parse_keywords_or_lengths(input, &["left", "right", "top", "bottom", "center"], 4) == 0 {
+        return Err(input.new_error_for_next_token());
+    }
+    Ok(())
+}
+
+/// Consumes the arguments of a gradient before its color stops, without the
+/// trailing comma.
+fn parse_prelude<'i, 't>(kind: GradientKind, input: &mut Parser<'i, 't>) -> Result<(), Error<'i>> {
+    let mut parsed = false;
+    match kind {
+        GradientKind::Linear => {
+            if input.try_parse(|input| input.expect_ident_matching("to")).is_err() {
+                return matching::parse_data_type::<()>(DataType::Angle, input);
+            }
+            expect_one_of(input, SIDES)?;
+            let _ = input.try_parse(|input| expect_one_of(input, SIDES));
+            return Ok(());
+        }
+        GradientKind::Radial => {
+            const SHAPE_AND_SIZE: &[&str] = &[
+                "circle",
+                "ellipse",
+                "closest-side",
+                "closest-corner",
+                "farthest-side",
+                "farthest-corner",
+            ];
+            parsed = parse_keywords_or_lengths(input, SHAPE_AND_SIZE, 3) > 0;
+        }
+        GradientKind::Conic => {
+            if input.try_parse(|input| input.expect_ident_matching("from")).is_ok() {
+                matching::parse_data_type::<()>(DataType::Angle, input)?;
+                parsed = true;
+            }
+        }
+    }
+    if input.try_parse(|input| input.expect_ident_matching("at")).is_ok() {
+        parse_position(input)?;
+        parsed = true;
+    }
+    if !parsed {
+        return Err(input.new_error_for_next_token());
+    }
+    Ok(())
+}
+
+/// Consumes the position of a color stop or hint.
+fn parse_stop_position<'i, 't>(kind: GradientKind, input: &mut Parser<'i, 't>) -> Result<MatchedValue, Error<'i>> {
+    if kind == GradientKind::Conic {
+        if let Ok(angle) = input.try_parse(|input| matching::parse_data_type(DataType::Angle, input)) {
+            return Ok(angle);
+        }
+        return matching::parse_data_type(DataType::Percentage, input);
+    }
+    matching::parse_data_type(DataType::LengthPercentage, input)
+}
+
+/// Consumes a color stop or a transition hint.
+fn parse_color_stop<'i, 't>(kind: GradientKind, input: &mut Parser<'i, 't>) -> Result<ColorStop, Error<'i>> {
+    let color = match input.try_parse(color::parse_color) {
+        Ok(color) => color,
+        Err(..) => {
+            let position = parse_stop_position(kind, input)?;
+            return Ok(ColorStop { color: None, positions: vec![position] });
+        }
+    };
+    let mut positions = vec![];
+    while positions.len() < 2 {
+        match input.try_parse(|input| parse_stop_position(kind, input)) {
+            Ok(position) => positions.push(position),
+            Err(..) => break,
+        }
+    }
+    Ok(ColorStop { color: Some(color), positions })
+}
+
+/// Consumes the arguments of a gradient function.
+fn parse_gradient<'i, 't>(
+    kind: GradientKind,
+    repeating: bool,
+    input: &mut Parser<'i, 't>,
+) -> Result<Gradient, Error<'i>> {
+    let start = input.position();
+    let prelude = input.try_parse(|input| {
+        parse_prelude(kind, input)?;
+        let prelude = input.slice_from(start);
+        input.expect_comma()?;
+        Ok::<_, Error<'i>>(prelude)
+    }).unwrap_or("");
+    let location = input.current_source_location();
+    let stops = input.parse_comma_separated(|input| parse_color_stop(kind, input))?;
+    // There must be at least two color stops, and each hint must be between
+    // two of them.
+    let is_hint = |stop: Option<&ColorStop>| stop.is_none_or(|stop| stop.color.is_none());
+    let valid = stops.len() >= 2 &&
+        !is_hint(stops.first()) &&
+        !is_hint(stops.last()) &&
+        stops.windows(2).all(|pair| !is_hint(Some(&pair[0])) || !is_hint(Some(&pair[1])));
+    if !valid {
+        return Err(location.new_custom_error(()));
+    }
+    Ok(Gradient { kind, repeating, prelude: prelude.trim_end().into(), stops })
+}
+
+/// Consumes the single string argument of a function, like `type()`.
+fn parse_string_argument<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Box<str>, Error<'i>> {
+    let string = input.parse_nested_block(|input| {
+        input.expect_string_cloned().map_err(Into::<Error<'i>>::into)
+    })?;
+    Ok(string.as_ref().into())
+}
+
+/// Consumes an option of an `image-set()`.
+fn parse_image_set_option<'i, 't>(input: &mut Parser<'i, 't>) -> Result<ImageSetOption, Error<'i>> {
+    let image = match input.try_parse(|input| input.expect_string_cloned()) {
+        Ok(url) => Image::Url(Url { url: url.as_ref().into(), quoted: true }),
+        Err(..) => parse_image(input)?,
+    };
+    let mut resolution = None;
+    let mut mime_type = None;
+    loop {
+        if resolution.is_none() {
+            let parsed = input.try_parse(|input| matching::parse_data_type(DataType::Resolution, input));
+            if let Ok(MatchedValue::Resolution { value, unit }) = parsed {
+                resolution = Some((value, unit));
+                continue;
+            }
+        }
+        if mime_type.is_none() {
+            let parsed = input.try_parse(|input| {
+                input.expect_function_matching("type")?;
+                parse_string_argument(input)
+            });
+            if let Ok(parsed) = parsed {
+                mime_type = Some(parsed);
+                continue;
+            }
+        }
+        break;
+    }
+    Ok(ImageSetOption { image, resolution, mime_type })
+}
+
+/// Returns the kind of gradient and whether it repeats for the given function
+/// name, if it's a gradient function.
+fn gradient_function(name: &str) -> Option<(GradientKind, bool)> {
+    const FUNCTIONS: &[(&str, GradientKind)] = &[
+        ("linear-gradient", GradientKind::Linear),
+        ("radial-gradient", GradientKind::Radial),
+        ("conic-gradient", GradientKind::Conic),
+    ];
+    let (name, repeating) = match name.get(..10) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("repeating-") => (&name[10..], true),
+        _ => (name, false),
+    };
+    FUNCTIONS
+        .iter()
+        .find(|(function, _)| name.eq_ignore_ascii_case(function))
+        .map(|&(_, kind)| (kind, repeating))
+}
+
+/// Consumes an `<image>`.
+pub(crate) fn parse_image<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Image, Error<'i>> {
+    if let Ok(url) = input.try_parse(url::parse_url) {
+        return Ok(Image::Url(url));
+    }
+    let location = input.current_source_location();
+    let token = input.next()?.clone();
+    let name = match token {
+        Token::Function(ref name) => name,
+        _ => return Err(location.new_unexpected_token_error(token)),
+    };
+    if let Some((kind, repeating)) = gradient_function(name) {
+        let gradient = input.parse_nested_block(|input| parse_gradient(kind, repeating, input))?;
+        return Ok(Image::Gradient(Box::new(gradient)));
+    }
+    if name.eq_ignore_ascii_case("image-set") {
+        let options = input.parse_nested_block(|input| input.parse_comma_separated(parse_image_set_option))?;
+        return Ok(Image::ImageSet(options));
+    }
+    Err(location.new_unexpected_token_error(token))
+}
+
+#[test]
+fn image_test() {
+    use super::units::LengthUnit;
+
+    fn parse(input: &str) -> Result<Image, ()> {
+        let mut input = cssparser::ParserInput::new(input);
+        Parser::new(&mut input).parse_entirely(parse_image).map_err(|_| ())
+    }
+
+    assert_eq!(parse("url(foo.png)"), Ok(Image::Url(Url { url: "foo.png".into(), quoted: false })));
+    assert_eq!(parse("src('foo.png')"), Ok(Image::Url(Url { url: "foo.png".into(), quoted: true })));
+
+    let red = cssparser::RGBA::new(255, 0, 0, 255);
+    let blue = cssparser::RGBA::new(0, 0, 255, 255);
+    assert_eq!(
+        parse("repeating-linear-gradient(to top left, red 10px, 50%, blue)"),
+        Ok(Image::Gradient(Box::new(Gradient {
+            kind: GradientKind::Linear,
+            repeating: true,
+            prelude: "to top left".into(),
+            stops: vec![
+                ColorStop {
+                    color: Some(Color::Rgba(red)),
+                    positions: vec![MatchedValue::Length { value: 10., unit: LengthUnit::Px }],
+                },
+                ColorStop { color: None, positions: vec![MatchedValue::Percentage(0.5)] },
+                ColorStop { color: Some(Color::Rgba(blue)), positions: vec![] },
+            ],
+        }))),
+    );
+    assert_eq!(
+        parse("image-set('a.png' 1x, url(b.png) type('image/png') 2dppx)"),
+        Ok(Image::ImageSet(vec![
+            ImageSetOption {
+                image: Image::Url(Url { url: "a.png".into(), quoted: true }),
+                resolution: Some((1., ResolutionUnit::X)),
+                mime_type: None,
+            },
+            ImageSetOption {
+                image: Image::Url(Url { url: "b.png".into(), quoted: false }),
+                resolution: Some((2., ResolutionUnit::Dppx)),
+                mime_type: Some("image/png".into()),
+            },
+        ])),
+    );
+    for valid in &[
+        "linear-gradient(red, blue)",
+        "linear-gradient(45deg, red 0 50%, blue)",
+        "radial-gradient(circle closest-side at center, red, blue)",
+        "radial-gradient(at 10px 20%, red, blue)",
+        "radial-gradient(red, blue 10px)",
+        "conic-gradient(from 90deg at left top, red 0.25turn, blue 50%)",
+        "REPEATING-CONIC-GRADIENT(red, blue)",
+        "image-set(linear-gradient(red, blue))",
+    ] {
+        assert!(parse(valid).is_ok(), "{}", valid);
+    }
+    for invalid in &[
+        "linear-gradient(red)",
+        "linear-gradient(red, 10%, 20%, blue)",
+        "linear-gradient(10%, red, blue)",
+        "linear-gradient(red, blue, 10%)",
+        "linear-gradient(to middle, red, blue)",
+        "conic-gradient(red 10px, blue)",
+        "radial-gradient(foo, red, blue)",
+        "repeating-foo-gradient(red, blue)",
+        "image-set()",
+        "image-set('a.png' 1x 2x)",
+        "element(#foo)",
+        "'foo.png'",
+    ] {
+        assert_eq!(parse(invalid), Err(()), "{}", invalid);
+    }
+}
@@ -0,0 +1,146 @@
+//! Import of registrations from JSON documents, behind the `json` feature.
+//!
+//! Documents are an array of `{ name, syntax, inherits, initialValue }`
+//! objects, the members of the `PropertyDefinition` dictionary, as design
+//! token pipelines produce them. `syntax` defaults to `"*"`, `initialValue`
+//! is optional, and other members are ignored.
+
+use std::fmt;
+
+use serde::Deserialize;
+
+use super::registry::{PropertyDefinition, PropertyRegistry, RegistrationError};
+
+/// Why an entry of a JSON document can't be registered.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonEntryErrorKind {
+    /// The entry is not a property definition object, with the error of
+    /// the JSON parser.
+    Malformed(String),
+    Registration(RegistrationError),
+}
+
+/// An entry of a JSON document that can't be registered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonEntryError {
+    /// The index of the entry in the document.
+    pub index: usize,
+    /// The name of the property, if the entry has one.
+    pub name: Option<String>,
+    pub kind: JsonEntryErrorKind,
+}
+
+impl fmt::Display for JsonEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "entry {}", self.index)?;
+        if let Some(ref name) = self.name {
+            write!(f, " ({})", name)?;
+        }
+        match self.kind {
+            JsonEntryErrorKind::Malformed(ref error) => write!(f, ": {}", error),
+            JsonEntryErrorKind::Registration(ref error) => write!(f, ": {}", error),
+        }
+    }
+}
+
+/// Why a JSON document can't be imported.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonImportError {
+    /// The document is not an array, with the error of the JSON parser.
+    Malformed(String),
+    /// Some of the entries can't be registered. The others are registered
+    /// anyway.
+    Entries(Vec<JsonEntryError>),
+}
+
+impl fmt::Display for JsonImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JsonImportError::Malformed(ref error) => write!(f, "malformed document: {}", error),
+            JsonImportError::Entries(ref errors) => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    error.fmt(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonImportError {}
+
+impl PropertyRegistry {
+    /// Registers the custom properties defined by a JSON document, in order,
+    /// as if with `register`. Entries that can't be registered don't prevent
+    /// the others from being registered, and are all reported.
+    pub fn import_json(&mut self, json: &str) -> Result<(), JsonImportError> {
+        let entries: Vec<serde_json::Value> =
+            serde_json::from_str(json).map_err(|e| JsonImportError::Malformed(e.to_string()))?;
+        let mut errors = vec![];
+        for (index, entry) in entries.into_iter().enumerate() {
+            let name = entry.get("name").and_then(serde_json::Value::as_str).map(str::to_owned);
+            let kind = match PropertyDefinition::deserialize(entry) {
+                Ok(definition) => match self.register(definition) {
+                    Ok(()) => continue,
+                    Err(error) => JsonEntryErrorKind::Registration(error),
+                },
+                Err(error) => JsonEntryErrorKind::Malformed(error.to_string()),
+            };
+            errors.push(JsonEntryError { index, name, kind });
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(JsonImportError::Entries(errors))
+        }
+    }
+}
+
+#[test]
+fn json_test() {
+    let mut registry = PropertyRegistry::new();
+    registry.import_json(r##"[
+        { "name": "--space-1", "syntax": "<length>", "inherits": false, "initialValue": "4px" },
+        { "name": "--brand", "syntax": "<color>", "inherits": true, "initialValue": "#09f", "type": "color" },
+        { "name": "--anything", "inherits": true }
+    ]"##).unwrap();
+    assert_eq!(registry.len(), 3);
+    assert!(registry.get("--anything").unwrap().syntax.is_universal());
+    assert_eq!(registry.get("--brand").unwrap().initial_value.as_ref().unwrap().to_css_string(), "rgb(0, 153, 255)");
+
+    let result = registry.import_json(r#"[
+        { "name": "--space-2", "syntax": "<length>", "inherits": false, "initialValue": "8px" },
+        { "name": "--space-1", "syntax": "<length>", "inherits": false, "initialValue": "4px" },
+        { "name": "--space-3", "syntax": "<length>", "inherits": "no", "initialValue": "12px" },
+        { "syntax": "<length>", "inherits": false },
+        { "name": "space-4", "inherits": false },
+        3
+    ]"#);
+    let errors = match result {
+        Err(JsonImportError::Entries(errors)) => errors,
+        result => panic!("{:?}", result),
+    };
+    let summary = errors.iter().map(|error| (error.index, error.name.as_deref())).collect::<Vec<_>>();
+    assert_eq!(summary, [(1, Some("--space-1")), (2, Some("--space-3")), (3, None), (4, Some("space-4")), (5, None)]);
+    assert!(matches!(
+        errors[0].kind,
+        JsonEntryErrorKind::Registration(RegistrationError::AlreadyRegistered(ref conflict))
+            if conflict.is_redundant(),
+    ));
+    assert!(matches!(errors[1].kind, JsonEntryErrorKind::Malformed(..)));
+    assert_eq!(errors[3].kind, JsonEntryErrorKind::Registration(RegistrationError::InvalidName));
+    assert_eq!(
+        errors[0].to_string(),
+        "entry 1 (--space-1): property already registered with the same definition",
+    );
+    assert!(registry.contains("--space-2"));
+    assert_eq!(registry.len(), 4);
+
+    for json in &["{}", "[", ""] {
+        assert!(matches!(registry.import_json(json), Err(JsonImportError::Malformed(..))), "{:?}", json);
+    }
+    registry.import_json("[]").unwrap();
+}
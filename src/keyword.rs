@@ -0,0 +1,92 @@
+//! Detection of CSS-wide keywords in values.
+
+use std::fmt;
+
+use cssparser::Parser;
+
+/// A CSS-wide keyword, or the reserved `default` keyword.
+///
+/// These apply to a property as a whole, so a value that's just one of them
+/// never matches a syntax descriptor, and can't be the initial value of a
+/// registered custom property.
+///
+/// https://drafts.csswg.org/css-values-4/#common-keywords
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CssWideKeyword {
+    Initial,
+    Inherit,
+    Unset,
+    Revert,
+    RevertLayer,
+    /// Reserved for future use as a CSS-wide keyword.
+    Default,
+}
+
+impl CssWideKeyword {
+    pub const ALL: &'static [CssWideKeyword] = &[
+        CssWideKeyword::Initial,
+        CssWideKeyword::Inherit,
+        CssWideKeyword::Unset,
+        CssWideKeyword::Revert,
+        CssWideKeyword::RevertLayer,
+        CssWideKeyword::Default,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            CssWideKeyword::Initial => "initial",
+            CssWideKeyword::Inherit => "inherit",
+            CssWideKeyword::Unset => "unset",
+            CssWideKeyword::Revert => "revert",
+            CssWideKeyword::RevertLayer => "revert-layer",
+            CssWideKeyword::Default => "default",
+        }
+    }
+
+    /// Returns the keyword for the value of an `<ident>` token, ASCII
+    /// case-insensitively.
+    pub fn from_ident(ident: &str) -> Option<Self> {
+        Self::ALL.iter().find(|k| ident.eq_ignore_ascii_case(k.as_str())).cloned()
+    }
+
+    /// Returns the keyword that the whole of `input` is, ignoring whitespace
+    /// and comments around it, consuming `input` only if there's one.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Option<Self> {
+        input.try_parse(|input| {
+            let keyword = Self::from_ident(input.expect_ident().map_err(|_| ())?).ok_or(())?;
+            input.expect_exhausted().map_err(|_| ())?;
+            Ok::<_, ()>(keyword)
+        }).ok()
+    }
+
+    /// Returns the keyword that the whole of `value` is, if any, like
+    /// `parse`.
+    pub fn parse_str(value: &str) -> Option<Self> {
+        let mut input = cssparser::ParserInput::new(value);
+        Self::parse(&mut Parser::new(&mut input))
+    }
+}
+
+impl fmt::Display for CssWideKeyword {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[test]
+fn css_wide_keyword_test() {
+    assert_eq!(CssWideKeyword::from_ident("REVERT-layer"), Some(CssWideKeyword::RevertLayer));
+    assert_eq!(CssWideKeyword::from_ident("revert-layers"), None);
+    assert_eq!(CssWideKeyword::parse_str(" /* */ inherit "), Some(CssWideKeyword::Inherit));
+    assert_eq!(CssWideKeyword::parse_str("Default"), Some(CssWideKeyword::Default));
+    assert_eq!(CssWideKeyword::parse_str("\\69 nitial"), Some(CssWideKeyword::Initial));
+    for value in &["", "inherit inherit", "inherit;", "\"unset\"", "initial()"] {
+        assert_eq!(CssWideKeyword::parse_str(value), None, "{:?}", value);
+    }
+
+    let mut input = cssparser::ParserInput::new("unset foo");
+    let mut input = Parser::new(&mut input);
+    assert_eq!(CssWideKeyword::parse(&mut input), None);
+    assert_eq!(input.expect_ident_cloned().unwrap(), "unset");
+    assert_eq!(CssWideKeyword::Unset.to_string(), "unset");
+}
@@ -1,18 +1,102 @@
 use std::borrow::Cow;
-use std::fmt::Debug;
+use std::convert::TryFrom;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::ops::Range;
+use std::str::FromStr;
 
+#[cfg(feature = "rkyv")]
+mod archive;
 mod ascii;
+mod binary;
+mod builder;
+mod calc;
+mod color;
+mod compute;
+#[cfg(feature = "codegen")]
+mod codegen;
+mod cst;
 mod default_impl;
+mod describe;
+mod dot;
+mod format;
+mod image;
+#[cfg(feature = "json")]
+mod json;
+mod keyword;
+#[cfg(feature = "lightningcss")]
+mod lightningcss_interop;
+mod location;
+mod matching;
+mod options;
+#[cfg(feature = "experimental-position")]
+mod position;
+mod property_rule;
+mod registry;
+#[cfg(feature = "schemars")]
+mod schema;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod shared_registry;
+#[cfg(feature = "stylo")]
+mod stylo;
+mod substitution;
+mod syntax_string;
+#[cfg(feature = "codegen")]
+mod tokens;
+mod transform;
+pub mod typed_om;
+mod units;
+mod url;
+mod value;
+mod visitor;
+#[cfg(feature = "wasm")]
+mod wasm;
 
-use default_impl::DefaultImpl;
+#[cfg(feature = "rkyv")]
+pub use archive::{ArchivedComponent, ArchivedDescriptor};
+pub use binary::{DecodeError, BINARY_FORMAT_VERSION};
+pub use builder::{BuildError, DescriptorBuilder};
+pub use calc::{CalcNode, CalcType, MathFunction, RoundingStrategy};
+pub use color::{AbsoluteColor, Color, ColorSpace};
+pub use compute::ComputeContext;
+#[cfg(feature = "codegen")]
+pub use codegen::CodegenError;
+pub use cst::{
+    parse_descriptor_lossless, parse_descriptor_lossless_with, ConcreteDescriptor, CstNode,
+};
+pub use default_impl::{CustomIdent, CustomIdentError, DataType, DefaultImpl};
+pub use format::FormatOptions;
+pub use image::{ColorStop, Gradient, GradientKind, Image, ImageSetOption};
+#[cfg(feature = "json")]
+pub use json::{JsonEntryError, JsonEntryErrorKind, JsonImportError};
+pub use keyword::CssWideKeyword;
+pub use location::SourceLocation;
+pub use matching::{MatchError, MatchErrorKind, MatchExplanation, SpannedMatch};
+pub use options::{MatchLimits, ParseOptions};
+#[cfg(feature = "experimental-position")]
+pub use position::{Position, PositionComponent, PositionKeyword};
+pub use property_rule::{parse_property_rule, PropertyRuleError};
+pub use registry::{
+    DeclaredValue, ObserverId, PropertyDefinition, PropertyRegistration, PropertyRegistry,
+    RegistrationConflict, RegistrationError, RegistryChange, RegistryDiff,
+};
+pub use shared_registry::SharedPropertyRegistry;
+pub use substitution::{PendingSubstitution, SubstitutedValues};
+pub use syntax_string::SyntaxString;
+pub use transform::{TransformFunction, TransformFunctionName};
+pub use units::{AngleUnit, FrequencyUnit, LengthUnit, LengthUnitKind, ResolutionUnit, TimeUnit};
+pub use url::Url;
+pub use value::{ComputedValue, MatchedValue};
+pub use visitor::ComponentVisitor;
 
 /// A trait that allows to customize the parsing of syntax descriptors to use
 /// custom data types, and identifiers.
-pub trait Impl: Clone + Debug + PartialEq + 'static {
+pub trait Impl: Clone + Debug + Eq + Hash + Ord + 'static {
     /// The custom identifier type.
-    type CustomIdent: Clone + Debug + PartialEq + 'static;
+    type CustomIdent: Clone + Debug + Eq + Hash + Ord + 'static;
     /// The custom DataType name.
-    type DataType: Clone + Debug + PartialEq + 'static;
+    type DataType: Clone + Debug + Eq + Hash + Ord + 'static;
     /// Consumes a custom identifier from a string that is a valid `<ident>`.
     fn custom_ident_from_ident(ident: &str) -> Option<Self::CustomIdent>;
     /// Consumes a custom data type name.
@@ -20,43 +104,442 @@ pub trait Impl: Clone + Debug + PartialEq + 'static {
     /// If the data type is premultiplied, return the un-premultiplied
     /// component.
     fn unpremultiply_data_type(data_type: &Self::DataType) -> Option<Component<Self>>;
+    /// Serializes a data type name, without the surrounding angle brackets.
+    fn serialize_data_type_name<W: fmt::Write>(data_type: &Self::DataType, dest: &mut W) -> fmt::Result;
+    /// Serializes a custom identifier.
+    fn serialize_custom_ident<W: fmt::Write>(ident: &Self::CustomIdent, dest: &mut W) -> fmt::Result;
 }
 
 /// https://drafts.css-houdini.org/css-properties-values-api-1/#parsing-syntax
-#[derive(Debug, PartialEq)]
-pub struct Descriptor<I: Impl>(Box<[Component<I>]>);
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Descriptor<I: Impl>(Vec<Component<I>>);
 impl<I: Impl> Descriptor<I> {
-    fn universal() -> Self {
-        Descriptor(Box::new([]))
+    /// Returns the universal syntax descriptor, `*`.
+    ///
+    /// https://drafts.css-houdini.org/css-properties-values-api-1/#universal-syntax-descriptor
+    #[inline]
+    pub const fn universal() -> Self {
+        Descriptor(Vec::new())
+    }
+
+    /// Returns the components of this descriptor. The universal syntax
+    /// descriptor has no components.
+    #[inline]
+    pub fn components(&self) -> &[Component<I>] {
+        &self.0
+    }
+
+    /// Returns an iterator over the components of this descriptor.
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, Component<I>> {
+        self.0.iter()
+    }
+
+    /// Returns the number of components in this descriptor.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether this descriptor has no components, which is only the
+    /// case for the universal syntax descriptor, so it's the same as
+    /// `is_universal`.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.is_universal()
+    }
+
+    /// Returns whether this is the universal syntax descriptor, `*`.
+    #[inline]
+    pub fn is_universal(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the data type if this descriptor is exactly one data type name
+    /// without a multiplier, like `<length>`.
+    pub fn single_data_type(&self) -> Option<&I::DataType> {
+        match *self.0.as_slice() {
+            [Component {
+                name: ComponentName::DataType(ref ty),
+                multiplier: None,
+            }] => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// Returns the component at `index`, if any.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&Component<I>> {
+        self.0.get(index)
+    }
+
+    fn check_insertion(&self, component: &Component<I>) -> Result<(), BuildError> {
+        if self.is_universal() {
+            return Err(BuildError::UniversalNotAlone);
+        }
+        if component.multiplier.is_some() && component.name.is_pre_multiplied() {
+            return Err(BuildError::MultiplierOnPremultipliedType);
+        }
+        Ok(())
+    }
+
+    /// Appends a component to this descriptor.
+    ///
+    /// Fails if this is the universal descriptor, or if the component is not
+    /// valid.
+    pub fn push_component(&mut self, component: Component<I>) -> Result<(), BuildError> {
+        self.check_insertion(&component)?;
+        self.0.push(component);
+        Ok(())
+    }
+
+    /// Inserts a component at `index`, with the same checks as
+    /// `push_component`.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, component: Component<I>) -> Result<(), BuildError> {
+        self.check_insertion(&component)?;
+        self.0.insert(index, component);
+        Ok(())
+    }
+
+    /// Removes and returns the component at `index`.
+    ///
+    /// Fails without removing anything if that would leave the descriptor
+    /// empty, since an empty descriptor would be the universal one.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Result<Component<I>, BuildError> {
+        assert!(index < self.len(), "Index out of bounds");
+        if self.len() == 1 {
+            return Err(BuildError::Empty);
+        }
+        Ok(self.0.remove(index))
+    }
+
+    /// Retains only the components for which `f` returns true.
+    ///
+    /// Fails without removing anything if no component would be retained.
+    pub fn retain(&mut self, mut f: impl FnMut(&Component<I>) -> bool) -> Result<(), BuildError> {
+        let keep = self.0.iter().map(&mut f).collect::<Vec<_>>();
+        if !keep.iter().any(|k| *k) {
+            return Err(BuildError::Empty);
+        }
+        let mut keep = keep.into_iter();
+        self.0.retain(|_| keep.next().unwrap());
+        Ok(())
+    }
+
+    /// Serializes this descriptor into `dest`, separating components with
+    /// ` | `, or as `*` if this is the universal syntax descriptor.
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        self.to_css_with_separator(" | ", dest)
+    }
+
+    pub(crate) fn to_css_with_separator<W: fmt::Write>(&self, separator: &str, dest: &mut W) -> fmt::Result {
+        if self.0.is_empty() {
+            return dest.write_char('*');
+        }
+        for (i, component) in self.0.iter().enumerate() {
+            if i != 0 {
+                dest.write_str(separator)?;
+            }
+            component.to_css(dest)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes this descriptor into a string.
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+
+    /// Serializes this descriptor without any optional whitespace, like
+    /// `foo|<length>#`.
+    pub fn serialize_minified(&self) -> String {
+        let mut s = String::new();
+        self.to_css_with_separator("|", &mut s).unwrap();
+        s
+    }
+
+    /// Serializes this descriptor with the canonical spacing used by the
+    /// spec, like `foo | <length>#`.
+    pub fn serialize_canonical(&self) -> String {
+        self.to_css_string()
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum ParseError {
+impl<I: Impl> cssparser::ToCss for Descriptor<I> {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        Descriptor::to_css(self, dest)
+    }
+}
+
+impl<I: Impl> IntoIterator for Descriptor<I> {
+    type Item = Component<I>;
+    type IntoIter = std::vec::IntoIter<Component<I>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, I: Impl> IntoIterator for &'a Descriptor<I> {
+    type Item = &'a Component<I>;
+    type IntoIter = std::slice::Iter<'a, Component<I>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<I: Impl> std::ops::Index<usize> for Descriptor<I> {
+    type Output = Component<I>;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Component<I> {
+        &self.0[index]
+    }
+}
+
+/// Compares a descriptor with a syntax string, by parsing the string. Strings
+/// that fail to parse never compare equal.
+impl<I: Impl> PartialEq<str> for Descriptor<I> {
+    fn eq(&self, other: &str) -> bool {
+        parse_descriptor_with::<I>(other).is_ok_and(|other| *self == other)
+    }
+}
+
+impl<'a, I: Impl> PartialEq<&'a str> for Descriptor<I> {
+    #[inline]
+    fn eq(&self, other: &&'a str) -> bool {
+        *self == **other
+    }
+}
+
+impl<I: Impl> Default for Descriptor<I> {
+    #[inline]
+    fn default() -> Self {
+        Self::universal()
+    }
+}
+
+impl<I: Impl> FromStr for Descriptor<I> {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(input: &str) -> Result<Self, ParseError> {
+        parse_descriptor_with(input)
+    }
+}
+
+impl<'a, I: Impl> TryFrom<&'a str> for Descriptor<I> {
+    type Error = ParseError;
+
+    #[inline]
+    fn try_from(input: &'a str) -> Result<Self, ParseError> {
+        parse_descriptor_with(input)
+    }
+}
+
+/// The kind of error that made parsing a syntax descriptor fail.
+///
+/// New variants may be added in the future, see `code()` for a stable
+/// identifier of each kind.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
     EmptyInput,
+    /// A `|` before the first component, reported at the pipe.
+    LeadingPipe,
+    /// A `|` after the last component, reported at the pipe.
+    TrailingPipe,
+    /// A `|` right after another one, reported at the second pipe.
+    EmptyAlternative,
     ExpectedPipeBetweenComponents,
+    /// A multiplier after a pre-multiplied data type name, like
+    /// `<transform-list>+`, reported at the multiplier.
+    MultiplierOnPremultipliedType,
+    /// A `*` combined with other components, reported at the `*`.
+    UniversalNotAlone,
     InvalidCustomIdent,
-    InvalidNameStart,
-    InvalidName,
-    UnclosedDataTypeName,
+    /// A component starting with a character that can't start a name, which
+    /// is carried along.
+    InvalidNameStart(char),
+    /// An invalid or reserved identifier, like `inherit`, with its source.
+    InvalidName(Box<str>),
+    /// A data type name without a closing `>`, with the text after the `<`.
+    UnclosedDataTypeName(Box<str>),
     UnexpectedEOF,
-    UnknownDataTypeName,
+    /// A data type name not known by the implementation, without the angle
+    /// brackets.
+    UnknownDataTypeName(Box<str>),
+}
+
+impl ParseErrorKind {
+    /// Returns a numeric code for this kind of error, suitable for FFI or
+    /// telemetry. These codes are stable, and new kinds get new codes:
+    ///
+    ///  * 1: `EmptyInput`
+    ///  * 2: `LeadingPipe`
+    ///  * 3: `TrailingPipe`
+    ///  * 4: `EmptyAlternative`
+    ///  * 5: `ExpectedPipeBetweenComponents`
+    ///  * 6: `MultiplierOnPremultipliedType`
+    ///  * 7: `UniversalNotAlone`
+    ///  * 8: `InvalidCustomIdent`
+    ///  * 9: `InvalidNameStart`
+    ///  * 10: `InvalidName`
+    ///  * 11: `UnclosedDataTypeName`
+    ///  * 12: `UnexpectedEOF`
+    ///  * 13: `UnknownDataTypeName`
+    pub fn code(&self) -> u32 {
+        match *self {
+            ParseErrorKind::EmptyInput => 1,
+            ParseErrorKind::LeadingPipe => 2,
+            ParseErrorKind::TrailingPipe => 3,
+            ParseErrorKind::EmptyAlternative => 4,
+            ParseErrorKind::ExpectedPipeBetweenComponents => 5,
+            ParseErrorKind::MultiplierOnPremultipliedType => 6,
+            ParseErrorKind::UniversalNotAlone => 7,
+            ParseErrorKind::InvalidCustomIdent => 8,
+            ParseErrorKind::InvalidNameStart(..) => 9,
+            ParseErrorKind::InvalidName(..) => 10,
+            ParseErrorKind::UnclosedDataTypeName(..) => 11,
+            ParseErrorKind::UnexpectedEOF => 12,
+            ParseErrorKind::UnknownDataTypeName(..) => 13,
+        }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseErrorKind::EmptyInput => f.write_str("syntax descriptor is empty"),
+            ParseErrorKind::LeadingPipe => f.write_str("syntax descriptor can't start with '|'"),
+            ParseErrorKind::TrailingPipe => f.write_str("syntax descriptor can't end with '|'"),
+            ParseErrorKind::EmptyAlternative => {
+                f.write_str("expected a syntax component between '|'s")
+            }
+            ParseErrorKind::ExpectedPipeBetweenComponents => {
+                f.write_str("expected '|' between syntax components")
+            }
+            ParseErrorKind::MultiplierOnPremultipliedType => {
+                f.write_str("pre-multiplied data type names can't have a multiplier")
+            }
+            ParseErrorKind::UniversalNotAlone => f.write_str(
+                "the universal syntax descriptor '*' can't be combined with other components",
+            ),
+            ParseErrorKind::InvalidCustomIdent => f.write_str("invalid custom identifier"),
+            ParseErrorKind::InvalidNameStart(c) => {
+                write!(f, "syntax component can't start with {:?}", c)
+            }
+            ParseErrorKind::InvalidName(ref name) => {
+                write!(f, "invalid identifier '{}' in syntax component", name)
+            }
+            ParseErrorKind::UnclosedDataTypeName(ref name) => {
+                write!(f, "data type name '<{}' is missing a closing '>'", name)
+            }
+            ParseErrorKind::UnexpectedEOF => f.write_str("unexpected end of syntax descriptor"),
+            ParseErrorKind::UnknownDataTypeName(ref name) => {
+                write!(f, "unknown data type name '<{}>'", name)
+            }
+        }
+    }
 }
 
+/// An error that made parsing a syntax descriptor fail, along with where in
+/// the input it happened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+    /// The location in the original input of the construct that caused the
+    /// error.
+    pub location: SourceLocation,
+}
+
+impl ParseError {
+    fn new(kind: ParseErrorKind, input: &str, offset: usize) -> Self {
+        Self {
+            kind,
+            location: SourceLocation::from_offset(input, offset),
+        }
+    }
+
+    /// Returns the stable numeric code of the kind of this error. See
+    /// `ParseErrorKind::code()`.
+    #[inline]
+    pub fn code(&self) -> u32 {
+        self.kind.code()
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.location.line, self.location.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 /// https://drafts.css-houdini.org/css-properties-values-api-1/#multipliers
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Multiplier {
     Space,
     Comma,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl Multiplier {
+    /// Returns the separator between the items of a list of values matching
+    /// a component with this multiplier, as serialized.
+    pub fn separator(&self) -> &'static str {
+        match *self {
+            Multiplier::Space => " ",
+            Multiplier::Comma => ", ",
+        }
+    }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_char(match *self {
+            Multiplier::Space => '+',
+            Multiplier::Comma => '#',
+        })
+    }
+}
+
+impl cssparser::ToCss for Multiplier {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        Multiplier::to_css(self, dest)
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Component<I: Impl> {
     name: ComponentName<I>,
     multiplier: Option<Multiplier>,
 }
 
 impl<I: Impl> Component<I> {
+    /// Creates a component, checking that pre-multiplied data type names
+    /// don't get a multiplier.
+    pub fn new(name: ComponentName<I>, multiplier: Option<Multiplier>) -> Result<Self, BuildError> {
+        if multiplier.is_some() && name.is_pre_multiplied() {
+            return Err(BuildError::MultiplierOnPremultipliedType);
+        }
+        Ok(Self { name, multiplier })
+    }
+
     #[inline]
     pub fn name(&self) -> &ComponentName<I> {
         &self.name
@@ -68,7 +551,7 @@ impl<I: Impl> Component<I> {
     }
 
     #[inline]
-    pub fn unpremultiplied(&self) -> Cow<Self> {
+    pub fn unpremultiplied(&self) -> Cow<'_, Self> {
         match self.name.unpremultiply() {
             Some(component) => {
                 debug_assert!(
@@ -80,19 +563,56 @@ impl<I: Impl> Component<I> {
             None => Cow::Borrowed(self),
         }
     }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        self.name.to_css(dest)?;
+        match self.multiplier {
+            Some(ref multiplier) => multiplier.to_css(dest),
+            None => Ok(()),
+        }
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl<I: Impl> cssparser::ToCss for Component<I> {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        Component::to_css(self, dest)
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
 pub enum ComponentName<I: Impl> {
     DataType(I::DataType),
     Ident(I::CustomIdent),
+    /// A data type name that isn't known by the implementation, as written
+    /// by the author. Only produced if
+    /// `ParseOptions::preserve_unknown_data_types` is set.
+    UnknownDataType(Box<str>),
 }
 
 impl<I: Impl> ComponentName<I> {
+    /// Returns the data type, if this is a data type name.
+    #[inline]
+    pub fn as_data_type(&self) -> Option<&I::DataType> {
+        match *self {
+            ComponentName::DataType(ref t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Returns the custom identifier, if this is an identifier.
+    #[inline]
+    pub fn as_ident(&self) -> Option<&I::CustomIdent> {
+        match *self {
+            ComponentName::Ident(ref ident) => Some(ident),
+            _ => None,
+        }
+    }
+
     fn unpremultiply(&self) -> Option<Component<I>> {
         match *self {
             ComponentName::DataType(ref t) => I::unpremultiply_data_type(t),
-            ComponentName::Ident(..) => None,
+            ComponentName::Ident(..) | ComponentName::UnknownDataType(..) => None,
         }
     }
 
@@ -100,6 +620,28 @@ impl<I: Impl> ComponentName<I> {
     fn is_pre_multiplied(&self) -> bool {
         self.unpremultiply().is_some()
     }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            ComponentName::DataType(ref t) => {
+                dest.write_char('<')?;
+                I::serialize_data_type_name(t, dest)?;
+                dest.write_char('>')
+            }
+            ComponentName::Ident(ref ident) => I::serialize_custom_ident(ident, dest),
+            ComponentName::UnknownDataType(ref name) => {
+                dest.write_char('<')?;
+                dest.write_str(name)?;
+                dest.write_char('>')
+            }
+        }
+    }
+}
+
+impl<I: Impl> cssparser::ToCss for ComponentName<I> {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        ComponentName::to_css(self, dest)
+    }
 }
 
 /// Parse a syntax descriptor with the default implementation.
@@ -109,17 +651,116 @@ pub fn parse_descriptor(input: &str) -> Result<Descriptor<DefaultImpl>, ParseErr
 }
 
 pub fn parse_descriptor_with<I: Impl>(input: &str) -> Result<Descriptor<I>, ParseError> {
+    parse_descriptor_internal(input, &ParseOptions::default(), None, None)
+}
+
+/// Parse a syntax descriptor with the default implementation and the given
+/// options.
+#[inline]
+pub fn parse_descriptor_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Descriptor<DefaultImpl>, ParseError> {
+    parse_descriptor_with_options_for::<DefaultImpl>(input, options)
+}
+
+/// Parse a syntax descriptor with the given options.
+pub fn parse_descriptor_with_options_for<I: Impl>(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Descriptor<I>, ParseError> {
+    parse_descriptor_internal(input, options, None, None)
+}
+
+/// A single problem found while parsing a syntax descriptor, as reported by
+/// `parse_descriptor_all_errors`.
+pub type Diagnostic = ParseError;
+
+/// Parse a syntax descriptor with the default implementation, reporting all
+/// the errors found rather than only the first one.
+#[inline]
+pub fn parse_descriptor_all_errors(input: &str) -> Result<Descriptor<DefaultImpl>, Vec<Diagnostic>> {
+    parse_descriptor_all_errors_with::<DefaultImpl>(input)
+}
+
+/// Parse a syntax descriptor, reporting all the errors found rather than only
+/// the first one.
+///
+/// After an invalid component, parsing resumes after the next `|`, so errors
+/// are reported at most once per component.
+pub fn parse_descriptor_all_errors_with<I: Impl>(input: &str) -> Result<Descriptor<I>, Vec<Diagnostic>> {
+    let recovered = parse_descriptor_recovering_with(input);
+    match recovered.descriptor {
+        Some(descriptor) if recovered.errors.is_empty() => Ok(descriptor),
+        _ => Err(recovered.errors),
+    }
+}
+
+/// The result of parsing a syntax descriptor while recovering from errors.
+#[derive(Debug, PartialEq)]
+pub struct RecoveredDescriptor<I: Impl> {
+    /// A descriptor with the components that could be parsed, or `None` if
+    /// no component could be salvaged.
+    pub descriptor: Option<Descriptor<I>>,
+    /// The errors found while parsing, in source order.
+    pub errors: Vec<ParseError>,
+}
+
+/// Parse a syntax descriptor with the default implementation, skipping
+/// invalid components.
+#[inline]
+pub fn parse_descriptor_recovering(input: &str) -> RecoveredDescriptor<DefaultImpl> {
+    parse_descriptor_recovering_with::<DefaultImpl>(input)
+}
+
+/// Parse a syntax descriptor, skipping invalid components, and return both
+/// the components that could be parsed and the errors found.
+///
+/// This is meant for tooling that needs to keep working on half-typed syntax
+/// strings. See `parse_descriptor_all_errors_with` for how recovery works.
+pub fn parse_descriptor_recovering_with<I: Impl>(input: &str) -> RecoveredDescriptor<I> {
+    let mut errors = vec![];
+    let descriptor = match parse_descriptor_internal(input, &ParseOptions::default(), Some(&mut errors), None) {
+        // Only the universal descriptor is legitimately empty, and it can't
+        // have errors.
+        Ok(ref descriptor) if descriptor.is_empty() && !errors.is_empty() => None,
+        Ok(descriptor) => Some(descriptor),
+        Err(error) => {
+            errors.push(error);
+            None
+        }
+    };
+    RecoveredDescriptor { descriptor, errors }
+}
+
+/// Parses a syntax descriptor. If `errors` is given, recoverable errors are
+/// collected there instead of aborting the parse, and the returned descriptor
+/// contains only the components that parsed successfully.
+///
+/// If `spans` is given, the source range of each parsed component is recorded
+/// there.
+fn parse_descriptor_internal<I: Impl>(
+    input: &str,
+    options: &ParseOptions,
+    errors: Option<&mut Vec<ParseError>>,
+    spans: Option<&mut Vec<Range<usize>>>,
+) -> Result<Descriptor<I>, ParseError> {
     // 1. Strip leading and trailing ASCII whitespace from string.
-    let input = ascii::trim_ascii_whitespace(input);
+    //
+    // We keep the original string around and just keep track of where the
+    // trimmed part starts, so that error offsets are relative to the
+    // original input.
+    let trimmed = ascii::trim_ascii_whitespace(input);
 
     // 2. If string's length is 0, return failure.
-    if input.is_empty() {
-        return Err(ParseError::EmptyInput);
+    if trimmed.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::EmptyInput, input, 0));
     }
+    let start = trimmed.as_ptr() as usize - input.as_ptr() as usize;
 
     // 3. If string's length is 1, and the only code point in string is U+002A
     //    ASTERISK (*), return the universal syntax descriptor.
-    if input.len() == 1 && input.as_bytes()[0] == b'*' {
+    if trimmed.len() == 1 && trimmed.as_bytes()[0] == b'*' {
         return Ok(Descriptor::universal());
     }
 
@@ -131,35 +772,34 @@ pub fn parse_descriptor_with<I: Impl>(input: &str) -> Result<Descriptor<I>, Pars
     // nulls in the parser specially.
     let mut components = vec![];
     {
-        let mut parser = Parser::new(input, &mut components);
+        let input = &input[..start + trimmed.len()];
+        let mut parser = Parser::new(input, start, options, &mut components, errors, spans);
         // 5. Repeatedly consume the next input code point from stream.
         parser.parse()?;
     }
-    Ok(Descriptor(components.into_boxed_slice()))
+    Ok(Descriptor(components))
 }
 
 struct Parser<'a, 'b, I: Impl> {
     input: &'a str,
     position: usize,
+    /// The position of the last `|` we consumed, if any.
+    last_pipe: Option<usize>,
+    options: &'b ParseOptions,
     output: &'b mut Vec<Component<I>>,
+    errors: Option<&'b mut Vec<ParseError>>,
+    spans: Option<&'b mut Vec<Range<usize>>>,
     phantom: std::marker::PhantomData<I>,
 }
 
 /// https://drafts.csswg.org/css-syntax-3/#whitespace
 fn is_whitespace(byte: u8) -> bool {
-    match byte {
-        b'\t' | b'\n' | b'\r' | b' ' => true,
-        _ => false,
-    }
+    matches!(byte, b'\t' | b'\n' | b'\r' | b' ')
 }
 
 /// https://drafts.csswg.org/css-syntax-3/#letter
 fn is_letter(byte: u8) -> bool {
-    match byte {
-        b'A'...b'Z' |
-        b'a'...b'z' => true,
-        _ => false,
-    }
+    byte.is_ascii_alphabetic()
 }
 
 /// https://drafts.csswg.org/css-syntax-3/#non-ascii-code-point
@@ -172,12 +812,28 @@ fn is_name_start(byte: u8) -> bool {
     is_letter(byte) || is_non_ascii(byte) || byte == b'_'
 }
 
+/// https://drafts.csswg.org/css-syntax-3/#ident-code-point
+fn is_name(byte: u8) -> bool {
+    is_name_start(byte) || byte.is_ascii_digit() || byte == b'-'
+}
+
 impl<'a, 'b, I: Impl> Parser<'a, 'b, I> {
-    fn new(input: &'a str, output: &'b mut Vec<Component<I>>) -> Self {
+    fn new(
+        input: &'a str,
+        position: usize,
+        options: &'b ParseOptions,
+        output: &'b mut Vec<Component<I>>,
+        errors: Option<&'b mut Vec<ParseError>>,
+        spans: Option<&'b mut Vec<Range<usize>>>,
+    ) -> Self {
         Self {
             input,
-            position: 0,
+            position,
+            last_pipe: None,
+            options,
             output,
+            errors,
+            spans,
             phantom: std::marker::PhantomData,
         }
     }
@@ -189,8 +845,13 @@ impl<'a, 'b, I: Impl> Parser<'a, 'b, I> {
     fn parse(&mut self) -> Result<(), ParseError> {
         // 5. Repeatedly consume the next input code point from stream:
         loop {
-            let component = self.parse_component()?;
-            self.output.push(component);
+            match self.parse_component() {
+                Ok(component) => self.output.push(component),
+                Err(error) => {
+                    self.recover(error)?;
+                    self.skip_to_next_pipe();
+                }
+            }
             self.skip_whitespace();
 
             let byte = match self.peek() {
@@ -199,13 +860,50 @@ impl<'a, 'b, I: Impl> Parser<'a, 'b, I> {
             };
 
             if byte != b'|' {
-                return Err(ParseError::ExpectedPipeBetweenComponents);
+                let kind = if byte == b'*' {
+                    ParseErrorKind::UniversalNotAlone
+                } else {
+                    ParseErrorKind::ExpectedPipeBetweenComponents
+                };
+                let error = self.error(kind);
+                self.recover(error)?;
+                self.skip_to_next_pipe();
+                if self.peek().is_none() {
+                    return Ok(());
+                }
             }
 
+            self.last_pipe = Some(self.position);
             self.position += 1;
         }
     }
 
+    /// Records `error` if we're collecting errors, or returns it otherwise.
+    fn recover(&mut self, error: ParseError) -> Result<(), ParseError> {
+        match self.errors {
+            Some(ref mut errors) => {
+                errors.push(error);
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Skips to the next `|` that isn't escaped, or to the end of the input.
+    fn skip_to_next_pipe(&mut self) {
+        loop {
+            match self.peek() {
+                None | Some(b'|') => return,
+                Some(b'\\') => self.position = (self.position + 2).min(self.input.len()),
+                Some(..) => self.position += 1,
+            }
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(kind, self.input, self.position)
+    }
+
     fn skip_whitespace(&mut self) {
         loop {
             match self.peek() {
@@ -216,54 +914,100 @@ impl<'a, 'b, I: Impl> Parser<'a, 'b, I> {
     }
 
     /// https://drafts.css-houdini.org/css-properties-values-api-1/#consume-data-type-name
-    fn parse_data_type_name(&mut self) -> Result<I::DataType, ParseError> {
+    ///
+    /// Errors are reported at the position of the opening `<`.
+    fn parse_data_type_name(&mut self) -> Result<ComponentName<I>, ParseError> {
         let start = self.position;
         loop {
             let byte = match self.peek() {
                 Some(b) => b,
-                None => return Err(ParseError::UnclosedDataTypeName),
+                None => {
+                    let name = self.input[start..].into();
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnclosedDataTypeName(name),
+                        self.input,
+                        start - 1,
+                    ));
+                }
             };
             if byte != b'>' {
                 self.position += 1;
                 continue;
             }
-            let ty = match I::data_type_name_from_str(&self.input[start..self.position]) {
-                Some(ty) => ty,
-                None => return Err(ParseError::UnknownDataTypeName),
+            let mut name = Cow::Borrowed(&self.input[start..self.position]);
+            if self.options.case_insensitive_data_type_names &&
+                name.bytes().any(|b| b.is_ascii_uppercase())
+            {
+                name = Cow::Owned(name.to_ascii_lowercase());
+            }
+            let name = match I::data_type_name_from_str(&name) {
+                Some(ty) => ComponentName::DataType(ty),
+                // Only names that could be known data types in the future
+                // are preserved, rather than anything up to the next `>`.
+                None if self.options.preserve_unknown_data_types &&
+                    start != self.position &&
+                    self.input[start..self.position].bytes().all(is_name) =>
+                {
+                    let name = &self.input[start..self.position];
+                    ComponentName::UnknownDataType(name.to_owned().into_boxed_str())
+                }
+                None => {
+                    let name = self.input[start..self.position].into();
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnknownDataTypeName(name),
+                        self.input,
+                        start - 1,
+                    ));
+                }
             };
             self.position += 1;
-            return Ok(ty)
+            return Ok(name)
         }
     }
 
     fn parse_name(&mut self) -> Result<ComponentName<I>, ParseError> {
         let b = match self.peek() {
             Some(b) => b,
-            None => return Err(ParseError::UnexpectedEOF),
+            None => return Err(self.error(ParseErrorKind::UnexpectedEOF)),
         };
 
         if b == b'<' {
             self.position += 1;
-            return Ok(ComponentName::DataType(self.parse_data_type_name()?));
+            return self.parse_data_type_name();
+        }
+
+        if b == b'*' {
+            return Err(self.error(ParseErrorKind::UniversalNotAlone));
         }
 
         if b != b'\\' && !is_name_start(b) {
-            return Err(ParseError::InvalidNameStart);
+            let c = self.input[self.position..].chars().next().unwrap();
+            return Err(self.error(ParseErrorKind::InvalidNameStart(c)));
         }
 
-        let input = &self.input[self.position..];
-        let mut input = cssparser::ParserInput::new(input);
+        let rest = &self.input[self.position..];
+        let mut input = cssparser::ParserInput::new(rest);
         let mut input = cssparser::Parser::new(&mut input);
         let name = input
             .expect_ident()
-            .ok()
-            .and_then(|name| I::custom_ident_from_ident(name.as_ref()));
+            .map(|name| I::custom_ident_from_ident(name.as_ref()));
+        let len = match name {
+            // If we parsed an identifier, report its source, otherwise report
+            // everything up to the next separator.
+            Ok(..) => input.position().byte_index(),
+            Err(..) => rest
+                .find(|c: char| c == '|' || (c.is_ascii() && is_whitespace(c as u8)))
+                .unwrap_or(rest.len()),
+        };
         let name = match name {
-            Some(name) => name,
-            None => return Err(ParseError::InvalidName),
+            Ok(Some(name)) => name,
+            Ok(None) | Err(..) => {
+                let name = rest[..len].into();
+                return Err(self.error(ParseErrorKind::InvalidName(name)));
+            }
         };
-        self.position += input.position().byte_index();
-        return Ok(ComponentName::Ident(name))
+        self.position += len;
+        Ok(ComponentName::Ident(name))
     }
 
     fn parse_multiplier(&mut self) -> Option<Multiplier> {
@@ -280,12 +1024,28 @@ impl<'a, 'b, I: Impl> Parser<'a, 'b, I> {
     fn parse_component(&mut self) -> Result<Component<I>, ParseError> {
         // Consume as much whitespace as possible from stream.
         self.skip_whitespace();
+        match (self.peek(), self.last_pipe) {
+            (Some(b'|'), None) => return Err(self.error(ParseErrorKind::LeadingPipe)),
+            (Some(b'|'), Some(..)) => return Err(self.error(ParseErrorKind::EmptyAlternative)),
+            (None, Some(pipe)) => {
+                return Err(ParseError::new(ParseErrorKind::TrailingPipe, self.input, pipe))
+            }
+            _ => {}
+        }
+        let component_start = self.position;
         let name = self.parse_name()?;
-        let multiplier = if name.is_pre_multiplied() {
-            None
-        } else {
-            self.parse_multiplier()
-        };
+        let start = self.position;
+        let multiplier = self.parse_multiplier();
+        if multiplier.is_some() && name.is_pre_multiplied() {
+            return Err(ParseError::new(
+                ParseErrorKind::MultiplierOnPremultipliedType,
+                self.input,
+                start,
+            ));
+        }
+        if let Some(ref mut spans) = self.spans {
+            spans.push(component_start..self.position);
+        }
         Ok(Component { name, multiplier })
     }
 }
@@ -307,12 +1067,95 @@ mod tests {
         for syntax in &["*", " * ", "* ", "\t*\t"] {
             assert_eq!(parse_descriptor(syntax), Ok(Descriptor::universal()));
         }
+        const UNIVERSAL: Descriptor<DefaultImpl> = Descriptor::universal();
+        assert!(UNIVERSAL.is_universal());
+    }
+
+    #[test]
+    fn universal_default() {
+        let descriptor = Descriptor::<DefaultImpl>::default();
+        assert_eq!(descriptor, Descriptor::universal());
+        assert_eq!(descriptor.to_css_string(), "*");
+    }
+
+    #[test]
+    fn iteration() {
+        let descriptor = parse_descriptor("foo | <length># | <color>").unwrap();
+        assert_eq!(descriptor[1].multiplier(), Some(Multiplier::Comma));
+
+        let mut data_types = 0;
+        for component in &descriptor {
+            if component.name().as_data_type().is_some() {
+                data_types += 1;
+            }
+        }
+        assert_eq!(data_types, 2);
+
+        let names = descriptor.into_iter().map(|c| c.name).collect::<Vec<_>>();
+        assert_eq!(names[0], ident!("foo"));
+        assert_eq!(names[2], ComponentName::DataType(DataType::Color));
+    }
+
+    #[test]
+    fn eq_str() {
+        let descriptor = parse_descriptor("<length> | auto").unwrap();
+        assert!(descriptor == "<length>|auto");
+        assert!(descriptor == *"  <length> |\tauto ");
+        assert!(descriptor != "auto | <length>");
+        assert!(descriptor != "<length> | auto |");
+        assert!(Descriptor::<DefaultImpl>::universal() == "*");
+    }
+
+    #[test]
+    fn mutation() {
+        let mut descriptor = parse_descriptor("<length>").unwrap();
+        let auto = Component::new(ident!("auto"), None).unwrap();
+        descriptor.push_component(auto.clone()).unwrap();
+        assert!(descriptor == "<length> | auto");
+
+        let color = Component::new(ComponentName::DataType(DataType::Color), None).unwrap();
+        descriptor.insert(0, color).unwrap();
+        assert!(descriptor == "<color> | <length> | auto");
+
+        assert_eq!(descriptor.remove(2), Ok(auto.clone()));
+        assert!(descriptor == "<color> | <length>");
+
+        descriptor.retain(|c| c.name().as_data_type() != Some(&DataType::Color)).unwrap();
+        assert!(descriptor == "<length>");
+        assert_eq!(descriptor.retain(|_| false), Err(BuildError::Empty));
+        assert_eq!(descriptor.remove(0), Err(BuildError::Empty));
+        assert!(descriptor == "<length>");
+
+        let mut universal = Descriptor::<DefaultImpl>::universal();
+        assert_eq!(universal.push_component(auto), Err(BuildError::UniversalNotAlone));
+        assert!(universal.is_universal());
+    }
+
+    #[test]
+    fn predicates() {
+        let universal = parse_descriptor("*").unwrap();
+        assert!(universal.is_universal());
+        assert_eq!(universal.single_data_type(), None);
+
+        let length = parse_descriptor("<length>").unwrap();
+        assert!(!length.is_universal());
+        assert_eq!(length.single_data_type(), Some(&DataType::Length));
+
+        let transform_list = parse_descriptor("<transform-list>").unwrap();
+        assert_eq!(transform_list.single_data_type(), Some(&DataType::TransformList));
+
+        for syntax in &["<length>+", "<length> | auto", "auto"] {
+            assert_eq!(parse_descriptor(syntax).unwrap().single_data_type(), None);
+        }
     }
 
     #[test]
     fn pipe_between_components() {
         for syntax in &["foo bar", "Foo <length>",  "foo, bar", "<length> <percentage>"] {
-            assert_eq!(parse_descriptor(syntax), Err(ParseError::ExpectedPipeBetweenComponents))
+            assert_eq!(
+                parse_descriptor(syntax).unwrap_err().kind,
+                ParseErrorKind::ExpectedPipeBetweenComponents,
+            )
         }
     }
 
@@ -321,9 +1164,170 @@ mod tests {
         assert!(parse_descriptor("|<length>").is_err());
     }
 
+    #[test]
+    fn pipes() {
+        fn test(syntax: &str, kind: ParseErrorKind, offset: usize) {
+            let error = parse_descriptor(syntax).unwrap_err();
+            assert_eq!(error.kind, kind);
+            assert_eq!(error.location.offset, offset);
+        }
+
+        test("| <length>", ParseErrorKind::LeadingPipe, 0);
+        test("|", ParseErrorKind::LeadingPipe, 0);
+        test("<length> |", ParseErrorKind::TrailingPipe, 9);
+        test("<length> | \n", ParseErrorKind::TrailingPipe, 9);
+        test("<length> || <color>", ParseErrorKind::EmptyAlternative, 10);
+        test("<length> | | <color>", ParseErrorKind::EmptyAlternative, 11);
+    }
+
+    #[test]
+    fn multiplier_on_premultiplied() {
+        for syntax in &["<transform-list>+", "<length> | <transform-list>#"] {
+            let error = parse_descriptor(syntax).unwrap_err();
+            assert_eq!(error.kind, ParseErrorKind::MultiplierOnPremultipliedType);
+            assert_eq!(error.location.offset, syntax.len() - 1);
+        }
+        assert!(parse_descriptor("<transform-function>+").is_ok());
+    }
+
+    #[test]
+    fn universal_not_alone() {
+        fn test(syntax: &str, offset: usize) {
+            let error = parse_descriptor(syntax).unwrap_err();
+            assert_eq!(error.kind, ParseErrorKind::UniversalNotAlone);
+            assert_eq!(error.location.offset, offset);
+        }
+
+        test("* | <length>", 0);
+        test("<length> | *", 11);
+        test("<length> *", 9);
+        test("*+", 0);
+    }
+
     #[test]
     fn simple_length() {
-        assert_eq!(parse_descriptor("foo | <length>#"), Ok(Descriptor(Box::new([
+        assert_eq!(parse_descriptor("foo | <length>#"), Ok(Descriptor(vec![
+            Component {
+                name: ident!("foo"),
+                multiplier: None,
+            },
+            Component {
+                name: ComponentName::DataType(DataType::Length),
+                multiplier: Some(Multiplier::Comma),
+            },
+        ])))
+    }
+
+    #[test]
+    fn accessors() {
+        let descriptor = parse_descriptor("foo | <length>#").unwrap();
+        assert_eq!(descriptor.len(), 2);
+        assert!(!descriptor.is_empty());
+        assert_eq!(descriptor.get(0).unwrap().name(), &ident!("foo"));
+        assert_eq!(descriptor.get(1).unwrap().multiplier(), Some(Multiplier::Comma));
+        assert!(descriptor.get(2).is_none());
+        assert_eq!(descriptor.iter().count(), descriptor.components().len());
+
+        let universal = parse_descriptor("*").unwrap();
+        assert!(universal.is_empty());
+        assert_eq!(universal.components(), &[]);
+    }
+
+    #[test]
+    fn serialization() {
+        fn test(syntax: &str, expected: &str) {
+            let descriptor = parse_descriptor(syntax).unwrap();
+            assert_eq!(descriptor.to_css_string(), expected);
+            assert_eq!(parse_descriptor(expected).unwrap(), descriptor);
+        }
+
+        test(" * ", "*");
+        test("<length>", "<length>");
+        test("foo|<length>#|<color>+", "foo | <length># | <color>+");
+        test("<transform-list>", "<transform-list>");
+        test("\\31 foo", "\\31 foo");
+    }
+
+    #[test]
+    fn error_display() {
+        let error = parse_descriptor("<foo>").unwrap_err();
+        assert_eq!(error.kind.to_string(), "unknown data type name '<foo>'");
+        let error: Box<dyn std::error::Error> = Box::new(parse_descriptor("").unwrap_err());
+        assert_eq!(error.to_string(), "syntax descriptor is empty at line 1, column 1");
+    }
+
+    #[test]
+    fn error_codes() {
+        assert_eq!(parse_descriptor("").unwrap_err().code(), 1);
+        assert_eq!(parse_descriptor("| foo").unwrap_err().code(), 2);
+        assert_eq!(parse_descriptor("<transform-list>+").unwrap_err().code(), 6);
+        assert_eq!(parse_descriptor("<foo>").unwrap_err().code(), 13);
+    }
+
+    #[test]
+    fn error_offsets() {
+        fn test(syntax: &str, kind: ParseErrorKind, offset: usize) {
+            let error = parse_descriptor(syntax).unwrap_err();
+            assert_eq!(error.kind, kind);
+            assert_eq!(error.location.offset, offset);
+        }
+
+        test("  ", ParseErrorKind::EmptyInput, 0);
+        test("foo bar", ParseErrorKind::ExpectedPipeBetweenComponents, 4);
+        test("  <length> | <foo>", ParseErrorKind::UnknownDataTypeName("foo".into()), 13);
+        test("<length> | <color", ParseErrorKind::UnclosedDataTypeName("color".into()), 11);
+        test(" |<length>", ParseErrorKind::LeadingPipe, 1);
+        test(" ,<length>", ParseErrorKind::InvalidNameStart(','), 1);
+        test("<length> | inherit", ParseErrorKind::InvalidName("inherit".into()), 11);
+        test("foo | \\\n+ | bar", ParseErrorKind::InvalidName("\\".into()), 6);
+        test("<length> | default+", ParseErrorKind::InvalidName("default".into()), 11);
+    }
+
+    #[test]
+    fn error_line_and_column() {
+        let error = parse_descriptor("<length> |\n  <foo>").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnknownDataTypeName("foo".into()));
+        assert_eq!(error.location, SourceLocation { offset: 13, line: 2, column: 3 });
+    }
+
+    #[test]
+    fn all_errors() {
+        fn test(syntax: &str, expected: &[(ParseErrorKind, usize)]) {
+            let errors = parse_descriptor_all_errors(syntax).unwrap_err();
+            let errors = errors
+                .into_iter()
+                .map(|e| (e.kind, e.location.offset))
+                .collect::<Vec<_>>();
+            assert_eq!(errors, expected);
+        }
+
+        test("", &[(ParseErrorKind::EmptyInput, 0)]);
+        test("<foo> | <length> | <bar>", &[
+            (ParseErrorKind::UnknownDataTypeName("foo".into()), 0),
+            (ParseErrorKind::UnknownDataTypeName("bar".into()), 19),
+        ]);
+        test("foo bar | inherit | <length>", &[
+            (ParseErrorKind::ExpectedPipeBetweenComponents, 4),
+            (ParseErrorKind::InvalidName("inherit".into()), 10),
+        ]);
+        test("|<length>|", &[
+            (ParseErrorKind::LeadingPipe, 0),
+            (ParseErrorKind::TrailingPipe, 9),
+        ]);
+        test("<length> | foo\\| bar", &[
+            (ParseErrorKind::ExpectedPipeBetweenComponents, 17),
+        ]);
+
+        assert_eq!(
+            parse_descriptor_all_errors("foo | <length>"),
+            parse_descriptor("foo | <length>").map_err(|e| vec![e]),
+        );
+    }
+
+    #[test]
+    fn recovering() {
+        let recovered = parse_descriptor_recovering("foo | <bar> | <length># | inherit");
+        assert_eq!(recovered.descriptor, Some(Descriptor(vec![
             Component {
                 name: ident!("foo"),
                 multiplier: None,
@@ -332,6 +1336,147 @@ mod tests {
                 name: ComponentName::DataType(DataType::Length),
                 multiplier: Some(Multiplier::Comma),
             },
-        ]))))
+        ])));
+        let kinds = recovered.errors.into_iter().map(|e| e.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [
+            ParseErrorKind::UnknownDataTypeName("bar".into()),
+            ParseErrorKind::InvalidName("inherit".into()),
+        ]);
+
+        let recovered = parse_descriptor_recovering("<bar> |");
+        assert_eq!(recovered.descriptor, None);
+        assert_eq!(recovered.errors.len(), 2);
+
+        let recovered = parse_descriptor_recovering("*");
+        assert_eq!(recovered.descriptor, Some(Descriptor::universal()));
+        assert!(recovered.errors.is_empty());
+
+        let recovered = parse_descriptor_recovering(" ");
+        assert_eq!(recovered.descriptor, None);
+        assert_eq!(recovered.errors[0].kind, ParseErrorKind::EmptyInput);
+    }
+
+    #[test]
+    fn options() {
+        let strict = ParseOptions::strict();
+        let compat = ParseOptions::compat();
+        assert_eq!(
+            parse_descriptor_with_options("<Length>", &strict).unwrap_err().kind,
+            ParseErrorKind::UnknownDataTypeName("Length".into()),
+        );
+        assert_eq!(
+            parse_descriptor_with_options("<Length> | <COLOR>+", &compat),
+            parse_descriptor("<length> | <color>+"),
+        );
+    }
+
+    #[test]
+    fn unknown_data_types() {
+        let options = ParseOptions {
+            preserve_unknown_data_types: true,
+            ..ParseOptions::strict()
+        };
+        let descriptor = parse_descriptor_with_options("<foo-bar>+ | <length>", &options).unwrap();
+        assert_eq!(descriptor, Descriptor(vec![
+            Component {
+                name: ComponentName::UnknownDataType("foo-bar".into()),
+                multiplier: Some(Multiplier::Space),
+            },
+            Component {
+                name: ComponentName::DataType(DataType::Length),
+                multiplier: None,
+            },
+        ]));
+        assert_eq!(descriptor.to_css_string(), "<foo-bar>+ | <length>");
+
+        assert_eq!(
+            parse_descriptor_with_options("<>", &options).unwrap_err().kind,
+            ParseErrorKind::UnknownDataTypeName("".into()),
+        );
+        let error = parse_descriptor_with_options("<foo | <length>", &options).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnknownDataTypeName("foo | <length".into()));
+        assert_eq!(error.location.offset, 0);
+        assert_eq!(
+            parse_descriptor_with_options("<a b>", &options).unwrap_err().kind,
+            ParseErrorKind::UnknownDataTypeName("a b".into()),
+        );
+        assert_eq!(
+            parse_descriptor("<foo-bar>").unwrap_err().kind,
+            ParseErrorKind::UnknownDataTypeName("foo-bar".into()),
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        let descriptor: Descriptor<DefaultImpl> = "foo | <length>#".parse().unwrap();
+        assert_eq!(Ok(descriptor), parse_descriptor("foo | <length>#"));
+        assert_eq!(
+            Descriptor::<DefaultImpl>::try_from("*"),
+            Ok(Descriptor::universal()),
+        );
+        assert!("<foo>".parse::<Descriptor<DefaultImpl>>().is_err());
+    }
+
+    #[test]
+    fn component_new() {
+        let length = ComponentName::<DefaultImpl>::DataType(DataType::Length);
+        assert_eq!(length.as_data_type(), Some(&DataType::Length));
+        assert_eq!(length.as_ident(), None);
+        let foo: ComponentName<DefaultImpl> = ident!("foo");
+        assert_eq!(foo.as_ident(), CustomIdent::from_ident("foo").as_ref());
+
+        let component = Component::new(length, Some(Multiplier::Comma)).unwrap();
+        assert_eq!(component.multiplier(), Some(Multiplier::Comma));
+        assert_eq!(
+            Component::<DefaultImpl>::new(
+                ComponentName::DataType(DataType::TransformList),
+                Some(Multiplier::Space),
+            ),
+            Err(BuildError::MultiplierOnPremultipliedType),
+        );
+    }
+
+    #[test]
+    fn hash_and_ord() {
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        map.insert(parse_descriptor("<length> | auto").unwrap(), 1);
+        map.insert(parse_descriptor("*").unwrap(), 2);
+        assert_eq!(map.get(&parse_descriptor("<length>|auto").unwrap()), Some(&1));
+        assert_eq!(map.get(&Descriptor::universal()), Some(&2));
+
+        let mut descriptors = [
+            parse_descriptor("foo").unwrap(),
+            parse_descriptor("<color>").unwrap(),
+            parse_descriptor("<length>+").unwrap(),
+            parse_descriptor("<length>").unwrap(),
+        ];
+        descriptors.sort();
+        let serialized = descriptors.iter().map(|d| d.to_css_string()).collect::<Vec<_>>();
+        assert_eq!(serialized, ["<length>", "<length>+", "<color>", "foo"]);
+    }
+
+    #[test]
+    fn minified_and_canonical() {
+        let descriptor = parse_descriptor("  foo|<length>#   |  <color>+ ").unwrap();
+        assert_eq!(descriptor.serialize_minified(), "foo|<length>#|<color>+");
+        assert_eq!(descriptor.serialize_canonical(), "foo | <length># | <color>+");
+
+        let universal = Descriptor::<DefaultImpl>::universal();
+        assert_eq!(universal.serialize_minified(), "*");
+        assert_eq!(universal.serialize_canonical(), "*");
+    }
+
+    #[test]
+    fn cssparser_to_css() {
+        use cssparser::ToCss;
+
+        let descriptor = parse_descriptor("<length># | \\31 foo").unwrap();
+        assert_eq!(ToCss::to_css_string(&descriptor), "<length># | \\31 foo");
+        assert_eq!(descriptor[0].name().to_css_string(), "<length>");
+        assert_eq!(descriptor[0].to_css_string(), "<length>#");
+        assert_eq!(Multiplier::Space.to_css_string(), "+");
+        assert_eq!(DataType::TransformList.to_css_string(), "<transform-list>");
     }
 }
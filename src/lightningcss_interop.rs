@@ -0,0 +1,162 @@
+//! Conversions to and from lightningcss syntax types, behind the
+//! `lightningcss` feature.
+
+use std::convert::TryFrom;
+
+use lightningcss::values::syntax::{
+    Multiplier as LightningMultiplier, SyntaxComponent, SyntaxComponentKind, SyntaxString,
+};
+
+use super::{
+    BuildError, Component, ComponentName, CustomIdent, DataType, DefaultImpl, Descriptor,
+    Multiplier, ParseErrorKind,
+};
+
+/// lightningcss supports `<string>`, which this crate only knows as an unknown
+/// data type.
+const STRING: &str = "string";
+
+impl<'a> TryFrom<&'a Component<DefaultImpl>> for SyntaxComponent {
+    type Error = ParseErrorKind;
+
+    fn try_from(component: &'a Component<DefaultImpl>) -> Result<Self, Self::Error> {
+        let kind = match *component.name() {
+            ComponentName::DataType(ty) => match ty {
+                DataType::Length => SyntaxComponentKind::Length,
+                DataType::Number => SyntaxComponentKind::Number,
+                DataType::Percentage => SyntaxComponentKind::Percentage,
+                DataType::LengthPercentage => SyntaxComponentKind::LengthPercentage,
+                DataType::Color => SyntaxComponentKind::Color,
+                DataType::Image => SyntaxComponentKind::Image,
+                DataType::Url => SyntaxComponentKind::Url,
+                DataType::Integer => SyntaxComponentKind::Integer,
+                DataType::Angle => SyntaxComponentKind::Angle,
+                DataType::Time => SyntaxComponentKind::Time,
+                DataType::Resolution => SyntaxComponentKind::Resolution,
+                DataType::TransformFunction => SyntaxComponentKind::TransformFunction,
+                DataType::TransformList => SyntaxComponentKind::TransformList,
+                DataType::CustomIdent => SyntaxComponentKind::CustomIdent,
+                #[cfg(feature = "experimental-position")]
+                DataType::Position => return Err(ParseErrorKind::UnknownDataTypeName(ty.as_str().into())),
+            },
+            ComponentName::Ident(ref ident) => SyntaxComponentKind::Literal(ident.as_str().to_owned()),
+            ComponentName::UnknownDataType(ref name) if &**name == STRING => SyntaxComponentKind::String,
+            ComponentName::UnknownDataType(ref name) => {
+                return Err(ParseErrorKind::UnknownDataTypeName(name.clone()));
+            }
+        };
+        let multiplier = match component.multiplier() {
+            None => LightningMultiplier::None,
+            Some(Multiplier::Space) => LightningMultiplier::Space,
+            Some(Multiplier::Comma) => LightningMultiplier::Comma,
+        };
+        Ok(SyntaxComponent { kind, multiplier })
+    }
+}
+
+impl<'a> TryFrom<&'a SyntaxComponent> for Component<DefaultImpl> {
+    type Error = BuildError;
+
+    fn try_from(component: &'a SyntaxComponent) -> Result<Self, Self::Error> {
+        let name = match component.kind {
+            SyntaxComponentKind::Length => ComponentName::DataType(DataType::Length),
+            SyntaxComponentKind::Number => ComponentName::DataType(DataType::Number),
+            SyntaxComponentKind::Percentage => ComponentName::DataType(DataType::Percentage),
+            SyntaxComponentKind::LengthPercentage => ComponentName::DataType(DataType::LengthPercentage),
+            SyntaxComponentKind::String => ComponentName::UnknownDataType(STRING.into()),
+            SyntaxComponentKind::Color => ComponentName::DataType(DataType::Color),
+            SyntaxComponentKind::Image => ComponentName::DataType(DataType::Image),
+            SyntaxComponentKind::Url => ComponentName::DataType(DataType::Url),
+            SyntaxComponentKind::Integer => ComponentName::DataType(DataType::Integer),
+            SyntaxComponentKind::Angle => ComponentName::DataType(DataType::Angle),
+            SyntaxComponentKind::Time => ComponentName::DataType(DataType::Time),
+            SyntaxComponentKind::Resolution => ComponentName::DataType(DataType::Resolution),
+            SyntaxComponentKind::TransformFunction => ComponentName::DataType(DataType::TransformFunction),
+            SyntaxComponentKind::TransformList => ComponentName::DataType(DataType::TransformList),
+            SyntaxComponentKind::CustomIdent => ComponentName::DataType(DataType::CustomIdent),
+            SyntaxComponentKind::Literal(ref ident) => match CustomIdent::from_ident(ident) {
+                Some(ident) => ComponentName::Ident(ident),
+                None => return Err(BuildError::InvalidCustomIdent(ident.as_str().into())),
+            },
+        };
+        let multiplier = match component.multiplier {
+            LightningMultiplier::None => None,
+            LightningMultiplier::Space => Some(Multiplier::Space),
+            LightningMultiplier::Comma => Some(Multiplier::Comma),
+        };
+        Component::new(name, multiplier)
+    }
+}
+
+/// Fails for unknown data types other than `<string>`, and for `<position>`,
+/// which lightningcss can't represent.
+impl<'a> TryFrom<&'a Descriptor<DefaultImpl>> for SyntaxString {
+    type Error = ParseErrorKind;
+
+    fn try_from(descriptor: &'a Descriptor<DefaultImpl>) -> Result<Self, Self::Error> {
+        if descriptor.is_universal() {
+            return Ok(SyntaxString::Universal);
+        }
+        let components = descriptor
+            .iter()
+            .map(SyntaxComponent::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(SyntaxString::Components(components))
+    }
+}
+
+/// `<string>` components become `ComponentName::UnknownDataType`.
+impl<'a> TryFrom<&'a SyntaxString> for Descriptor<DefaultImpl> {
+    type Error = BuildError;
+
+    fn try_from(syntax: &'a SyntaxString) -> Result<Self, Self::Error> {
+        let components = match *syntax {
+            SyntaxString::Universal => return Ok(Descriptor::universal()),
+            SyntaxString::Components(ref components) => components,
+        };
+        if components.is_empty() {
+            return Err(BuildError::Empty);
+        }
+        let components = components
+            .iter()
+            .map(Component::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Descriptor(components))
+    }
+}
+
+#[test]
+fn lightningcss_test() {
+    use super::{parse_descriptor, parse_descriptor_with_options, ParseOptions};
+
+    let descriptor = parse_descriptor("<length># | auto | <transform-list>").unwrap();
+    let syntax = SyntaxString::try_from(&descriptor).unwrap();
+    assert_eq!(syntax, SyntaxString::parse_string("<length># | auto | <transform-list>").unwrap());
+    assert_eq!(Descriptor::try_from(&syntax).unwrap(), descriptor);
+
+    let universal = Descriptor::universal();
+    assert_eq!(SyntaxString::try_from(&universal).unwrap(), SyntaxString::Universal);
+    assert_eq!(Descriptor::try_from(&SyntaxString::Universal).unwrap(), universal);
+
+    let string = SyntaxString::parse_string("<string>+").unwrap();
+    let descriptor = Descriptor::try_from(&string).unwrap();
+    assert_eq!(descriptor.to_css_string(), "<string>+");
+    assert_eq!(SyntaxString::try_from(&descriptor).unwrap(), string);
+
+    let unknown = parse_descriptor_with_options("<foo>", &ParseOptions::compat()).unwrap();
+    assert_eq!(
+        SyntaxString::try_from(&unknown),
+        Err(ParseErrorKind::UnknownDataTypeName("foo".into())),
+    );
+    assert_eq!(
+        Descriptor::try_from(&SyntaxString::Components(vec![])),
+        Err(BuildError::Empty),
+    );
+    assert_eq!(
+        Descriptor::try_from(&SyntaxString::Components(vec![SyntaxComponent {
+            kind: SyntaxComponentKind::TransformList,
+            multiplier: LightningMultiplier::Comma,
+        }])),
+        Err(BuildError::MultiplierOnPremultipliedType),
+    );
+}
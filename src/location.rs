@@ -0,0 +1,58 @@
+/// A location in the input of a syntax descriptor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SourceLocation {
+    /// The byte offset into the input.
+    pub offset: usize,
+    /// The line number, starting at 1.
+    pub line: u32,
+    /// The column number in code points, starting at 1.
+    pub column: u32,
+}
+
+impl SourceLocation {
+    /// Computes the location of the byte at `offset` in `input`.
+    ///
+    /// Lines are split per https://drafts.csswg.org/css-syntax-3/#input-preprocessing,
+    /// that is, CR LF, CR, LF and FF each count as a single newline.
+    pub fn from_offset(input: &str, offset: usize) -> Self {
+        debug_assert!(offset <= input.len());
+        let mut line = 1;
+        let mut line_start = 0;
+        let bytes = input.as_bytes();
+        let mut i = 0;
+        while i < offset {
+            match bytes[i] {
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                    i += 1;
+                    continue;
+                }
+                b'\n' | b'\r' | b'\x0C' => {
+                    line += 1;
+                    line_start = i + 1;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        let column = input[line_start..offset].chars().count() as u32 + 1;
+        Self { offset, line, column }
+    }
+}
+
+#[test]
+fn from_offset_test() {
+    fn test(input: &str, offset: usize, line: u32, column: u32) {
+        assert_eq!(
+            SourceLocation::from_offset(input, offset),
+            SourceLocation { offset, line, column },
+        );
+    }
+
+    test("", 0, 1, 1);
+    test("<length>", 3, 1, 4);
+    test("foo |\n<bar>", 6, 2, 1);
+    test("foo |\r\n<bar>", 7, 2, 1);
+    test("foo |\r\n<bar>", 5, 1, 6);
+    test("a\rb\x0Cc", 4, 3, 1);
+    test("é | <bar>", 5, 1, 5);
+}
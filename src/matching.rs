@@ -0,0 +1,968 @@
+//! Matching of CSS values against syntax descriptors.
+//!
+//! https://drafts.css-houdini.org/css-properties-values-api-1/#parse-a-value-according-to-a-syntax
+
+use std::fmt;
+use std::ops::Range;
+
+use cssparser::{BasicParseErrorKind, Parser, ToCss, Token};
+
+use super::{Component, ComponentName, CustomIdent, DataType, DefaultImpl, Descriptor, Multiplier};
+use super::ascii;
+use super::calc::{self, CalcNode, CalcType};
+use super::color;
+use super::image;
+use super::keyword::CssWideKeyword;
+use super::location::SourceLocation;
+use super::options::MatchLimits;
+#[cfg(feature = "experimental-position")]
+use super::position;
+use super::substitution::{self, References};
+use super::transform;
+use super::units::{AngleUnit, LengthUnit, ResolutionUnit, TimeUnit};
+use super::url;
+use super::value::{MatchedValue, Output};
+
+/// The kind of error that made a value not match a syntax descriptor.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum MatchErrorKind {
+    /// A token that the descriptor doesn't accept there, with its source.
+    UnexpectedToken(Box<str>),
+    /// The value ended where the descriptor expected more.
+    UnexpectedEnd,
+    /// A function or block nested deeper than `MatchLimits::max_nesting_depth`.
+    NestingTooDeep,
+    /// A token past `MatchLimits::max_tokens`.
+    TooManyTokens,
+}
+
+impl fmt::Display for MatchErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MatchErrorKind::UnexpectedToken(ref token) => write!(f, "unexpected '{}'", token),
+            MatchErrorKind::UnexpectedEnd => f.write_str("unexpected end of value"),
+            MatchErrorKind::NestingTooDeep => f.write_str("value nested too deeply"),
+            MatchErrorKind::TooManyTokens => f.write_str("too many tokens in value"),
+        }
+    }
+}
+
+/// An error that made a value not match a syntax descriptor, along with
+/// where in the value it happened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchError {
+    /// What went wrong.
+    pub kind: MatchErrorKind,
+    /// The location in the value of the token that didn't match.
+    pub location: SourceLocation,
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.location.line, self.location.column
+        )
+    }
+}
+
+impl std::error::Error for MatchError {}
+
+/// Why a value didn't match a syntax descriptor, as returned by
+/// `Descriptor::explain_mismatch`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchExplanation {
+    /// The error of the components that got furthest into the value.
+    pub error: MatchError,
+    /// The components that got furthest into the value, in the order of the
+    /// descriptor. Empty for the universal syntax descriptor, and for values
+    /// pending substitution.
+    pub expected: Vec<Component<DefaultImpl>>,
+}
+
+impl fmt::Display for MatchExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.expected.is_empty() {
+            return self.error.fmt(f);
+        }
+        f.write_str("expected ")?;
+        for (i, component) in self.expected.iter().enumerate() {
+            if i > 0 {
+                f.write_str(if i + 1 == self.expected.len() { " or " } else { ", " })?;
+            }
+            match *component.name() {
+                ComponentName::Ident(..) => write!(f, "'{}'", component.to_css_string())?,
+                _ => component.to_css(f)?,
+            }
+        }
+        match self.error.kind {
+            MatchErrorKind::UnexpectedToken(ref token) => write!(f, ", found '{}'", token)?,
+            MatchErrorKind::UnexpectedEnd => f.write_str(", found the end of the value")?,
+            ref kind => write!(f, ": {}", kind)?,
+        }
+        write!(f, " at line {}, column {}", self.error.location.line, self.error.location.column)
+    }
+}
+
+impl std::error::Error for MatchExplanation {}
+
+pub(crate) type Error<'i> = cssparser::ParseError<'i, ()>;
+
+/// A value matched by `Descriptor::parse_value_with_spans`, along with where
+/// its parts are in the input. Byte ranges are relative to the start of the
+/// value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpannedMatch {
+    /// The matched value.
+    pub value: MatchedValue,
+    /// The index of the component of the descriptor that matched, or `None`
+    /// if the value was matched by the universal syntax descriptor or is
+    /// pending substitution.
+    pub component: Option<usize>,
+    /// Where the value is, without surrounding whitespace.
+    pub span: Range<usize>,
+    /// Where each item of the value is, if the component that matched has a
+    /// multiplier or is a pre-multiplied data type name. Empty otherwise.
+    pub item_spans: Vec<Range<usize>>,
+}
+
+impl MatchError {
+    /// Converts an internal error into a `MatchError`, with a location
+    /// relative to `source`, the value, which starts at `start`.
+    fn new(error: &Error, source: &str, start: cssparser::SourceLocation) -> Self {
+        let kind = match error.kind {
+            cssparser::ParseErrorKind::Basic(BasicParseErrorKind::UnexpectedToken(ref token)) => {
+                MatchErrorKind::UnexpectedToken(token.to_css_string().into_boxed_str())
+            }
+            _ => MatchErrorKind::UnexpectedEnd,
+        };
+        let offset = offset_of(source, start, error.location);
+        Self {
+            kind,
+            location: SourceLocation::from_offset(source, offset),
+        }
+    }
+}
+
+/// Returns the byte offset into `source`, which starts at `start`, of
+/// `location`.
+///
+/// cssparser counts columns in UTF-16 code units, and lines separated by
+/// CR LF, CR, LF or FF.
+fn offset_of(source: &str, start: cssparser::SourceLocation, location: cssparser::SourceLocation) -> usize {
+    let mut offset = 0;
+    let mut columns = location.column - 1;
+    if location.line == start.line {
+        columns -= start.column - 1;
+    } else {
+        let bytes = source.as_bytes();
+        let mut line = start.line;
+        while line < location.line && offset < bytes.len() {
+            match bytes[offset] {
+                b'\r' if bytes.get(offset + 1) == Some(&b'\n') => offset += 1,
+                b'\n' | b'\r' | b'\x0C' => line += 1,
+                _ => {}
+            }
+            offset += 1;
+        }
+    }
+    for c in source[offset..].chars() {
+        if columns == 0 {
+            break;
+        }
+        columns = columns.saturating_sub(c.len_utf16() as u32);
+        offset += c.len_utf8();
+    }
+    offset
+}
+
+/// Consumes the tokens of `input`, which is nested `depth` functions or
+/// blocks deep, counting them in `tokens`, until one of them exceeds
+/// `limits`, in which case it returns what was exceeded and where.
+///
+/// Exceeding the nesting depth stops the recursion, so the stack used is
+/// bounded too.
+fn check_limits<'i, 't>(
+    input: &mut Parser<'i, 't>,
+    limits: &MatchLimits,
+    depth: usize,
+    tokens: &mut usize,
+) -> Result<(), (MatchErrorKind, cssparser::SourceLocation)> {
+    loop {
+        let location = input.current_source_location();
+        let token = match input.next_including_whitespace_and_comments() {
+            Ok(token) => token,
+            Err(..) => return Ok(()),
+        };
+        *tokens += 1;
+        if limits.max_tokens.is_some_and(|max| *tokens > max) {
+            return Err((MatchErrorKind::TooManyTokens, location));
+        }
+        match *token {
+            Token::Function(..) |
+            Token::ParenthesisBlock |
+            Token::SquareBracketBlock |
+            Token::CurlyBracketBlock => {}
+            _ => continue,
+        }
+        if limits.max_nesting_depth.is_some_and(|max| depth >= max) {
+            return Err((MatchErrorKind::NestingTooDeep, location));
+        }
+        let mut result = Ok(());
+        // The block is skipped to its end even if we stop early.
+        let _ = input.parse_nested_block(|input| -> Result<(), Error<'i>> {
+            result = check_limits(input, limits, depth + 1, tokens);
+            Ok(())
+        });
+        result?;
+    }
+}
+
+/// Returns whether `a` happened further in the input than `b`.
+fn is_further(a: &Error, b: &Error) -> bool {
+    (a.location.line, a.location.column) > (b.location.line, b.location.column)
+}
+
+/// Rounds `value` to the nearest integer, choosing the one closer to positive
+/// infinity if it's exactly in between, and clamping it to the `i64` range.
+pub(crate) fn round_to_integer(value: f64) -> i64 {
+    // NOTE: `as` saturates, and turns NaN into zero.
+    (value + 0.5).floor() as i64
+}
+
+/// Returns the value of a math function where a value of data type `ty` is
+/// expected, or `None` if it doesn't resolve to that data type.
+///
+/// Number-only math functions are simplified to a number, and rounded where
+/// an integer is expected, unless they involve relative lengths or
+/// percentages, like `calc(1em / 1px)`.
+///
+/// https://drafts.csswg.org/css-values-4/#calc-type-checking
+fn math_function_value(ty: DataType, node: CalcNode) -> Option<MatchedValue> {
+    let calc_type = node.calc_type()?;
+    let matches = match ty {
+        DataType::Number if calc_type == CalcType::Number => {
+            return Some(match node.to_number() {
+                Some(number) => MatchedValue::Number(number),
+                None => MatchedValue::Calc(Box::new(node)),
+            });
+        }
+        DataType::Integer if calc_type == CalcType::Number => {
+            return Some(match node.to_number() {
+                Some(number) => MatchedValue::Integer(round_to_integer(number)),
+                None => MatchedValue::IntegerCalc(Box::new(node)),
+            });
+        }
+        DataType::Length => calc_type == CalcType::Length,
+        DataType::Percentage => calc_type == CalcType::Percentage,
+        DataType::LengthPercentage => matches!(
+            calc_type,
+            CalcType::Length | CalcType::Percentage | CalcType::LengthPercentage
+        ),
+        DataType::Angle => calc_type == CalcType::Angle,
+        DataType::Time => calc_type == CalcType::Time,
+        DataType::Resolution => calc_type == CalcType::Resolution,
+        _ => false,
+    };
+    if !matches {
+        return None;
+    }
+    Some(MatchedValue::Calc(Box::new(node)))
+}
+
+/// Consumes a single value of the given data type.
+pub(crate) fn parse_data_type<'i, 't, O: Output>(
+    ty: DataType,
+    input: &mut Parser<'i, 't>,
+) -> Result<O, Error<'i>> {
+    if ty == DataType::Color {
+        let color = color::parse_color(input)?;
+        return Ok(O::value(|| MatchedValue::Color(color)));
+    }
+    if ty == DataType::Image {
+        let image = image::parse_image(input)?;
+        return Ok(O::value(|| MatchedValue::Image(image)));
+    }
+    if ty == DataType::Url {
+        let url = url::parse_url(input)?;
+        return Ok(O::value(|| MatchedValue::Url(url)));
+    }
+    if ty == DataType::TransformFunction {
+        return transform::parse_transform_function(input);
+    }
+    #[cfg(feature = "experimental-position")]
+    if ty == DataType::Position {
+        return position::parse_position(input);
+    }
+    let location = input.current_source_location();
+    let token = input.next()?.clone();
+    let value = match (ty, &token) {
+        (DataType::Length, Token::Dimension { value, unit, .. }) |
+        (DataType::LengthPercentage, Token::Dimension { value, unit, .. }) => {
+            match LengthUnit::from_str(unit) {
+                Some(unit) => O::value(|| MatchedValue::Length { value: (*value).into(), unit }),
+                None => return Err(location.new_unexpected_token_error(token)),
+            }
+        }
+        // https://drafts.csswg.org/css-values-4/#zero-value
+        (DataType::Length, Token::Number { value: zero, .. }) |
+        (DataType::LengthPercentage, Token::Number { value: zero, .. }) if *zero == 0. => {
+            O::value(|| MatchedValue::Length { value: 0., unit: LengthUnit::Px })
+        }
+        (DataType::Number, Token::Number { value, .. }) => {
+            O::value(|| MatchedValue::Number((*value).into()))
+        }
+        (DataType::Integer, Token::Number { int_value: Some(value), .. }) => {
+            O::value(|| MatchedValue::Integer((*value).into()))
+        }
+        (_, Token::Function(name)) if calc::is_math_function(name) => {
+            let node = CalcNode::parse_function(name, input)?;
+            match math_function_value(ty, node) {
+                Some(value) => O::value(|| value),
+                None => return Err(location.new_unexpected_token_error(token)),
+            }
+        }
+        (DataType::Percentage, Token::Percentage { unit_value, .. }) |
+        (DataType::LengthPercentage, Token::Percentage { unit_value, .. }) => {
+            O::value(|| MatchedValue::Percentage((*unit_value).into()))
+        }
+        (DataType::Angle, Token::Dimension { value, unit, .. }) => match AngleUnit::from_str(unit) {
+            Some(unit) => O::value(|| MatchedValue::Angle { value: (*value).into(), unit }),
+            None => return Err(location.new_unexpected_token_error(token)),
+        },
+        (DataType::Time, Token::Dimension { value, unit, .. }) => match TimeUnit::from_str(unit) {
+            Some(unit) => O::value(|| MatchedValue::Time { value: (*value).into(), unit }),
+            None => return Err(location.new_unexpected_token_error(token)),
+        },
+        (DataType::Resolution, Token::Dimension { value, unit, .. }) => {
+            match ResolutionUnit::from_str(unit) {
+                Some(unit) => O::value(|| MatchedValue::Resolution { value: (*value).into(), unit }),
+                None => return Err(location.new_unexpected_token_error(token)),
+            }
+        }
+        (DataType::CustomIdent, Token::Ident(ident)) => match CustomIdent::from_ident(ident) {
+            Some(ident) => O::value(|| MatchedValue::CustomIdent(ident)),
+            None => return Err(location.new_unexpected_token_error(token)),
+        },
+        _ => return Err(location.new_unexpected_token_error(token)),
+    };
+    Ok(value)
+}
+
+/// Consumes a single value matching the name of a component, ignoring its
+/// multiplier.
+fn parse_component_name<'i, 't, O: Output>(
+    name: &ComponentName<DefaultImpl>,
+    input: &mut Parser<'i, 't>,
+    spans: Option<&mut ItemSpans>,
+) -> Result<O, Error<'i>> {
+    match *name {
+        ComponentName::DataType(DataType::TransformList) => {
+            // A pre-multiplied data type name, same as `<transform-function>+`.
+            let items = parse_list(Multiplier::Space, input, spans, transform::parse_transform_function)?;
+            Ok(O::list(items, |items| {
+                MatchedValue::TransformList(items.into_iter().map(|item| match item {
+                    MatchedValue::TransformFunction(function) => function,
+                    _ => unreachable!("transform list items are transform functions"),
+                }).collect())
+            }))
+        }
+        ComponentName::DataType(ty) => parse_data_type(ty, input),
+        ComponentName::Ident(ref ident) => {
+            let location = input.current_source_location();
+            let token = input.next()?;
+            match *token {
+                // Keywords are ASCII case-insensitive, like everywhere else in
+                // CSS, but we return the spelling of the descriptor.
+                Token::Ident(ref value) if value.eq_ignore_ascii_case(ident) => {
+                    Ok(O::value(|| MatchedValue::Ident(ident.clone())))
+                }
+                ref token => Err(location.new_unexpected_token_error(token.clone())),
+            }
+        }
+        // We don't know how to parse these, so nothing matches them.
+        ComponentName::UnknownDataType(..) => Err(input.new_error_for_next_token()),
+    }
+}
+
+/// Where the items of a list matched at the top level of a value are, for
+/// `Descriptor::parse_value_with_spans`.
+struct ItemSpans {
+    /// The byte index of the start of the value in the parser input.
+    start: usize,
+    /// The byte ranges of the items, relative to the start of the value.
+    items: Vec<Range<usize>>,
+}
+
+/// Consumes one or more values separated per `multiplier`, recording where
+/// they are in `spans` if given.
+fn parse_list<'i, 't, O: Output>(
+    multiplier: Multiplier,
+    input: &mut Parser<'i, 't>,
+    mut spans: Option<&mut ItemSpans>,
+    mut parse_one: impl FnMut(&mut Parser<'i, '_>) -> Result<O, Error<'i>>,
+) -> Result<Vec<O>, Error<'i>> {
+    let mut parse_item = |input: &mut Parser<'i, '_>| -> Result<O, Error<'i>> {
+        input.skip_whitespace();
+        let start = input.position().byte_index();
+        let item = parse_one(input)?;
+        if let Some(ref mut spans) = spans {
+            let end = input.position().byte_index();
+            spans.items.push(start - spans.start..end - spans.start);
+        }
+        Ok(item)
+    };
+    match multiplier {
+        Multiplier::Comma => input.parse_comma_separated(|input| {
+            // Check that each item is a single value ourselves, so that
+            // errors are reported at the extra token rather than before it.
+            let item = parse_item(input)?;
+            expect_exhausted(input)?;
+            Ok(item)
+        }),
+        Multiplier::Space => {
+            let mut items = vec![parse_item(input)?];
+            while !input.is_exhausted() {
+                items.push(parse_item(input)?);
+            }
+            Ok(items)
+        }
+    }
+}
+
+/// Like `Parser::expect_exhausted`, but reporting errors at the unexpected
+/// token rather than at the whitespace before it.
+fn expect_exhausted<'i, 't>(input: &mut Parser<'i, 't>) -> Result<(), Error<'i>> {
+    input.skip_whitespace();
+    let location = input.current_source_location();
+    match input.next() {
+        Ok(token) => Err(location.new_unexpected_token_error(token.clone())),
+        Err(..) => Ok(()),
+    }
+}
+
+fn parse_component<'i, 't, O: Output>(
+    component: &Component<DefaultImpl>,
+    input: &mut Parser<'i, 't>,
+    spans: Option<&mut ItemSpans>,
+) -> Result<O, Error<'i>> {
+    match component.multiplier() {
+        None => parse_component_name(component.name(), input, spans),
+        Some(multiplier) => {
+            let items = parse_list(multiplier, input, spans, |input| {
+                parse_component_name(component.name(), input, None)
+            })?;
+            Ok(O::list(items, |items| MatchedValue::List(items, multiplier)))
+        }
+    }
+}
+
+/// Consumes the whole of `input` as a value matching `component`.
+fn parse_entire_component<'i, 't, O: Output>(
+    component: &Component<DefaultImpl>,
+    input: &mut Parser<'i, 't>,
+    spans: Option<&mut ItemSpans>,
+) -> Result<O, Error<'i>> {
+    let value = parse_component(component, input, spans)?;
+    expect_exhausted(input)?;
+    Ok(value)
+}
+
+/// Consumes a `<declaration-value>`, that is, any tokens but bad strings or
+/// URLs, unmatched closing brackets, and, at the top level, semicolons and
+/// `!`, recording the `var()` and `env()` references in it.
+///
+/// https://drafts.csswg.org/css-syntax-3/#typedef-declaration-value
+pub(crate) fn consume_declaration_value<'i, 't, R: References>(
+    input: &mut Parser<'i, 't>,
+    top_level: bool,
+    references: &mut R,
+) -> Result<(), Error<'i>> {
+    loop {
+        let location = input.current_source_location();
+        let token = match input.next_including_whitespace_and_comments() {
+            Ok(token) => token,
+            Err(..) => return Ok(()),
+        };
+        let function = match *token {
+            Token::BadString(..) |
+            Token::BadUrl(..) |
+            Token::CloseParenthesis |
+            Token::CloseSquareBracket |
+            Token::CloseCurlyBracket => return Err(location.new_unexpected_token_error(token.clone())),
+            Token::Semicolon | Token::Delim('!') if top_level => {
+                return Err(location.new_unexpected_token_error(token.clone()));
+            }
+            Token::Function(ref name) => Some(name.clone()),
+            Token::ParenthesisBlock |
+            Token::SquareBracketBlock |
+            Token::CurlyBracketBlock => None,
+            _ => continue,
+        };
+        input.parse_nested_block(|input| {
+            if let Some(ref name) = function {
+                if let Some(result) = substitution::parse_arguments(name, input, references) {
+                    return result;
+                }
+            }
+            consume_declaration_value(input, false, references)
+        })?;
+    }
+}
+
+/// Consumes a value that references custom properties or environment
+/// variables, which can't be matched until they're substituted, but is valid
+/// at parse time as long as it's a valid custom property value.
+///
+/// Returns `None` without consuming anything if there are no references.
+///
+/// https://drafts.csswg.org/css-variables-2/#variables-in-shorthands
+fn parse_pending_substitution<'i, 't, O: Output>(input: &mut Parser<'i, 't>) -> Option<Result<O, Error<'i>>> {
+    let start = input.state();
+    let mut references = O::References::default();
+    let result = consume_declaration_value(input, true, &mut references);
+    if !references.has_references() {
+        input.reset(&start);
+        return None;
+    }
+    Some(result.map(|()| {
+        let source = ascii::trim_ascii_whitespace(input.slice_from(start.position()));
+        O::pending_substitution(references, source)
+    }))
+}
+
+/// Consumes any value that's valid for a custom property, which is what the
+/// universal syntax descriptor matches, except for a lone CSS-wide keyword,
+/// which is not a value to match but applies to the property as a whole.
+/// Other descriptors never match those, since they're not `<custom-ident>`s
+/// and descriptors can't contain them as keywords.
+///
+/// https://drafts.css-houdini.org/css-properties-values-api-1/#universal-syntax-descriptor
+fn parse_universal<'i, 't, O: Output>(input: &mut Parser<'i, 't>) -> Result<O, Error<'i>> {
+    let start = input.position();
+    input.skip_whitespace();
+    let location = input.current_source_location();
+    if let Some(keyword) = CssWideKeyword::parse(input) {
+        return Err(location.new_unexpected_token_error(Token::Ident(keyword.as_str().into())));
+    }
+    // Any references are fine here, and there's nothing to record about them.
+    consume_declaration_value(input, true, &mut false)?;
+    Ok(O::value(|| {
+        let source = ascii::trim_ascii_whitespace(input.slice_from(start));
+        MatchedValue::Unparsed(source.into())
+    }))
+}
+
+impl Descriptor<DefaultImpl> {
+    /// Parses a value according to this syntax descriptor, that is, checks
+    /// whether the whole of `input` matches one of the components of the
+    /// descriptor, and returns what it matched. Components are tried in
+    /// order.
+    ///
+    /// https://drafts.css-houdini.org/css-properties-values-api-1/#parse-a-value-according-to-a-syntax
+    ///
+    /// On failure, the error of the component that got furthest into the
+    /// value is reported.
+    pub fn parse_value<'i, 't>(&self, input: &mut Parser<'i, 't>) -> Result<MatchedValue, MatchError> {
+        self.match_value(input, None).map(|(value, _)| value).map_err(|e| e.error)
+    }
+
+    /// Like `parse_value`, but also returns which component of the
+    /// descriptor matched and where the value and its list items are in the
+    /// input, so that they can be highlighted.
+    pub fn parse_value_with_spans<'i, 't>(&self, input: &mut Parser<'i, 't>) -> Result<SpannedMatch, MatchError> {
+        let start = input.position();
+        let mut spans = ItemSpans { start: start.byte_index(), items: vec![] };
+        let (value, component) = self.match_value(input, Some(&mut spans)).map_err(|e| e.error)?;
+        let source = input.slice_from(start);
+        let trimmed = ascii::trim_ascii_whitespace(source);
+        let offset = trimmed.as_ptr() as usize - source.as_ptr() as usize;
+        Ok(SpannedMatch {
+            value,
+            component,
+            span: offset..offset + trimmed.len(),
+            item_spans: spans.items,
+        })
+    }
+
+    /// Parses a value according to this descriptor, returning it along with
+    /// the index of the component that matched, if any.
+    fn match_value<'i, 't>(
+        &self,
+        input: &mut Parser<'i, 't>,
+        mut spans: Option<&mut ItemSpans>,
+    ) -> Result<(MatchedValue, Option<usize>), MatchExplanation> {
+        let start = input.state();
+        let result = match parse_pending_substitution(input) {
+            Some(result) => Some(result),
+            None if self.is_universal() => Some(input.try_parse(parse_universal)),
+            None => None,
+        };
+        if let Some(result) = result {
+            let error = match result {
+                Ok(value) => return Ok((value, None)),
+                Err(error) => error,
+            };
+            while input.next_including_whitespace_and_comments().is_ok() {}
+            let source = input.slice_from(start.position());
+            let error = MatchError::new(&error, source, start.source_location());
+            return Err(MatchExplanation { error, expected: vec![] });
+        }
+        let mut furthest: Option<Error<'i>> = None;
+        let mut expected = vec![];
+        for (index, component) in self.iter().enumerate() {
+            if let Some(ref mut spans) = spans {
+                spans.items.clear();
+            }
+            let result = input.try_parse(|input| {
+                parse_entire_component(component, input, spans.as_deref_mut())
+            });
+            let error = match result {
+                Ok(value) => return Ok((value, Some(index))),
+                Err(error) => error,
+            };
+            match furthest {
+                Some(ref f) if is_further(f, &error) => {}
+                Some(ref f) if !is_further(&error, f) => expected.push(component.clone()),
+                _ => {
+                    furthest = Some(error);
+                    expected = vec![component.clone()];
+                }
+            }
+        }
+        while input.next_including_whitespace_and_comments().is_ok() {}
+        let source = input.slice_from(start.position());
+        let error = MatchError::new(&furthest.unwrap(), source, start.source_location());
+        Err(MatchExplanation { error, expected })
+    }
+
+    /// Returns why `input` doesn't match this syntax descriptor, or `None`
+    /// if it does.
+    ///
+    /// This reports the same error as `parse_value`, along with what the
+    /// components that got furthest into the value expected there.
+    pub fn explain_mismatch<'i, 't>(&self, input: &mut Parser<'i, 't>) -> Option<MatchExplanation> {
+        self.match_value(input, None).err()
+    }
+
+    /// Like `parse_value`, but failing without trying to match the value if
+    /// it exceeds `limits`.
+    pub fn parse_value_with_limits<'i, 't>(
+        &self,
+        input: &mut Parser<'i, 't>,
+        limits: &MatchLimits,
+    ) -> Result<MatchedValue, MatchError> {
+        let start = input.state();
+        if let Err((kind, location)) = check_limits(input, limits, 0, &mut 0) {
+            while input.next_including_whitespace_and_comments().is_ok() {}
+            let source = input.slice_from(start.position());
+            let offset = offset_of(source, start.source_location(), location);
+            return Err(MatchError { kind, location: SourceLocation::from_offset(source, offset) });
+        }
+        input.reset(&start);
+        self.parse_value(input)
+    }
+
+    /// Like `value_matches`, but not matching values that exceed `limits`.
+    pub fn value_matches_with_limits<'i, 't>(&self, input: &mut Parser<'i, 't>, limits: &MatchLimits) -> bool {
+        let start = input.state();
+        if check_limits(input, limits, 0, &mut 0).is_err() {
+            return false;
+        }
+        input.reset(&start);
+        self.value_matches(input)
+    }
+
+    /// Returns whether the whole of `input` matches this syntax descriptor.
+    ///
+    /// This is the same check as `parse_value`, but it doesn't build the
+    /// matched value or an error, nor record the names that `var()` and
+    /// `env()` reference, so it's cheaper when only validity matters. Math
+    /// functions, colors, images and transform functions are still parsed
+    /// into their values, which can allocate, since that's how they're
+    /// validated.
+    pub fn value_matches<'i, 't>(&self, input: &mut Parser<'i, 't>) -> bool {
+        if let Some(result) = parse_pending_substitution::<()>(input) {
+            return result.is_ok();
+        }
+        if self.is_universal() {
+            return input.try_parse(parse_universal::<()>).is_ok();
+        }
+        self.iter().any(|component| {
+            input.try_parse(|input| parse_entire_component::<()>(component, input, None)).is_ok()
+        })
+    }
+
+    /// Returns whether the whole of `value` matches this syntax descriptor,
+    /// like `value_matches`, for callers that have the value as a string.
+    pub fn value_matches_str(&self, value: &str) -> bool {
+        let mut input = cssparser::ParserInput::new(value);
+        self.value_matches(&mut Parser::new(&mut input))
+    }
+}
+
+#[test]
+fn parse_value_test() {
+    use super::parse_descriptor;
+
+    fn parse(syntax: &str, value: &str) -> Result<MatchedValue, MatchError> {
+        let descriptor = parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        descriptor.parse_value(&mut Parser::new(&mut input))
+    }
+
+    for &(syntax, value) in &[
+        ("<length>", "10px"),
+        ("<length>", " 0 "),
+        ("<length>", "1.5Q"),
+        ("<length>+", "1rlh 2dvmin 3svh 4cqi 5vb"),
+        ("<length> | auto", "auto"),
+        ("auto | <length>", "2em"),
+        ("<number>", "1.5"),
+        ("<integer>", "3"),
+        ("<integer>", "calc(1.5 * 2)"),
+        ("<number>", "calc(1 / 3)"),
+        ("<angle>", "calc(1turn - 10deg)"),
+        ("<length>", "max(1rem, 2vw)"),
+        ("<length-percentage>", "clamp(1rem, 50%, 10vw)"),
+        ("<integer>", "round(up, 2.1)"),
+        ("<time>", "calc(1s / 2)"),
+        ("<resolution>", "calc(2x * 2)"),
+        ("<percentage> | <angle>", "90deg"),
+        ("<angle>+", "1grad 2RAD 0.5turn"),
+        ("<length-percentage>", "50%"),
+        ("<length-percentage>", "calc(50% - 1em)"),
+        ("<length>", "calc(10px * 2)"),
+        ("<percentage>", "calc(50% + 10%)"),
+        ("<color>", "rgb(0, 0, 255)"),
+        ("<color>", "#abc"),
+        ("<color>", "oklch(70% 0.1 120)"),
+        ("<color># | none", "hwb(10 20% 30%), color(srgb 1 0 0)"),
+        ("<url>", "url(foo.png)"),
+        ("<image>", "url('foo.png')"),
+        ("<image>#", "linear-gradient(red, blue), image-set('a.png' 2x)"),
+        ("<time>+", "1s 200ms"),
+        ("<resolution>#", "2x, 96dpi"),
+        ("<transform-list>", "translate(10px) rotate(45deg)"),
+        ("<transform-function>#", "matrix(1, 0, 0, 1, 0, 0), perspective(none)"),
+        ("<custom-ident>", "foo"),
+        ("auto", "AUTO"),
+        ("<length> | auto", "Auto"),
+        ("*", "anything { goes; !here } [(1px)]"),
+        ("*", ""),
+        ("*", "inherit inherit"),
+        ("*", "foo(initial)"),
+        ("*", "{ unclosed"),
+        ("<length>", "var(--foo)"),
+        ("<color>+", "red var(--foo, blue) env(bar)"),
+        ("<integer>", "calc(var(--foo) + 1)"),
+        ("*", "var(--a)"),
+    ] {
+        assert!(parse(syntax, value).is_ok(), "{:?} should match {:?}", value, syntax);
+    }
+
+    for &(syntax, value) in &[
+        ("<length>", "10"),
+        ("<length>", "10deg"),
+        ("<length>", "10foo"),
+        ("<integer>", "1.5"),
+        ("<integer>", "calc(1px)"),
+        ("<number>", "calc(10%)"),
+        ("<angle>", "calc(1deg + 1s)"),
+        ("<time>", "calc(10px)"),
+        ("<length>", "min(1px, 10%)"),
+        ("<custom-ident>", "calc(1)"),
+        ("<length>", "calc(50% - 1em)"),
+        ("<length-percentage>", "calc(10px + 1s)"),
+        ("<percentage>", "calc(10px)"),
+        ("<angle>", "0"),
+        ("<angle>", "1px"),
+        ("<time>", "1hz"),
+        ("<resolution>", "2dppxx"),
+        ("<length> | auto", "none"),
+        ("<length>", "10px 10px"),
+        ("<length>+", ""),
+        ("<length>#", "1px,"),
+        ("<length>#", ",1px"),
+        ("<length>#", "1px,,2px"),
+        ("<length>#", "1px 2px, 3px"),
+        ("<length>+", "1px, 2px"),
+        ("<length>+ | <length>#", "1px 2px, 3px"),
+        ("<custom-ident>", "inherit"),
+        ("<custom-ident>", "Revert-Layer"),
+        ("<custom-ident>", "DEFAULT"),
+        ("auto", "auto-fill"),
+        ("<transform-function>", "foo(1px)"),
+        ("*", "foo;"),
+        ("*", "1px !important"),
+        ("*", "foo)"),
+        ("*", "(]"),
+        ("*", "'bad\nstring'"),
+        ("*", "url(bad url)"),
+        ("*", " inherit "),
+        ("*", "Revert-Layer"),
+        ("*", "default"),
+        ("<length>", "var(foo)"),
+        ("<length>", "var(--foo) ;"),
+        ("<length>", "var(--foo) )"),
+        ("<transform-list>", "translate(10deg)"),
+        ("<transform-list>", ""),
+        ("<transform-list>", "none"),
+        ("<transform-list>", "scale(2),rotate(1deg)"),
+    ] {
+        assert!(parse(syntax, value).is_err(), "{:?} shouldn't match {:?}", value, syntax);
+    }
+
+    assert_eq!(parse("<number>", "calc(1 / 4)"), Ok(MatchedValue::Number(0.25)));
+    assert_eq!(parse("<integer>", "calc(5 / 2)"), Ok(MatchedValue::Integer(3)));
+    for &value in &["calc(1em / 1px)", "sign(10%)", "round(up, 1.5em / 1em)"] {
+        assert!(matches!(parse("<number>", value), Ok(MatchedValue::Calc(..))), "{:?}", value);
+        assert!(matches!(parse("<integer>", value), Ok(MatchedValue::IntegerCalc(..))), "{:?}", value);
+    }
+    assert_eq!(parse("<integer>", "sign(10%)").unwrap().to_css_string(), "sign(10%)");
+
+    let error = parse("<length> | <color>", "10px 20px").unwrap_err();
+    assert_eq!(error.kind, MatchErrorKind::UnexpectedToken("20px".into()));
+    assert_eq!(error.location, SourceLocation { offset: 5, line: 1, column: 6 });
+
+    let error = parse("<length>#", "1px,\n  é, 2px").unwrap_err();
+    assert_eq!(error.kind, MatchErrorKind::UnexpectedToken("é".into()));
+    assert_eq!(error.location, SourceLocation { offset: 7, line: 2, column: 3 });
+    assert_eq!(parse("<length>", "").unwrap_err().kind, MatchErrorKind::UnexpectedEnd);
+
+    let error = parse("<length>#", "1px 2px, 3px").unwrap_err();
+    assert_eq!(error.kind, MatchErrorKind::UnexpectedToken("2px".into()));
+    assert_eq!(error.location.offset, 4);
+}
+
+#[test]
+fn parse_value_with_spans_test() {
+    use super::parse_descriptor;
+
+    fn parse(syntax: &str, value: &str) -> SpannedMatch {
+        let descriptor = parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        descriptor.parse_value_with_spans(&mut Parser::new(&mut input)).unwrap()
+    }
+
+    let matched = parse("<length> | <color>+", " red  rgb(0, 0, 0)\n");
+    assert_eq!(matched.component, Some(1));
+    assert_eq!(matched.span, 1..18);
+    assert_eq!(matched.item_spans, [1..4, 6..18]);
+
+    let matched = parse("<length>#", "1px ,2px");
+    assert_eq!(matched.item_spans, [0..3, 5..8]);
+
+    let matched = parse("none | <transform-list>", "scale(2) rotate(1deg)");
+    assert_eq!(matched.component, Some(1));
+    assert_eq!(matched.item_spans, [0..8, 9..21]);
+
+    let matched = parse("<length>", "  1px");
+    assert_eq!((matched.component, matched.span), (Some(0), 2..5));
+    assert!(matched.item_spans.is_empty());
+
+    let matched = parse("<length>+", "1px var(--c)");
+    assert_eq!((matched.component, matched.span), (None, 0..12));
+    assert!(matched.item_spans.is_empty());
+}
+
+#[test]
+fn explain_mismatch_test() {
+    use super::parse_descriptor;
+
+    fn explain(syntax: &str, value: &str) -> Option<String> {
+        let descriptor = parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        descriptor.explain_mismatch(&mut Parser::new(&mut input)).map(|e| e.to_string())
+    }
+
+    assert_eq!(
+        explain("<length> | auto", "12").unwrap(),
+        "expected <length> or 'auto', found '12' at line 1, column 1",
+    );
+    assert_eq!(
+        explain("<length> | <color># | none", "foo").unwrap(),
+        "expected <length>, <color># or 'none', found 'foo' at line 1, column 1",
+    );
+    assert_eq!(
+        explain("<color># | <length>#", "red, blue,").unwrap(),
+        "expected <color>#, found the end of the value at line 1, column 11",
+    );
+    assert_eq!(
+        explain("<color>+ | <length>", "red 1px").unwrap(),
+        "expected <color>+, found '1px' at line 1, column 5",
+    );
+    assert_eq!(explain("*", "a;").unwrap(), "unexpected ';' at line 1, column 2");
+    assert_eq!(explain("<length> | auto", "auto"), None);
+}
+
+#[test]
+fn value_matches_test() {
+    use super::parse_descriptor;
+
+    fn matches(syntax: &str, value: &str) -> bool {
+        let descriptor = parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        let mut input = Parser::new(&mut input);
+        let matches = descriptor.value_matches(&mut input);
+        assert_eq!(matches, {
+            let mut input = cssparser::ParserInput::new(value);
+            descriptor.parse_value(&mut Parser::new(&mut input)).is_ok()
+        });
+        matches
+    }
+
+    assert!(matches("<length> | auto", "auto"));
+    assert!(matches("<color>#", "red, blue"));
+    assert!(matches("*", ""));
+    assert!(!matches("<length> | auto", "10px auto"));
+    assert!(!matches("<integer>+", "1 2.5"));
+    assert!(!matches("*", "unset"));
+    assert!(!matches("*", "a; b"));
+    assert!(matches("<length>", "calc(var(--a, env(b)) * 2)"));
+    assert!(matches("*", "var(--a) foo"));
+    assert!(!matches("<length>", "var(a)"));
+    assert!(!matches("<length>", "env(b) !important"));
+
+    let descriptor = parse_descriptor("<length-percentage># | none").unwrap();
+    assert!(descriptor.value_matches_str(" 1px, calc(50% - 1em) "));
+    assert!(descriptor.value_matches_str("NONE"));
+    assert!(descriptor.value_matches_str("var(--x)"));
+    assert!(!descriptor.value_matches_str("1px none"));
+    assert!(!descriptor.value_matches_str(""));
+}
+
+#[test]
+fn match_limits_test() {
+    use super::parse_descriptor;
+
+    fn parse(syntax: &str, value: &str, limits: &MatchLimits) -> Result<MatchedValue, String> {
+        let descriptor = parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        let result = descriptor.parse_value_with_limits(&mut Parser::new(&mut input), limits);
+        let mut input = cssparser::ParserInput::new(value);
+        assert_eq!(descriptor.value_matches_with_limits(&mut Parser::new(&mut input), limits), result.is_ok());
+        result.map_err(|e| e.to_string())
+    }
+
+    let limits = MatchLimits { max_nesting_depth: Some(2), max_tokens: Some(12) };
+    assert!(parse("<length>", "calc((1px + 2px) * 3)", &limits).is_ok());
+    assert_eq!(
+        parse("<length>", "calc(((1px)))", &limits),
+        Err("value nested too deeply at line 1, column 7".into()),
+    );
+    assert_eq!(
+        parse("<length>+", "1px 2px 3px 4px 5px 6px 7px", &limits),
+        Err("too many tokens in value at line 1, column 25".into()),
+    );
+    assert_eq!(
+        parse("*", "a(b(c(d)))", &limits),
+        Err("value nested too deeply at line 1, column 5".into()),
+    );
+    assert!(parse("<length>", "calc(1px + 2px)", &MatchLimits::default()).is_ok());
+    let nested = format!("{}1px{}", "calc(".repeat(100), ")".repeat(100));
+    assert!(parse("<length>", &nested, &MatchLimits::untrusted()).is_err());
+    assert!(parse("<length>+", &"1px ".repeat(10_000), &MatchLimits::untrusted()).is_err());
+}
@@ -0,0 +1,57 @@
+/// Options to customize how syntax descriptors are parsed.
+///
+/// The default options follow the spec strictly.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseOptions {
+    /// Whether data type names are matched ASCII case-insensitively, so that
+    /// e.g. `<Length>` is accepted. The spec requires an exact match.
+    pub case_insensitive_data_type_names: bool,
+    /// Whether unknown data type names are preserved as
+    /// `ComponentName::UnknownDataType` instead of being a parse error, so
+    /// that descriptors using future or vendor data types can be round-tripped.
+    /// Names with anything but identifier code points are still an error.
+    pub preserve_unknown_data_types: bool,
+}
+
+impl ParseOptions {
+    /// Options that follow the spec strictly.
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Options that accept some invalid but unambiguous syntax descriptors,
+    /// for tools that process author input leniently.
+    pub fn compat() -> Self {
+        Self {
+            case_insensitive_data_type_names: true,
+            preserve_unknown_data_types: true,
+        }
+    }
+}
+
+/// Limits on the values that `Descriptor::parse_value_with_limits` and
+/// `Descriptor::value_matches_with_limits` accept, so that matching
+/// untrusted values uses bounded memory and stack.
+///
+/// Values that exceed them don't match. The default options have no limits.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchLimits {
+    /// How deeply functions and blocks, like `calc()` arguments or
+    /// parenthesized sums in them, can be nested. A value with no functions
+    /// nor blocks has a depth of zero.
+    pub max_nesting_depth: Option<usize>,
+    /// How many tokens the value can have, including whitespace and
+    /// comments, and the tokens in functions and blocks.
+    pub max_tokens: Option<usize>,
+}
+
+impl MatchLimits {
+    /// Limits that no value that makes sense in a stylesheet gets close to,
+    /// for services that validate untrusted values.
+    pub fn untrusted() -> Self {
+        Self {
+            max_nesting_depth: Some(32),
+            max_tokens: Some(10_000),
+        }
+    }
+}
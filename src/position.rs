@@ -0,0 +1,281 @@
+//! Matching of `<position>` values, behind the `experimental-position`
+//! feature.
+//!
+//! https://drafts.csswg.org/css-values-4/#position
+
+use std::fmt;
+
+use cssparser::{Parser, Token};
+
+use super::matching::{self, Error};
+use super::value::{MatchedValue, Output};
+use super::DataType;
+
+/// A keyword of a `<position>`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PositionKeyword {
+    Left,
+    Center,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl PositionKeyword {
+    pub const ALL: &'static [PositionKeyword] = &[
+        PositionKeyword::Left,
+        PositionKeyword::Center,
+        PositionKeyword::Right,
+        PositionKeyword::Top,
+        PositionKeyword::Bottom,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            PositionKeyword::Left => "left",
+            PositionKeyword::Center => "center",
+            PositionKeyword::Right => "right",
+            PositionKeyword::Top => "top",
+            PositionKeyword::Bottom => "bottom",
+        }
+    }
+
+    /// Returns the keyword with the given name, ASCII case-insensitively.
+    pub fn from_ident(ident: &str) -> Option<Self> {
+        Self::ALL.iter().find(|keyword| ident.eq_ignore_ascii_case(keyword.as_str())).cloned()
+    }
+
+    /// Returns whether this keyword can position along the horizontal axis.
+    fn is_horizontal(&self) -> bool {
+        matches!(*self, PositionKeyword::Left | PositionKeyword::Center | PositionKeyword::Right)
+    }
+
+    /// Returns whether this keyword can position along the vertical axis.
+    fn is_vertical(&self) -> bool {
+        matches!(*self, PositionKeyword::Top | PositionKeyword::Center | PositionKeyword::Bottom)
+    }
+
+    /// Returns where along its axis this keyword positions, where `1.0` is
+    /// `100%`.
+    pub fn to_percentage(&self) -> f64 {
+        match *self {
+            PositionKeyword::Left | PositionKeyword::Top => 0.,
+            PositionKeyword::Center => 0.5,
+            PositionKeyword::Right | PositionKeyword::Bottom => 1.,
+        }
+    }
+}
+
+/// The position along one axis of a `<position>`: a keyword, an offset, or
+/// an offset from the side a keyword names.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionComponent {
+    pub keyword: Option<PositionKeyword>,
+    /// A `<length-percentage>`, from the start of the axis if there's no
+    /// keyword.
+    pub offset: Option<Box<MatchedValue>>,
+}
+
+impl PositionComponent {
+    fn keyword(keyword: PositionKeyword) -> Self {
+        Self { keyword: Some(keyword), offset: None }
+    }
+
+    /// Returns whether the offset is or contains a percentage.
+    pub fn has_percentage(&self) -> bool {
+        self.offset.as_ref().is_some_and(|offset| offset.has_percentage())
+    }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        if let Some(keyword) = self.keyword {
+            dest.write_str(keyword.as_str())?;
+            if self.offset.is_some() {
+                dest.write_char(' ')?;
+            }
+        }
+        match self.offset {
+            Some(ref offset) => offset.to_css(dest),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A matched `<position>`, with the one-value syntax expanded so that both
+/// axes are always given.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Position {
+    pub horizontal: PositionComponent,
+    pub vertical: PositionComponent,
+}
+
+impl Position {
+    /// Returns whether any of the offsets is or contains a percentage.
+    pub fn has_percentage(&self) -> bool {
+        self.horizontal.has_percentage() || self.vertical.has_percentage()
+    }
+
+    /// Serializes the horizontal position followed by the vertical one.
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        self.horizontal.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.vertical.to_css(dest)
+    }
+}
+
+impl cssparser::ToCss for Position {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        Position::to_css(self, dest)
+    }
+}
+
+/// A single keyword or `<length-percentage>` of a `<position>`.
+enum Item {
+    Keyword(PositionKeyword),
+    Offset(MatchedValue),
+}
+
+impl Item {
+    fn into_component(self) -> PositionComponent {
+        match self {
+            Item::Keyword(keyword) => PositionComponent::keyword(keyword),
+            Item::Offset(offset) => PositionComponent { keyword: None, offset: Some(Box::new(offset)) },
+        }
+    }
+}
+
+fn parse_keyword<'i, 't>(input: &mut Parser<'i, 't>) -> Result<PositionKeyword, Error<'i>> {
+    let location = input.current_source_location();
+    let token = input.next()?;
+    if let Token::Ident(ref ident) = *token {
+        if let Some(keyword) = PositionKeyword::from_ident(ident) {
+            return Ok(keyword);
+        }
+    }
+    Err(location.new_unexpected_token_error(token.clone()))
+}
+
+fn parse_offset<'i, 't>(input: &mut Parser<'i, 't>) -> Result<MatchedValue, Error<'i>> {
+    matching::parse_data_type(DataType::LengthPercentage, input)
+}
+
+fn parse_item<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Item, Error<'i>> {
+    if let Ok(keyword) = input.try_parse(parse_keyword) {
+        return Ok(Item::Keyword(keyword));
+    }
+    Ok(Item::Offset(parse_offset(input)?))
+}
+
+/// Consumes a side keyword followed by an offset from it, like `right 10px`.
+fn parse_side_and_offset<'i, 't>(input: &mut Parser<'i, 't>) -> Result<PositionComponent, Error<'i>> {
+    let location = input.current_source_location();
+    let keyword = parse_keyword(input)?;
+    if keyword == PositionKeyword::Center {
+        return Err(location.new_unexpected_token_error(Token::Ident(keyword.as_str().into())));
+    }
+    let offset = parse_offset(input)?;
+    Ok(PositionComponent { keyword: Some(keyword), offset: Some(Box::new(offset)) })
+}
+
+/// `[ left | right ] <length-percentage> && [ top | bottom ] <length-percentage>`
+fn parse_four_values<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Position, Error<'i>> {
+    let location = input.current_source_location();
+    let first = parse_side_and_offset(input)?;
+    let second = parse_side_and_offset(input)?;
+    let (horizontal, vertical) = match (first.keyword, second.keyword) {
+        (Some(a), Some(b)) if a.is_horizontal() && b.is_vertical() => (first, second),
+        (Some(a), Some(b)) if a.is_vertical() && b.is_horizontal() => (second, first),
+        _ => return Err(location.new_custom_error(())),
+    };
+    Ok(Position { horizontal, vertical })
+}
+
+/// A horizontal and a vertical position, in either order if they're both
+/// keywords.
+fn parse_two_values<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Position, Error<'i>> {
+    let location = input.current_source_location();
+    let (first, second) = (parse_item(input)?, parse_item(input)?);
+    let (horizontal, vertical) = match (first, second) {
+        (Item::Keyword(first), Item::Keyword(second)) => {
+            let (horizontal, vertical) = if first.is_horizontal() && second.is_vertical() {
+                (first, second)
+            } else if first.is_vertical() && second.is_horizontal() {
+                (second, first)
+            } else {
+                return Err(location.new_custom_error(()));
+            };
+            (PositionComponent::keyword(horizontal), PositionComponent::keyword(vertical))
+        }
+        (first, second) => {
+            let valid = match (&first, &second) {
+                (Item::Keyword(keyword), _) => keyword.is_horizontal(),
+                (_, Item::Keyword(keyword)) => keyword.is_vertical(),
+                _ => true,
+            };
+            if !valid {
+                return Err(location.new_custom_error(()));
+            }
+            (first.into_component(), second.into_component())
+        }
+    };
+    Ok(Position { horizontal, vertical })
+}
+
+/// A single keyword or offset, with `center` on the other axis.
+fn parse_one_value<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Position, Error<'i>> {
+    let center = PositionComponent::keyword(PositionKeyword::Center);
+    Ok(match parse_item(input)? {
+        Item::Keyword(keyword) if !keyword.is_horizontal() => {
+            Position { horizontal: center, vertical: PositionComponent::keyword(keyword) }
+        }
+        item => Position { horizontal: item.into_component(), vertical: center },
+    })
+}
+
+/// Consumes a `<position>`, preferring the longest form that matches so that
+/// `left top` is a single position rather than two.
+pub(crate) fn parse_position<'i, 't, O: Output>(input: &mut Parser<'i, 't>) -> Result<O, Error<'i>> {
+    let position = match input.try_parse(parse_four_values) {
+        Ok(position) => position,
+        Err(..) => match input.try_parse(parse_two_values) {
+            Ok(position) => position,
+            Err(..) => parse_one_value(input)?,
+        },
+    };
+    Ok(O::value(|| MatchedValue::Position(position)))
+}
+
+#[test]
+fn position_test() {
+    fn parse(syntax: &str, value: &str) -> Result<String, ()> {
+        let descriptor = super::parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        let mut input = Parser::new(&mut input);
+        match descriptor.parse_value(&mut input) {
+            Ok(value) => Ok(value.to_css_string()),
+            Err(..) => Err(()),
+        }
+    }
+
+    for &(value, serialization) in &[
+        ("left", "left center"),
+        ("TOP", "center top"),
+        ("center", "center center"),
+        ("10px", "10px center"),
+        ("top left", "left top"),
+        ("center bottom", "center bottom"),
+        ("bottom center", "center bottom"),
+        ("left 10%", "left 10%"),
+        ("1em calc(50% - 2px)", "1em calc(50% - 2px)"),
+        ("10px top", "10px top"),
+        ("bottom 10px right 20%", "right 20% bottom 10px"),
+        ("left 0 top 1px", "left 0px top 1px"),
+    ] {
+        assert_eq!(parse("<position>", value).as_deref(), Ok(serialization), "{:?}", value);
+    }
+    for value in &["left right", "top 10px", "10px left", "left 10px top", "center 1px top 2px", "1 2"] {
+        assert_eq!(parse("<position>", value), Err(()), "{:?}", value);
+    }
+    assert_eq!(parse("<position>+", "left top right").as_deref(), Ok("left top right center"));
+    assert_eq!(parse("<position>#", "1px 2px, top").as_deref(), Ok("1px 2px, center top"));
+    assert_eq!(parse("<position> | auto", "auto").as_deref(), Ok("auto"));
+}
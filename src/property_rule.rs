@@ -0,0 +1,293 @@
+//! Parsing of `@property` rules.
+//!
+//! https://drafts.css-houdini.org/css-properties-values-api-1/#at-property-rule
+
+use std::fmt;
+
+use cssparser::{
+    AtRuleParser, AtRuleType, CowRcStr, DeclarationListParser, DeclarationParser, Parser,
+    QualifiedRuleParser, SourceLocation, Token,
+};
+
+use super::registry::{self, PropertyRegistration, PropertyRegistry, RegistrationError};
+use super::parse_descriptor;
+
+/// Why an `@property` rule is invalid.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyRuleError {
+    /// The input is not a single `@property` rule for a custom property
+    /// name.
+    InvalidPrelude,
+    /// A required descriptor is missing, or all its declarations were
+    /// invalid. The name is that of the descriptor, like `syntax`.
+    MissingDescriptor(&'static str),
+    /// The descriptors don't make a valid registration, like when the
+    /// initial value doesn't match the syntax.
+    Registration(RegistrationError),
+}
+
+impl fmt::Display for PropertyRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PropertyRuleError::InvalidPrelude => f.write_str("not an @property rule for a custom property"),
+            PropertyRuleError::MissingDescriptor(name) => write!(f, "missing '{}' descriptor", name),
+            PropertyRuleError::Registration(ref error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for PropertyRuleError {}
+
+type Error<'i> = cssparser::ParseError<'i, PropertyRuleError>;
+
+/// A valid declaration of one of the descriptors of the rule.
+enum PropertyDescriptor<'i> {
+    /// The syntax string, which is only parsed once all the descriptors are
+    /// known.
+    Syntax(CowRcStr<'i>),
+    Inherits(bool),
+    /// The initial value, as written.
+    InitialValue(&'i str),
+}
+
+struct PropertyRuleParser;
+
+impl<'i> QualifiedRuleParser<'i> for PropertyRuleParser {
+    type Prelude = ();
+    type QualifiedRule = PropertyRegistration;
+    type Error = PropertyRuleError;
+}
+
+impl<'i> AtRuleParser<'i> for PropertyRuleParser {
+    type PreludeNoBlock = ();
+    type PreludeBlock = CowRcStr<'i>;
+    type AtRule = PropertyRegistration;
+    type Error = PropertyRuleError;
+
+    fn parse_prelude<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<AtRuleType<(), CowRcStr<'i>>, Error<'i>> {
+        if !name.eq_ignore_ascii_case("property") {
+            return Err(input.new_custom_error(PropertyRuleError::InvalidPrelude));
+        }
+        let location = input.current_source_location();
+        let name = input.expect_ident_cloned()?;
+        if !name.starts_with("--") {
+            return Err(location.new_unexpected_token_error(Token::Ident(name)));
+        }
+        input.expect_exhausted()?;
+        Ok(AtRuleType::WithBlock(name))
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        _: SourceLocation,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<PropertyRegistration, Error<'i>> {
+        let (mut syntax, mut inherits, mut initial_value) = (None, None, None);
+        // Invalid and unknown descriptors are ignored, and the last valid
+        // declaration of each descriptor wins.
+        for descriptor in DeclarationListParser::new(input, DescriptorParser).flatten() {
+            match descriptor {
+                PropertyDescriptor::Syntax(value) => syntax = Some(value),
+                PropertyDescriptor::Inherits(value) => inherits = Some(value),
+                PropertyDescriptor::InitialValue(value) => initial_value = Some(value),
+            }
+        }
+        let error = |error| input.new_custom_error(error);
+        let syntax = syntax.ok_or_else(|| error(PropertyRuleError::MissingDescriptor("syntax")))?;
+        let inherits = inherits.ok_or_else(|| error(PropertyRuleError::MissingDescriptor("inherits")))?;
+        let syntax = parse_descriptor(&syntax)
+            .map_err(|e| error(PropertyRuleError::Registration(RegistrationError::InvalidSyntax(e))))?;
+        let initial_value = match initial_value {
+            Some(value) => Some(
+                registry::parse_initial_value(&syntax, value)
+                    .map_err(|e| error(PropertyRuleError::Registration(e)))?,
+            ),
+            None if syntax.is_universal() => None,
+            None => return Err(error(PropertyRuleError::MissingDescriptor("initial-value"))),
+        };
+        Ok(PropertyRegistration { name: (*name).into(), syntax, inherits, initial_value })
+    }
+}
+
+struct DescriptorParser;
+
+impl<'i> DeclarationParser<'i> for DescriptorParser {
+    type Declaration = PropertyDescriptor<'i>;
+    type Error = PropertyRuleError;
+
+    fn parse_value<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<PropertyDescriptor<'i>, Error<'i>> {
+        if name.eq_ignore_ascii_case("syntax") {
+            let syntax = input.expect_string_cloned()?;
+            input.expect_exhausted()?;
+            return Ok(PropertyDescriptor::Syntax(syntax));
+        }
+        if name.eq_ignore_ascii_case("inherits") {
+            let location = input.current_source_location();
+            let ident = input.expect_ident_cloned()?;
+            let inherits = if ident.eq_ignore_ascii_case("true") {
+                true
+            } else if ident.eq_ignore_ascii_case("false") {
+                false
+            } else {
+                return Err(location.new_unexpected_token_error(Token::Ident(ident)));
+            };
+            input.expect_exhausted()?;
+            return Ok(PropertyDescriptor::Inherits(inherits));
+        }
+        if name.eq_ignore_ascii_case("initial-value") {
+            let start = input.position();
+            while input.next_including_whitespace_and_comments().is_ok() {}
+            return Ok(PropertyDescriptor::InitialValue(input.slice_from(start)));
+        }
+        Err(input.new_error_for_next_token())
+    }
+}
+
+impl<'i> AtRuleParser<'i> for DescriptorParser {
+    type PreludeNoBlock = ();
+    type PreludeBlock = ();
+    type AtRule = PropertyDescriptor<'i>;
+    type Error = PropertyRuleError;
+}
+
+/// Parses an `@property` rule, validating its descriptors together: the
+/// `syntax` and `inherits` descriptors are required, and so is
+/// `initial-value` unless the syntax is the universal syntax descriptor. The
+/// initial value must match the syntax.
+///
+/// https://drafts.css-houdini.org/css-properties-values-api-1/#at-property-rule
+pub fn parse_property_rule(input: &str) -> Result<PropertyRegistration, PropertyRuleError> {
+    let mut input = cssparser::ParserInput::new(input);
+    let mut input = Parser::new(&mut input);
+    cssparser::parse_one_rule(&mut input, &mut PropertyRuleParser).map_err(|error| match error.kind {
+        cssparser::ParseErrorKind::Custom(error) => error,
+        cssparser::ParseErrorKind::Basic(..) => PropertyRuleError::InvalidPrelude,
+    })
+}
+
+impl PropertyRegistration {
+    /// Serializes this registration as an `@property` rule, like
+    /// `@property --a { syntax: "<length>"; inherits: false; initial-value: 0px; }`.
+    ///
+    /// https://drafts.css-houdini.org/css-properties-values-api-1/#serialize-a-csspropertyrule
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_str("@property ")?;
+        cssparser::serialize_identifier(&self.name, dest)?;
+        dest.write_str(" { syntax: ")?;
+        cssparser::serialize_string(&self.syntax.to_css_string(), dest)?;
+        dest.write_str("; inherits: ")?;
+        dest.write_str(if self.inherits { "true" } else { "false" })?;
+        if let Some(ref initial_value) = self.initial_value {
+            dest.write_str("; initial-value: ")?;
+            initial_value.to_css(dest)?;
+        }
+        dest.write_str("; }")
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+impl PropertyRegistry {
+    /// Serializes the registrations as `@property` rules, one per line, in
+    /// code point order of the property names.
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        for (i, registration) in self.iter().enumerate() {
+            if i > 0 {
+                dest.write_char('\n')?;
+            }
+            registration.to_css(dest)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+#[test]
+fn property_rule_test() {
+    use super::MatchedValue;
+    use super::units::LengthUnit;
+
+    let registration = parse_property_rule(
+        "@property --foo { syntax: '<length> | auto'; inherits: false; initial-value: 0px }",
+    ).unwrap();
+    assert_eq!(&*registration.name, "--foo");
+    assert_eq!(registration.syntax.to_css_string(), "<length> | auto");
+    assert!(!registration.inherits);
+    assert_eq!(registration.initial_value, Some(MatchedValue::Length { value: 0., unit: LengthUnit::Px }));
+
+    let registration = parse_property_rule(
+        "@PROPERTY --bar {\n  Syntax: \"*\";\n  inherits: maybe;\n  unknown: 1;\n  inherits: TRUE;\n}",
+    ).unwrap();
+    assert!(registration.syntax.is_universal());
+    assert!(registration.inherits);
+    assert_eq!(registration.initial_value, None);
+    let registration = parse_property_rule("@property --baz{syntax:'*';inherits:true;initial-value:a}");
+    assert_eq!(registration.unwrap().initial_value, Some(MatchedValue::Unparsed("a".into())));
+
+    for &(rule, ref error) in &[
+        ("@property foo { syntax: '*'; inherits: true }", PropertyRuleError::InvalidPrelude),
+        ("@property --a --b { syntax: '*'; inherits: true }", PropertyRuleError::InvalidPrelude),
+        ("@media --a { syntax: '*'; inherits: true }", PropertyRuleError::InvalidPrelude),
+        ("@property --a;", PropertyRuleError::InvalidPrelude),
+        ("@property --a { syntax: '*'; inherits: true } @property --b {}", PropertyRuleError::InvalidPrelude),
+        ("--a { syntax: '*'; inherits: true }", PropertyRuleError::InvalidPrelude),
+        ("@property --a { inherits: true }", PropertyRuleError::MissingDescriptor("syntax")),
+        ("@property --a { syntax: <length>; inherits: 1 }", PropertyRuleError::MissingDescriptor("syntax")),
+        ("@property --a { syntax: '*' }", PropertyRuleError::MissingDescriptor("inherits")),
+        (
+            "@property --a { syntax: '<length>'; inherits: true }",
+            PropertyRuleError::MissingDescriptor("initial-value"),
+        ),
+    ] {
+        assert_eq!(parse_property_rule(rule).as_ref(), Err(error), "{:?}", rule);
+    }
+    for rule in &[
+        "@property --a { syntax: '<length'; inherits: true; initial-value: 0px }",
+        "@property --a { syntax: '<length>'; inherits: true; initial-value: red }",
+        "@property --a { syntax: '<length>'; inherits: true; initial-value: 2em }",
+    ] {
+        assert!(matches!(parse_property_rule(rule), Err(PropertyRuleError::Registration(..))), "{:?}", rule);
+    }
+
+    let mut registry = PropertyRegistry::new();
+    for &(name, syntax, inherits, initial_value) in &[
+        ("--b", "*", true, None),
+        ("--a", " <length>+ | AUTO", false, Some("1px  2PX")),
+        ("--c\\", "*", false, Some("{ a: \"b\" }")),
+    ] {
+        registry.register(registry::PropertyDefinition {
+            name: name.into(),
+            syntax: syntax.into(),
+            inherits,
+            initial_value: initial_value.map(Into::into),
+        }).unwrap();
+    }
+    let css = registry.to_css_string();
+    assert_eq!(css, concat!(
+        "@property --a { syntax: \"<length>+ | AUTO\"; inherits: false; initial-value: 1px 2px; }\n",
+        "@property --b { syntax: \"*\"; inherits: true; }\n",
+        "@property --c\\\\ { syntax: \"*\"; inherits: false; initial-value: { a: \"b\" }; }",
+    ));
+    for (rule, registration) in css.lines().zip(registry.iter()) {
+        assert_eq!(parse_property_rule(rule).as_ref(), Ok(registration), "{:?}", rule);
+    }
+    assert_eq!(PropertyRegistry::new().to_css_string(), "");
+}
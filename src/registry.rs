@@ -0,0 +1,663 @@
+//! Registration of custom properties, like `CSS.registerProperty()`.
+//!
+//! https://drafts.css-houdini.org/css-properties-values-api-1/#registering-custom-properties
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::typed_om::PropertySyntaxes;
+use super::value::ComputedValue;
+use super::{parse_descriptor, CssWideKeyword, DefaultImpl, Descriptor, MatchError, MatchedValue, ParseError};
+
+/// The arguments of `CSS.registerProperty()`.
+///
+/// With the `serde` feature, this (de)serializes like the JavaScript
+/// dictionary, with camelCase member names and `syntax` defaulting to `"*"`.
+///
+/// https://drafts.css-houdini.org/css-properties-values-api-1/#dictdef-propertydefinition
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PropertyDefinition {
+    /// The name of the custom property, including the leading `--`.
+    pub name: String,
+    /// The syntax string, like `<length> | auto`.
+    #[cfg_attr(feature = "serde", serde(default = "universal_syntax"))]
+    pub syntax: String,
+    /// Whether the property inherits by default, like non-registered
+    /// custom properties do.
+    pub inherits: bool,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub initial_value: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+fn universal_syntax() -> String {
+    "*".into()
+}
+
+/// A registered custom property.
+///
+/// https://drafts.css-houdini.org/css-properties-values-api-1/#registered-custom-property
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyRegistration {
+    pub name: Box<str>,
+    pub syntax: Descriptor<DefaultImpl>,
+    /// Whether the property inherits by default, that is, whether an element
+    /// with no declared value for it takes the value of its parent rather
+    /// than the initial value.
+    pub inherits: bool,
+    /// The initial value, as matched against `syntax`.
+    pub initial_value: Option<MatchedValue>,
+}
+
+/// The value that the cascade gives a custom property on an element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeclaredValue<'a> {
+    Value(&'a MatchedValue),
+    Keyword(CssWideKeyword),
+}
+
+impl PropertyRegistration {
+    /// Returns the value of the property on an element, given its declared
+    /// value, if any, and `parent`, the value on the parent element, which
+    /// for the root element is the initial value. `None` is the
+    /// guaranteed-invalid value.
+    ///
+    /// With no declared value, or `unset`, the property takes the value of
+    /// the parent if it inherits, and the initial value otherwise. `revert`
+    /// and `revert-layer` behave like `unset`, as there are no other origins
+    /// or layers to revert to.
+    ///
+    /// https://drafts.csswg.org/css-cascade-5/#defaulting
+    pub fn specified_value<'a>(
+        &'a self,
+        parent: Option<&'a MatchedValue>,
+        declared: Option<DeclaredValue<'a>>,
+    ) -> Option<&'a MatchedValue> {
+        match declared {
+            Some(DeclaredValue::Value(value)) => Some(value),
+            Some(DeclaredValue::Keyword(CssWideKeyword::Initial)) => self.initial_value.as_ref(),
+            Some(DeclaredValue::Keyword(CssWideKeyword::Inherit)) => parent,
+            Some(DeclaredValue::Keyword(..)) | None => {
+                if self.inherits {
+                    parent
+                } else {
+                    self.initial_value.as_ref()
+                }
+            }
+        }
+    }
+}
+
+/// Why a custom property can't be registered.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegistrationError {
+    /// The name is not a custom property name. This is a `SyntaxError` in
+    /// `CSS.registerProperty()`.
+    InvalidName,
+    /// The syntax string is not a valid syntax descriptor. This is a
+    /// `SyntaxError`.
+    InvalidSyntax(ParseError),
+    /// The initial value can't be the value of a custom property. This is a
+    /// `SyntaxError`.
+    InvalidInitialValue(MatchError),
+    /// The syntax is not the universal syntax descriptor, but there's no
+    /// initial value. This is a `SyntaxError`.
+    MissingInitialValue,
+    /// The initial value is not computationally independent, like `1em`.
+    /// This is a `SyntaxError`.
+    DependentInitialValue,
+    /// A property with the same name is already registered. This is an
+    /// `InvalidModificationError`.
+    AlreadyRegistered(Box<RegistrationConflict>),
+}
+
+impl fmt::Display for RegistrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RegistrationError::InvalidName => f.write_str("not a custom property name"),
+            RegistrationError::InvalidSyntax(ref error) => write!(f, "invalid syntax: {}", error),
+            RegistrationError::InvalidInitialValue(ref error) => {
+                write!(f, "invalid initial value: {}", error)
+            }
+            RegistrationError::MissingInitialValue => f.write_str("missing initial value"),
+            RegistrationError::DependentInitialValue => {
+                f.write_str("initial value is not computationally independent")
+            }
+            RegistrationError::AlreadyRegistered(ref conflict) => conflict.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RegistrationError {}
+
+/// A definition of a property that's already registered, which tells how
+/// the registrations differ, like when a stylesheet and a script register a
+/// property differently.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegistrationConflict {
+    pub existing: PropertyRegistration,
+    /// The definition of the registration that failed, which is not
+    /// validated.
+    pub new: PropertyDefinition,
+}
+
+impl RegistrationConflict {
+    /// Returns whether the new syntax is invalid or not the same as the
+    /// existing one, ignoring whitespace.
+    pub fn syntax_differs(&self) -> bool {
+        parse_descriptor(&self.new.syntax).map_or(true, |syntax| syntax != self.existing.syntax)
+    }
+
+    pub fn inherits_differs(&self) -> bool {
+        self.new.inherits != self.existing.inherits
+    }
+
+    /// Returns whether the new initial value doesn't match the existing
+    /// syntax as the existing initial value, or only one of them is given.
+    pub fn initial_value_differs(&self) -> bool {
+        match (&self.new.initial_value, &self.existing.initial_value) {
+            (Some(new), Some(existing)) => {
+                parse_initial_value(&self.existing.syntax, new).map_or(true, |new| new != *existing)
+            }
+            (new, existing) => new.is_some() != existing.is_some(),
+        }
+    }
+
+    /// Returns whether the new definition would register the property just
+    /// like it is, like when the same stylesheet is loaded twice.
+    pub fn is_redundant(&self) -> bool {
+        !self.syntax_differs() && !self.inherits_differs() && !self.initial_value_differs()
+    }
+}
+
+impl fmt::Display for RegistrationConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("property already registered")?;
+        let differences = [
+            (self.syntax_differs(), "syntax"),
+            (self.inherits_differs(), "inherits"),
+            (self.initial_value_differs(), "initial value"),
+        ];
+        let differences = differences.iter().filter(|&&(differs, _)| differs).map(|&(_, name)| name);
+        let differences = differences.collect::<Vec<_>>();
+        match differences.split_last() {
+            None => f.write_str(" with the same definition"),
+            Some((last, [])) => write!(f, " with a different {}", last),
+            Some((last, rest)) => write!(f, " with a different {} and {}", rest.join(", "), last),
+        }
+    }
+}
+
+/// Matches the initial value of a registration against its syntax, which
+/// unless it's the universal syntax descriptor also requires the value to be
+/// computationally independent.
+pub(crate) fn parse_initial_value(
+    syntax: &Descriptor<DefaultImpl>,
+    value: &str,
+) -> Result<MatchedValue, RegistrationError> {
+    let mut input = cssparser::ParserInput::new(value);
+    let mut input = cssparser::Parser::new(&mut input);
+    let value = syntax.parse_value(&mut input).map_err(RegistrationError::InvalidInitialValue)?;
+    if !syntax.is_universal() && !value.is_computationally_independent() {
+        return Err(RegistrationError::DependentInitialValue);
+    }
+    Ok(value)
+}
+
+/// The differences between two sets of registrations.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RegistryDiff<'a> {
+    /// The registrations of the properties that only the new set has.
+    pub added: Vec<&'a PropertyRegistration>,
+    /// The registrations of the properties that only the old set has.
+    pub removed: Vec<&'a PropertyRegistration>,
+    /// The old and new registrations of the properties registered
+    /// differently in each set.
+    pub changed: Vec<(&'a PropertyRegistration, &'a PropertyRegistration)>,
+}
+
+impl<'a> RegistryDiff<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A change to the registrations of a registry.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RegistryChange<'a> {
+    Registered(&'a PropertyRegistration),
+    Unregistered(&'a PropertyRegistration),
+}
+
+impl<'a> RegistryChange<'a> {
+    /// Returns the registration that was added or removed.
+    pub fn registration(&self) -> &'a PropertyRegistration {
+        match *self {
+            RegistryChange::Registered(registration) |
+            RegistryChange::Unregistered(registration) => registration,
+        }
+    }
+
+    /// Returns the name of the property whose registration changed.
+    pub fn name(&self) -> &'a str {
+        &self.registration().name
+    }
+}
+
+/// Identifies an observer of a registry, so that it can be removed.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ObserverId(u64);
+
+type Observer = Arc<dyn Fn(&RegistryChange) + Send + Sync>;
+
+/// The observers of a registry, which its clones don't have.
+#[derive(Default)]
+struct Observers {
+    observers: Vec<(ObserverId, Observer)>,
+    next_id: u64,
+}
+
+impl Observers {
+    fn notify(&self, change: RegistryChange) {
+        for (_, observer) in &self.observers {
+            observer(&change);
+        }
+    }
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.observers.iter().map(|&(id, _)| id)).finish()
+    }
+}
+
+/// The registered custom properties of a document, or of a tree scope like a
+/// shadow tree.
+///
+/// Registries of tree scopes are nested in the registry of their outer
+/// scope, and look up the properties not registered in them there, so that
+/// their registrations shadow the outer ones. Other than lookups, methods
+/// only deal with the registrations made in the registry itself.
+#[derive(Clone, Debug, Default)]
+pub struct PropertyRegistry {
+    registrations: BTreeMap<Box<str>, PropertyRegistration>,
+    generation: u64,
+    outer: Option<Arc<PropertyRegistry>>,
+    observers: Observers,
+}
+
+impl PropertyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an empty registry for a tree scope nested in the scope of
+    /// `outer`.
+    pub fn nested(outer: Arc<PropertyRegistry>) -> Self {
+        Self { outer: Some(outer), ..Self::default() }
+    }
+
+    /// Returns the registry of the outer scope, if this one is nested.
+    pub fn outer(&self) -> Option<&Arc<PropertyRegistry>> {
+        self.outer.as_ref()
+    }
+
+    /// Registers a custom property, failing if the definition is invalid or
+    /// the property is already registered in this registry, though not if
+    /// it's registered in an outer one. The initial value is required
+    /// unless the syntax is the universal syntax descriptor, and must match
+    /// the syntax.
+    ///
+    /// https://drafts.css-houdini.org/css-properties-values-api-1/#the-registerproperty-function
+    pub fn register(&mut self, definition: PropertyDefinition) -> Result<(), RegistrationError> {
+        if !definition.name.starts_with("--") {
+            return Err(RegistrationError::InvalidName);
+        }
+        if let Some(existing) = self.registrations.get(&*definition.name) {
+            let conflict = RegistrationConflict { existing: existing.clone(), new: definition };
+            return Err(RegistrationError::AlreadyRegistered(Box::new(conflict)));
+        }
+        let syntax = parse_descriptor(&definition.syntax).map_err(RegistrationError::InvalidSyntax)?;
+        let initial_value = match definition.initial_value {
+            Some(ref value) => Some(parse_initial_value(&syntax, value)?),
+            None if syntax.is_universal() => None,
+            None => return Err(RegistrationError::MissingInitialValue),
+        };
+        let name: Box<str> = definition.name.into();
+        let registration = PropertyRegistration {
+            name: name.clone(),
+            syntax,
+            inherits: definition.inherits,
+            initial_value,
+        };
+        self.generation += 1;
+        let registration = self.registrations.entry(name).or_insert(registration);
+        self.observers.notify(RegistryChange::Registered(registration));
+        Ok(())
+    }
+
+    /// Removes the registration of the custom property `name`, returning it
+    /// if there was one.
+    ///
+    /// There's no `CSS.unregisterProperty()`, but registrations go away with
+    /// the stylesheets or documents that made them.
+    pub fn unregister(&mut self, name: &str) -> Option<PropertyRegistration> {
+        let registration = self.registrations.remove(name)?;
+        self.generation += 1;
+        self.observers.notify(RegistryChange::Unregistered(&registration));
+        Some(registration)
+    }
+
+    /// Calls `observer` after every registration and unregistration, until
+    /// it's removed with `unobserve`. Clones and snapshots of the registry
+    /// don't have its observers, but the copies that replace a registry in a
+    /// `SharedPropertyRegistry` when it changes do.
+    ///
+    /// Observers are called while the registry is being changed, so they
+    /// can't access a `SharedPropertyRegistry` that it's part of.
+    pub fn observe(&mut self, observer: impl Fn(&RegistryChange) + Send + Sync + 'static) -> ObserverId {
+        let id = ObserverId(self.observers.next_id);
+        self.observers.next_id += 1;
+        self.observers.observers.push((id, Arc::new(observer)));
+        id
+    }
+
+    /// Removes an observer added with `observe`, returning whether it was
+    /// still there.
+    pub fn unobserve(&mut self, id: ObserverId) -> bool {
+        let len = self.observers.observers.len();
+        self.observers.observers.retain(|&(observer_id, _)| observer_id != id);
+        self.observers.observers.len() != len
+    }
+
+    /// Returns a number that increases every time the registrations change,
+    /// so that anything derived from them, like matched or computed values,
+    /// can be invalidated when it does.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the current registrations, which unlike the registry don't
+    /// change when properties are registered or unregistered, so that they
+    /// can be compared with later ones with `diff`.
+    pub fn snapshot(&self) -> Arc<PropertyRegistry> {
+        Arc::new(self.clone())
+    }
+
+    /// Clones the registry along with its observers, for a change to it that
+    /// replaces it with the clone once done.
+    pub(crate) fn clone_with_observers(&self) -> Self {
+        let observers = Observers {
+            observers: self.observers.observers.clone(),
+            next_id: self.observers.next_id,
+        };
+        Self { observers, ..self.clone() }
+    }
+
+    /// Returns the registrations that changed from `old` to `new`, each in
+    /// code point order of the property names.
+    pub fn diff<'a>(old: &'a PropertyRegistry, new: &'a PropertyRegistry) -> RegistryDiff<'a> {
+        let mut diff = RegistryDiff::default();
+        for old_registration in old.iter() {
+            match new.registrations.get(&old_registration.name) {
+                Some(new_registration) if new_registration != old_registration => {
+                    diff.changed.push((old_registration, new_registration));
+                }
+                Some(..) => {}
+                None => diff.removed.push(old_registration),
+            }
+        }
+        diff.added = new
+            .iter()
+            .filter(|registration| !old.registrations.contains_key(&registration.name))
+            .collect();
+        diff
+    }
+
+    /// Returns the registrations in code point order of the property names.
+    pub fn iter(&self) -> impl Iterator<Item = &PropertyRegistration> + '_ {
+        self.registrations.values()
+    }
+
+    /// Returns the registration of the custom property `name`, in this
+    /// registry or the closest outer one that has it.
+    pub fn get(&self, name: &str) -> Option<&PropertyRegistration> {
+        self.registrations.get(name).or_else(|| self.outer.as_ref()?.get(name))
+    }
+
+    /// Returns the initial value of the custom property `name`, which is the
+    /// guaranteed-invalid value unless it's registered with an initial value.
+    ///
+    /// This is also the value that it takes when it's invalid at
+    /// computed-value time, like when a `var()` reference in its value can't
+    /// be substituted.
+    ///
+    /// https://drafts.csswg.org/css-variables-2/#invalid-at-computed-value-time
+    pub fn initial_value(&self, name: &str) -> ComputedValue {
+        self.get(name).and_then(|registration| registration.initial_value.clone()).into()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.registrations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registrations.is_empty()
+    }
+}
+
+impl PropertySyntaxes for PropertyRegistry {
+    fn syntax(&self, name: &str) -> Option<&Descriptor<DefaultImpl>> {
+        Some(&self.get(name)?.syntax)
+    }
+}
+
+#[cfg(test)]
+fn definition(name: &str, syntax: &str, initial_value: Option<&str>) -> PropertyDefinition {
+    PropertyDefinition {
+        name: name.into(),
+        syntax: syntax.into(),
+        inherits: false,
+        initial_value: initial_value.map(Into::into),
+    }
+}
+
+#[test]
+fn registration_test() {
+    let mut registry = PropertyRegistry::new();
+    assert!(registry.is_empty());
+    assert_eq!(registry.generation(), 0);
+    registry.register(definition("--a", "<length>", Some(" 0px "))).unwrap();
+    registry.register(definition("--b", "*", None)).unwrap();
+    assert_eq!(registry.len(), 2);
+    assert_eq!(registry.generation(), 2);
+
+    let a = registry.get("--a").unwrap();
+    assert_eq!(a.syntax.to_css_string(), "<length>");
+    assert_eq!(a.initial_value, Some(MatchedValue::Length { value: 0., unit: crate::units::LengthUnit::Px }));
+    assert!(!a.inherits);
+    assert!(registry.contains("--b"));
+    assert_eq!(registry.initial_value("--a"), ComputedValue::Value(a.initial_value.clone().unwrap()));
+    assert_eq!(registry.initial_value("--b"), ComputedValue::GuaranteedInvalid);
+    assert_eq!(registry.initial_value("--c"), ComputedValue::GuaranteedInvalid);
+    assert!(!registry.contains("--c"));
+
+    let conflict = |definition| match registry.clone().register(definition) {
+        Err(RegistrationError::AlreadyRegistered(conflict)) => conflict,
+        result => panic!("{:?}", result),
+    };
+    let new = definition("--a", "<color>", Some("red"));
+    let error = conflict(new.clone());
+    assert_eq!((&error.existing, &error.new), (registry.get("--a").unwrap(), &new));
+    assert!(error.syntax_differs() && !error.inherits_differs() && error.initial_value_differs());
+    assert_eq!(error.to_string(), "property already registered with a different syntax and initial value");
+    let error = conflict(definition("--a", " <length> ", Some("0px")));
+    assert!(error.is_redundant());
+    assert_eq!(error.to_string(), "property already registered with the same definition");
+    let error = conflict(PropertyDefinition { inherits: true, ..definition("--a", "<length", None) });
+    assert_eq!(
+        error.to_string(),
+        "property already registered with a different syntax, inherits and initial value",
+    );
+    let error = conflict(definition("--a", "<length>", Some("1px")));
+    assert_eq!(error.to_string(), "property already registered with a different initial value");
+    assert!(conflict(definition("--b", "*", None)).is_redundant());
+    assert_eq!(registry.register(definition("a", "*", None)), Err(RegistrationError::InvalidName));
+    assert!(matches!(
+        registry.register(definition("--c", "<length", None)),
+        Err(RegistrationError::InvalidSyntax(..)),
+    ));
+    for value in &["inherit", "a)", "!important"] {
+        assert!(matches!(
+            registry.register(definition("--c", "*", Some(value))),
+            Err(RegistrationError::InvalidInitialValue(..)),
+        ), "{:?}", value);
+    }
+    for &(syntax, value, ref error) in &[
+        ("<length>", None, RegistrationError::MissingInitialValue),
+        ("<length>", Some("1em"), RegistrationError::DependentInitialValue),
+        ("<length>", Some("calc(1px + 1em)"), RegistrationError::DependentInitialValue),
+        ("<length>#", Some("1px, 2cqw"), RegistrationError::DependentInitialValue),
+        ("<length>", Some("var(--x)"), RegistrationError::DependentInitialValue),
+    ] {
+        assert_eq!(registry.register(definition("--c", syntax, value)).as_ref(), Err(error), "{:?}", value);
+    }
+    assert!(matches!(
+        registry.register(definition("--c", "<length>", Some("red"))),
+        Err(RegistrationError::InvalidInitialValue(..)),
+    ));
+    assert_eq!(registry.len(), 2);
+    registry.register(definition("--c", "<length> | auto", Some("calc(10vw + 1px)"))).unwrap();
+    registry.register(definition("--d", "*", Some("1em var(--x)"))).unwrap();
+    assert_eq!(registry.len(), 4);
+    // Failed registrations don't change anything.
+    assert_eq!(registry.generation(), 4);
+}
+
+#[test]
+fn specified_value_test() {
+    let mut registry = PropertyRegistry::new();
+    registry.register(definition("--a", "<length>", Some("0px"))).unwrap();
+    registry.register(definition("--b", "*", None)).unwrap();
+    registry.register(definition("--c", "<length> | auto", Some("calc(10vw + 1px)"))).unwrap();
+    registry.register(definition("--d", "*", Some("1em var(--x)"))).unwrap();
+
+    let mut inherited = registry.get("--c").unwrap().clone();
+    inherited.inherits = true;
+    let (a, d) = (registry.get("--a").unwrap(), registry.get("--d").unwrap());
+    let parent = MatchedValue::Unparsed("parent".into());
+    let declared = MatchedValue::Unparsed("declared".into());
+    for &(registration, declared, expected) in &[
+        (a, Some(DeclaredValue::Value(&declared)), Some(&declared)),
+        (a, None, a.initial_value.as_ref()),
+        (a, Some(DeclaredValue::Keyword(CssWideKeyword::Unset)), a.initial_value.as_ref()),
+        (a, Some(DeclaredValue::Keyword(CssWideKeyword::Inherit)), Some(&parent)),
+        (&inherited, None, Some(&parent)),
+        (&inherited, Some(DeclaredValue::Keyword(CssWideKeyword::Revert)), Some(&parent)),
+        (&inherited, Some(DeclaredValue::Keyword(CssWideKeyword::Initial)), inherited.initial_value.as_ref()),
+        (registry.get("--b").unwrap(), Some(DeclaredValue::Keyword(CssWideKeyword::Initial)), None),
+        (d, None, d.initial_value.as_ref()),
+    ] {
+        assert_eq!(registration.specified_value(Some(&parent), declared), expected, "{:?}", declared);
+    }
+    assert_eq!(inherited.specified_value(None, None), None);
+
+    let mut map = crate::typed_om::StylePropertyMap::new(&registry);
+    assert!(map.set("--a", &[crate::typed_om::StyleValue::Other("red".into())]).is_err());
+}
+
+#[test]
+fn generation_test() {
+    let mut registry = PropertyRegistry::new();
+    registry.register(definition("--a", "<length>", Some("0px"))).unwrap();
+    registry.register(definition("--b", "*", None)).unwrap();
+    registry.register(definition("--c", "<length> | auto", Some("calc(10vw + 1px)"))).unwrap();
+    registry.register(definition("--d", "*", Some("1em var(--x)"))).unwrap();
+
+    assert_eq!(registry.unregister("--d").map(|registration| registration.name), Some("--d".into()));
+    assert_eq!(registry.unregister("--d"), None);
+    assert_eq!((registry.len(), registry.generation()), (3, 5));
+    registry.register(definition("--d", "<length>", Some("1px"))).unwrap();
+    assert_eq!(registry.get("--d").unwrap().syntax.to_css_string(), "<length>");
+    assert_eq!(registry.generation(), 6);
+
+    let snapshot = registry.snapshot();
+    assert!(PropertyRegistry::diff(&snapshot, &registry).is_empty());
+    registry.unregister("--a");
+    registry.unregister("--c");
+    registry.register(definition("--c", "<length>", Some("1px"))).unwrap();
+    registry.register(definition("--e", "*", None)).unwrap();
+    registry.register(definition("--0", "*", None)).unwrap();
+    fn names(registrations: &[&PropertyRegistration]) -> Vec<String> {
+        registrations.iter().map(|registration| registration.name.to_string()).collect()
+    }
+    assert_eq!(names(&snapshot.iter().collect::<Vec<_>>()), ["--a", "--b", "--c", "--d"]);
+    let diff = PropertyRegistry::diff(&snapshot, &registry);
+    assert_eq!(names(&diff.added), ["--0", "--e"]);
+    assert_eq!(names(&diff.removed), ["--a"]);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].0.initial_value.as_ref().unwrap().to_css_string(), "calc(10vw + 1px)");
+    assert_eq!(diff.changed[0].1.initial_value.as_ref().unwrap().to_css_string(), "1px");
+    assert!(!diff.is_empty());
+}
+
+#[test]
+fn nested_registry_test() {
+    let mut registry = PropertyRegistry::new();
+    registry.register(definition("--b", "*", None)).unwrap();
+    registry.register(definition("--c", "<length>", Some("1px"))).unwrap();
+    registry.register(definition("--d", "<length>", Some("1px"))).unwrap();
+    registry.register(definition("--e", "*", None)).unwrap();
+
+    let document = registry.snapshot();
+    let mut shadow = PropertyRegistry::nested(document.clone());
+    shadow.register(definition("--c", "<color>", Some("red"))).unwrap();
+    shadow.register(definition("--s", "<integer>", Some("1"))).unwrap();
+    assert!(Arc::ptr_eq(shadow.outer().unwrap(), &document));
+    assert_eq!(shadow.get("--c").unwrap().syntax.to_css_string(), "<color>");
+    assert_eq!(shadow.get("--d").unwrap().syntax.to_css_string(), "<length>");
+    assert!(shadow.contains("--b") && !shadow.contains("--a"));
+    assert_eq!(shadow.initial_value("--d").to_css_string(), "1px");
+    assert!(!document.contains("--s"));
+    assert_eq!(shadow.len(), 2);
+    assert_eq!(shadow.iter().map(|registration| &*registration.name).collect::<Vec<_>>(), ["--c", "--s"]);
+    assert_eq!(shadow.unregister("--d"), None);
+    assert_eq!(shadow.unregister("--c").unwrap().syntax.to_css_string(), "<color>");
+    assert_eq!(shadow.get("--c").unwrap().syntax.to_css_string(), "<length>");
+    let inner = PropertyRegistry::nested(Arc::new(shadow));
+    assert!(inner.contains("--s") && inner.contains("--e"));
+    assert!(inner.is_empty());
+}
+
+#[test]
+fn observer_test() {
+    let mut registry = PropertyRegistry::new();
+    let changes = Arc::new(std::sync::Mutex::new(vec![]));
+    let observed = changes.clone();
+    let id = registry.observe(move |change| {
+        let registered = matches!(*change, RegistryChange::Registered(..));
+        observed.lock().unwrap().push((registered, change.name().to_owned()));
+    });
+    registry.register(definition("--a", "*", None)).unwrap();
+    registry.register(definition("--a", "*", None)).unwrap_err();
+    registry.clone().register(definition("--b", "*", None)).unwrap();
+    registry.unregister("--a");
+    registry.unregister("--a");
+    assert!(registry.unobserve(id));
+    assert!(!registry.unobserve(id));
+    registry.register(definition("--c", "*", None)).unwrap();
+    assert_eq!(*changes.lock().unwrap(), [(true, "--a".to_owned()), (false, "--a".to_owned())]);
+}
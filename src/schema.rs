@@ -0,0 +1,175 @@
+//! JSON Schema support, behind the `schemars` feature.
+//!
+//! The schemas describe the serde representation of the public types, see the
+//! `serde_impls` module.
+
+use std::borrow::Cow;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use super::{Component, ComponentName, CustomIdent, DataType, Descriptor, Impl, Multiplier};
+
+impl JsonSchema for DataType {
+    fn schema_name() -> Cow<'static, str> {
+        "DataType".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        concat!(module_path!(), "::DataType").into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let names = DataType::ALL.iter().map(|ty| ty.as_str()).collect::<Vec<_>>();
+        json_schema!({
+            "description": "A data type name, without the angle brackets.",
+            "type": "string",
+            "enum": names,
+        })
+    }
+}
+
+impl JsonSchema for CustomIdent {
+    fn schema_name() -> Cow<'static, str> {
+        "CustomIdent".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        concat!(module_path!(), "::CustomIdent").into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A custom identifier, which can't be a CSS-wide keyword or 'default'.",
+            "type": "string",
+        })
+    }
+}
+
+impl JsonSchema for Multiplier {
+    fn schema_name() -> Cow<'static, str> {
+        "Multiplier".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        concat!(module_path!(), "::Multiplier").into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A multiplier: 'space' for '+', and 'comma' for '#'.",
+            "type": "string",
+            "enum": ["space", "comma"],
+        })
+    }
+}
+
+impl<I: Impl> JsonSchema for ComponentName<I>
+where
+    I::DataType: JsonSchema,
+    I::CustomIdent: JsonSchema,
+{
+    fn schema_name() -> Cow<'static, str> {
+        "ComponentName".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!(
+            "{}::ComponentName<{}, {}>",
+            module_path!(),
+            I::DataType::schema_id(),
+            I::CustomIdent::schema_id(),
+        )
+        .into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let data_type = generator.subschema_for::<I::DataType>();
+        let ident = generator.subschema_for::<I::CustomIdent>();
+        json_schema!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": { "data-type": data_type },
+                    "required": ["data-type"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": { "ident": ident },
+                    "required": ["ident"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": { "unknown-data-type": { "type": "string" } },
+                    "required": ["unknown-data-type"],
+                    "additionalProperties": false,
+                },
+            ],
+        })
+    }
+}
+
+impl<I: Impl> JsonSchema for Component<I>
+where
+    I::DataType: JsonSchema,
+    I::CustomIdent: JsonSchema,
+{
+    fn schema_name() -> Cow<'static, str> {
+        "Component".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("{}::Component<{}>", module_path!(), ComponentName::<I>::schema_id()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let name = generator.subschema_for::<ComponentName<I>>();
+        let multiplier = generator.subschema_for::<Option<Multiplier>>();
+        json_schema!({
+            "type": "object",
+            "properties": {
+                "name": name,
+                "multiplier": multiplier,
+            },
+            "required": ["name"],
+        })
+    }
+}
+
+impl<I: Impl> JsonSchema for Descriptor<I>
+where
+    I::DataType: JsonSchema,
+    I::CustomIdent: JsonSchema,
+{
+    fn schema_name() -> Cow<'static, str> {
+        "Descriptor".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("{}::Descriptor<{}>", module_path!(), Component::<I>::schema_id()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let component = generator.subschema_for::<Component<I>>();
+        json_schema!({
+            "description": "A syntax descriptor, as a list of components. An empty list is the universal descriptor.",
+            "type": "array",
+            "items": component,
+        })
+    }
+}
+
+#[test]
+fn schema_test() {
+    use super::DefaultImpl;
+
+    let schema = schemars::schema_for!(Descriptor<DefaultImpl>);
+    let schema = serde_json::to_value(&schema).unwrap();
+    assert_eq!(schema["type"], "array");
+    assert_eq!(schema["items"]["$ref"], "#/$defs/Component");
+    let defs = &schema["$defs"];
+    assert_eq!(defs["DataType"]["enum"].as_array().unwrap().len(), DataType::ALL.len());
+    assert_eq!(defs["Multiplier"]["enum"][1], "comma");
+    assert_eq!(defs["ComponentName"]["oneOf"].as_array().unwrap().len(), 3);
+}
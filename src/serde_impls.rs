@@ -0,0 +1,176 @@
+//! serde support, behind the `serde` feature.
+//!
+//! Descriptors serialize as a sequence of components (empty for the universal
+//! descriptor), each of them being a `{ "name": ..., "multiplier": ... }`
+//! object. Data types and identifiers serialize as strings. Deserialization
+//! checks the same invariants as the parser.
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    BuildError, Component, ComponentName, CustomIdent, DataType, Descriptor, Impl, Multiplier,
+};
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum NameRepr<D, C> {
+    DataType(D),
+    Ident(C),
+    UnknownDataType(Box<str>),
+}
+
+#[derive(Deserialize, Serialize)]
+struct ComponentRepr<N> {
+    name: N,
+    multiplier: Option<Multiplier>,
+}
+
+impl<I: Impl> Serialize for ComponentName<I>
+where
+    I::DataType: Serialize,
+    I::CustomIdent: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr: NameRepr<&I::DataType, &I::CustomIdent> = match *self {
+            ComponentName::DataType(ref ty) => NameRepr::DataType(ty),
+            ComponentName::Ident(ref ident) => NameRepr::Ident(ident),
+            ComponentName::UnknownDataType(ref name) => NameRepr::UnknownDataType(name.clone()),
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, I: Impl> Deserialize<'de> for ComponentName<I>
+where
+    I::DataType: Deserialize<'de>,
+    I::CustomIdent: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match NameRepr::<I::DataType, I::CustomIdent>::deserialize(deserializer)? {
+            NameRepr::DataType(ty) => ComponentName::DataType(ty),
+            NameRepr::Ident(ident) => ComponentName::Ident(ident),
+            NameRepr::UnknownDataType(name) => ComponentName::UnknownDataType(name),
+        })
+    }
+}
+
+impl<I: Impl> Serialize for Component<I>
+where
+    I::DataType: Serialize,
+    I::CustomIdent: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ComponentRepr {
+            name: &self.name,
+            multiplier: self.multiplier,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, I: Impl> Deserialize<'de> for Component<I>
+where
+    I::DataType: Deserialize<'de>,
+    I::CustomIdent: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ComponentRepr::<ComponentName<I>>::deserialize(deserializer)?;
+        Component::new(repr.name, repr.multiplier).map_err(de::Error::custom)
+    }
+}
+
+impl<I: Impl> Serialize for Descriptor<I>
+where
+    I::DataType: Serialize,
+    I::CustomIdent: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, I: Impl> Deserialize<'de> for Descriptor<I>
+where
+    I::DataType: Deserialize<'de>,
+    I::CustomIdent: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let components = Vec::<Component<I>>::deserialize(deserializer)?;
+        Ok(Descriptor(components))
+    }
+}
+
+impl Serialize for DataType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for DataType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        DataType::from_str(&name)
+            .ok_or_else(|| de::Error::custom(format_args!("unknown data type name '{}'", name)))
+    }
+}
+
+impl Serialize for CustomIdent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomIdent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ident = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        CustomIdent::from_ident(&ident)
+            .ok_or_else(|| de::Error::custom(BuildError::InvalidCustomIdent(ident.into())))
+    }
+}
+
+#[test]
+fn serde_test() {
+    use super::{parse_descriptor, DefaultImpl, PropertyDefinition};
+
+    let descriptor = parse_descriptor("<length># | auto | <transform-list>").unwrap();
+    let json = serde_json::to_string(&descriptor).unwrap();
+    assert_eq!(
+        json,
+        r#"[{"name":{"data-type":"length"},"multiplier":"comma"},{"name":{"ident":"auto"},"multiplier":null},{"name":{"data-type":"transform-list"},"multiplier":null}]"#,
+    );
+    let roundtrip: Descriptor<DefaultImpl> = serde_json::from_str(&json).unwrap();
+    assert_eq!(roundtrip, descriptor);
+
+    // Values and readers can't be borrowed from, unlike strings.
+    let value = serde_json::to_value(&descriptor).unwrap();
+    assert_eq!(serde_json::from_value::<Descriptor<DefaultImpl>>(value).unwrap(), descriptor);
+    let roundtrip: Descriptor<DefaultImpl> = serde_json::from_reader(json.as_bytes()).unwrap();
+    assert_eq!(roundtrip, descriptor);
+
+    let universal: Descriptor<DefaultImpl> = serde_json::from_str("[]").unwrap();
+    assert!(universal.is_universal());
+
+    for invalid in &[
+        r#"[{"name":{"data-type":"transform-list"},"multiplier":"space"}]"#,
+        r#"[{"name":{"data-type":"foo"},"multiplier":null}]"#,
+        r#"[{"name":{"ident":"inherit"},"multiplier":null}]"#,
+    ] {
+        assert!(serde_json::from_str::<Descriptor<DefaultImpl>>(invalid).is_err());
+    }
+
+    let definition: PropertyDefinition =
+        serde_json::from_str(r#"{"name":"--a","inherits":true,"initialValue":"1px"}"#).unwrap();
+    assert_eq!(definition, PropertyDefinition {
+        name: "--a".into(),
+        syntax: "*".into(),
+        inherits: true,
+        initial_value: Some("1px".into()),
+    });
+    let definition = PropertyDefinition { initial_value: None, syntax: "<length>".into(), ..definition };
+    let json = serde_json::to_string(&definition).unwrap();
+    assert_eq!(json, r#"{"name":"--a","syntax":"<length>","inherits":true}"#);
+    assert_eq!(serde_json::from_str::<PropertyDefinition>(&json).unwrap(), definition);
+    assert!(serde_json::from_str::<PropertyDefinition>(r#"{"name":"--a"}"#).is_err());
+}
@@ -0,0 +1,123 @@
+//! A registry of custom properties that can be shared between threads.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use super::registry::{PropertyDefinition, PropertyRegistration, PropertyRegistry, RegistrationError};
+
+/// A `PropertyRegistry` shared between threads, like the style threads of a
+/// parallel style engine.
+///
+/// Readers take a snapshot of the registrations, which they can keep using
+/// without any locking while the registry changes: changes are made to a
+/// copy of the registrations, which replaces them once done, so snapshots
+/// never change. Clones share the same registrations.
+#[derive(Clone, Debug, Default)]
+pub struct SharedPropertyRegistry {
+    current: Arc<RwLock<Arc<PropertyRegistry>>>,
+}
+
+impl SharedPropertyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current registrations.
+    pub fn snapshot(&self) -> Arc<PropertyRegistry> {
+        self.current.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Changes the registrations, so that snapshots have either all or none
+    /// of the changes made by `change`, even if it panics.
+    pub fn update<R>(&self, change: impl FnOnce(&mut PropertyRegistry) -> R) -> R {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        // Change a copy rather than the current registrations in place, so
+        // that they're left alone if `change` panics. The copy keeps calling
+        // the observers, unlike plain clones.
+        let mut registry = current.clone_with_observers();
+        let result = change(&mut registry);
+        *current = Arc::new(registry);
+        result
+    }
+
+    /// Registers a custom property, like `PropertyRegistry::register`.
+    pub fn register(&self, definition: PropertyDefinition) -> Result<(), RegistrationError> {
+        self.update(|registry| registry.register(definition))
+    }
+
+    /// Removes the registration of a custom property, like
+    /// `PropertyRegistry::unregister`.
+    pub fn unregister(&self, name: &str) -> Option<PropertyRegistration> {
+        self.update(|registry| registry.unregister(name))
+    }
+
+    /// Returns the generation of the current registrations.
+    pub fn generation(&self) -> u64 {
+        self.current.read().unwrap_or_else(PoisonError::into_inner).generation()
+    }
+}
+
+impl From<PropertyRegistry> for SharedPropertyRegistry {
+    fn from(registry: PropertyRegistry) -> Self {
+        Self { current: Arc::new(RwLock::new(Arc::new(registry))) }
+    }
+}
+
+#[test]
+fn shared_property_registry_test() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedPropertyRegistry>();
+
+    let definition = |name: &str| PropertyDefinition {
+        name: name.into(),
+        syntax: "<length>".into(),
+        inherits: false,
+        initial_value: Some("0px".into()),
+    };
+    let registry = SharedPropertyRegistry::new();
+    registry.register(definition("--a")).unwrap();
+    let snapshot = registry.snapshot();
+
+    let threads = (0..4).map(|i| {
+        let registry = registry.clone();
+        std::thread::spawn(move || {
+            assert!(registry.snapshot().contains("--a"));
+            registry.register(definition(&format!("--thread-{}", i))).unwrap();
+        })
+    }).collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(registry.snapshot().len(), 5);
+    assert_eq!(registry.generation(), 5);
+    assert_eq!((snapshot.len(), snapshot.generation()), (1, 1));
+
+    let result = registry.update(|registry| {
+        registry.unregister("--thread-0");
+        registry.register(definition("--thread-1"))
+    });
+    assert!(matches!(result, Err(RegistrationError::AlreadyRegistered(..))));
+    assert_eq!(registry.unregister("--a").map(|registration| registration.name), Some("--a".into()));
+    let current = registry.snapshot();
+    assert_eq!((current.len(), current.generation()), (3, 7));
+    assert!(snapshot.contains("--a"));
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        registry.update(|registry| {
+            registry.unregister("--thread-1");
+            panic!("half-way through");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(registry.snapshot().contains("--thread-1"));
+    assert_eq!(registry.generation(), 7);
+
+    let registered = Arc::new(std::sync::Mutex::new(vec![]));
+    let observed = registered.clone();
+    registry.update(|registry| {
+        registry.observe(move |change| observed.lock().unwrap().push(change.name().to_owned()));
+    });
+    let snapshot = registry.snapshot();
+    registry.register(definition("--b")).unwrap();
+    assert_eq!(*registered.lock().unwrap(), ["--b"]);
+    assert!(!snapshot.contains("--b"));
+}
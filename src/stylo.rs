@@ -0,0 +1,85 @@
+//! Mappings to Servo's style system, behind the `stylo` feature.
+//!
+//! These don't depend on stylo itself, which pins its own `cssparser`. They
+//! name the stylo items a registered-property implementation needs for each
+//! data type, so that bridges and build scripts can be generated from
+//! `DataType::ALL` instead of being written by hand.
+
+use super::DataType;
+
+impl DataType {
+    /// The path of the stylo specified value type for this data type.
+    pub fn stylo_specified_value_type(&self) -> &'static str {
+        match *self {
+            DataType::Length => "style::values::specified::Length",
+            DataType::Number => "style::values::specified::Number",
+            DataType::Percentage => "style::values::specified::Percentage",
+            DataType::LengthPercentage => "style::values::specified::LengthPercentage",
+            DataType::Color => "style::values::specified::Color",
+            DataType::Image => "style::values::specified::Image",
+            DataType::Url => "style::values::specified::url::SpecifiedUrl",
+            DataType::Integer => "style::values::specified::Integer",
+            DataType::Angle => "style::values::specified::Angle",
+            DataType::Time => "style::values::specified::Time",
+            DataType::Resolution => "style::values::specified::Resolution",
+            DataType::TransformFunction => "style::values::specified::transform::TransformOperation",
+            DataType::TransformList => "style::values::specified::Transform",
+            DataType::CustomIdent => "style::values::CustomIdent",
+            #[cfg(feature = "experimental-position")]
+            DataType::Position => "style::values::specified::Position",
+        }
+    }
+
+    /// The path of the stylo function that parses a specified value of this
+    /// data type.
+    ///
+    /// This is the type's `style::parser::Parse` implementation, except for
+    /// `<custom-ident>`, whose parser takes the list of excluded identifiers,
+    /// and `<transform-function>`, which stylo only parses as part of a
+    /// transform list.
+    pub fn stylo_parse_fn(&self) -> &'static str {
+        match *self {
+            DataType::CustomIdent => "style::values::CustomIdent::parse",
+            DataType::TransformFunction | DataType::TransformList => {
+                "<style::values::specified::Transform as style::parser::Parse>::parse"
+            }
+            DataType::Length => "<style::values::specified::Length as style::parser::Parse>::parse",
+            DataType::Number => "<style::values::specified::Number as style::parser::Parse>::parse",
+            DataType::Percentage => {
+                "<style::values::specified::Percentage as style::parser::Parse>::parse"
+            }
+            DataType::LengthPercentage => {
+                "<style::values::specified::LengthPercentage as style::parser::Parse>::parse"
+            }
+            DataType::Color => "<style::values::specified::Color as style::parser::Parse>::parse",
+            DataType::Image => "<style::values::specified::Image as style::parser::Parse>::parse",
+            DataType::Url => {
+                "<style::values::specified::url::SpecifiedUrl as style::parser::Parse>::parse"
+            }
+            DataType::Integer => "<style::values::specified::Integer as style::parser::Parse>::parse",
+            DataType::Angle => "<style::values::specified::Angle as style::parser::Parse>::parse",
+            DataType::Time => "<style::values::specified::Time as style::parser::Parse>::parse",
+            DataType::Resolution => {
+                "<style::values::specified::Resolution as style::parser::Parse>::parse"
+            }
+            #[cfg(feature = "experimental-position")]
+            DataType::Position => "<style::values::specified::Position as style::parser::Parse>::parse",
+        }
+    }
+}
+
+#[test]
+fn stylo_test() {
+    assert_eq!(DataType::Length.stylo_specified_value_type(), "style::values::specified::Length");
+    assert_eq!(DataType::TransformList.stylo_parse_fn(), DataType::TransformFunction.stylo_parse_fn());
+    for ty in DataType::ALL {
+        let value_type = ty.stylo_specified_value_type();
+        assert!(value_type.starts_with("style::values::"));
+        if !matches!(*ty, DataType::CustomIdent | DataType::TransformFunction) {
+            assert_eq!(
+                ty.stylo_parse_fn(),
+                format!("<{} as style::parser::Parse>::parse", value_type),
+            );
+        }
+    }
+}
@@ -0,0 +1,495 @@
+//! Detection of `var()` and `env()` references in values, whose matching is
+//! deferred until they're substituted, and their substitution.
+//!
+//! https://drafts.csswg.org/css-variables-2/#using-variables
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use cssparser::{Parser, Token, TokenSerializationType};
+
+use super::matching::{self, Error};
+use super::registry::PropertyRegistry;
+use super::value::{ComputedValue, MatchedValue};
+use super::Descriptor;
+
+/// A value that references custom properties or environment variables, so
+/// that it can only be matched against a syntax descriptor once those are
+/// substituted, at computed-value time.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PendingSubstitution {
+    /// The value, as written.
+    pub source: Box<str>,
+    /// The names of the custom properties referenced with `var()`, including
+    /// the references in fallbacks, in order of appearance and without
+    /// duplicates.
+    pub variables: Vec<Box<str>>,
+    /// The names of the environment variables referenced with `env()`, in
+    /// the same way.
+    pub environment_variables: Vec<Box<str>>,
+}
+
+/// Where the `var()` and `env()` references of a value are recorded while
+/// it's consumed: either their names, or only whether there are any.
+pub(crate) trait References: Default {
+    fn add_variable(&mut self, name: &str);
+
+    fn add_environment_variable(&mut self, name: &str);
+
+    /// Returns whether the value references anything at all.
+    fn has_references(&self) -> bool;
+}
+
+fn push_unique(names: &mut Vec<Box<str>>, name: &str) {
+    if !names.iter().any(|n| **n == *name) {
+        names.push(name.into());
+    }
+}
+
+impl References for PendingSubstitution {
+    fn add_variable(&mut self, name: &str) {
+        push_unique(&mut self.variables, name);
+    }
+
+    fn add_environment_variable(&mut self, name: &str) {
+        push_unique(&mut self.environment_variables, name);
+    }
+
+    fn has_references(&self) -> bool {
+        !self.variables.is_empty() || !self.environment_variables.is_empty()
+    }
+}
+
+impl References for bool {
+    fn add_variable(&mut self, _: &str) {
+        *self = true;
+    }
+
+    fn add_environment_variable(&mut self, _: &str) {
+        *self = true;
+    }
+
+    fn has_references(&self) -> bool {
+        *self
+    }
+}
+
+/// Consumes the arguments of a `var()` or `env()` function, recording what
+/// they reference, or returns `None` if `name` is not one of those.
+pub(crate) fn parse_arguments<'i, 't, R: References>(
+    name: &str,
+    input: &mut Parser<'i, 't>,
+    references: &mut R,
+) -> Option<Result<(), Error<'i>>> {
+    let is_var = name.eq_ignore_ascii_case("var");
+    if !is_var && !name.eq_ignore_ascii_case("env") {
+        return None;
+    }
+    Some((|| {
+        let location = input.current_source_location();
+        let name = input.expect_ident_cloned()?;
+        if is_var {
+            // https://drafts.csswg.org/css-variables-2/#typedef-custom-property-name
+            if !name.starts_with("--") {
+                return Err(location.new_unexpected_token_error(Token::Ident(name)));
+            }
+            references.add_variable(&name);
+        } else {
+            // https://drafts.csswg.org/css-env-1/#env-function
+            while input.try_parse(|input| match input.next() {
+                Ok(&Token::Number { int_value: Some(index), .. }) if index >= 0 => Ok(()),
+                _ => Err(()),
+            }).is_ok() {}
+            references.add_environment_variable(&name);
+        }
+        if input.try_parse(|input| input.expect_comma()).is_ok() {
+            return matching::consume_declaration_value(input, true, references);
+        }
+        input.expect_exhausted()?;
+        Ok(())
+    })())
+}
+
+fn is_reference(function: &str) -> bool {
+    function.eq_ignore_ascii_case("var") || function.eq_ignore_ascii_case("env")
+}
+
+/// Substituted CSS, which keeps track of its last token so that the tokens
+/// appended to it are never parsed back as part of that one.
+struct TokenStream {
+    css: String,
+    last: TokenSerializationType,
+}
+
+impl TokenStream {
+    fn new() -> Self {
+        Self { css: String::new(), last: TokenSerializationType::nothing() }
+    }
+
+    /// Appends `css`, whose first and last tokens are of types `first` and
+    /// `last`, separating it with an empty comment if needed.
+    ///
+    /// https://drafts.csswg.org/css-syntax/#serialization
+    fn push(&mut self, css: &str, first: TokenSerializationType, last: TokenSerializationType) {
+        if css.is_empty() {
+            return;
+        }
+        if self.last.needs_separator_when_before(first) {
+            self.css.push_str("/**/");
+        }
+        self.css.push_str(css);
+        self.last = last;
+    }
+
+    /// Appends the serialization of a value.
+    fn push_value(&mut self, value: &MatchedValue) {
+        let css = value.to_css_string();
+        let mut input = cssparser::ParserInput::new(&css);
+        let mut input = Parser::new(&mut input);
+        let (mut first, mut last) = (TokenSerializationType::nothing(), TokenSerializationType::nothing());
+        while let Ok(token) = input.next_including_whitespace_and_comments() {
+            first.set_if_nothing(token.serialization_type());
+            last = match *token {
+                Token::Function(..) |
+                Token::ParenthesisBlock |
+                Token::SquareBracketBlock |
+                Token::CurlyBracketBlock => Token::CloseParenthesis.serialization_type(),
+                ref token => token.serialization_type(),
+            };
+        }
+        self.push(&css, first, last);
+    }
+}
+
+/// Finds the reference cycles among custom properties, as the strongly
+/// connected components of their dependency graph, with Tarjan's algorithm.
+///
+/// https://drafts.csswg.org/css-variables-2/#cycles
+struct CycleFinder<'a> {
+    /// The properties referenced by each property, including in fallbacks.
+    references: BTreeMap<&'a str, &'a [Box<str>]>,
+    /// The index in visiting order and lowest reachable index of each
+    /// visited property.
+    indices: BTreeMap<&'a str, (usize, usize)>,
+    stack: Vec<&'a str>,
+    on_stack: BTreeSet<&'a str>,
+    cycles: Vec<Vec<Box<str>>>,
+}
+
+impl<'a> CycleFinder<'a> {
+    /// Returns the cycles among the properties of `specified`, each sorted in
+    /// code point order, in code point order of their first property.
+    fn find(specified: &BTreeMap<&'a str, &'a MatchedValue>) -> Vec<Vec<Box<str>>> {
+        let references = specified
+            .iter()
+            .filter_map(|(&name, &value)| match *value {
+                MatchedValue::PendingSubstitution(ref pending) => Some((name, &*pending.variables)),
+                _ => None,
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut finder = CycleFinder {
+            references,
+            indices: BTreeMap::new(),
+            stack: vec![],
+            on_stack: BTreeSet::new(),
+            cycles: vec![],
+        };
+        let names = finder.references.keys().cloned().collect::<Vec<_>>();
+        for name in names {
+            if !finder.indices.contains_key(name) {
+                finder.visit(name);
+            }
+        }
+        finder.cycles.sort();
+        finder.cycles
+    }
+
+    fn visit(&mut self, name: &'a str) {
+        let index = self.indices.len();
+        self.indices.insert(name, (index, index));
+        self.stack.push(name);
+        self.on_stack.insert(name);
+        for reference in self.references[name] {
+            // Properties with no references are never part of a cycle.
+            let reference = match self.references.get_key_value(&**reference) {
+                Some((&reference, _)) => reference,
+                None => continue,
+            };
+            let lowest = match self.indices.get(reference) {
+                None => {
+                    self.visit(reference);
+                    self.indices[reference].1
+                }
+                Some(&(index, _)) if self.on_stack.contains(reference) => index,
+                Some(..) => continue,
+            };
+            let entry = self.indices.get_mut(name).unwrap();
+            entry.1 = entry.1.min(lowest);
+        }
+        if self.indices[name].0 != self.indices[name].1 {
+            return;
+        }
+        let mut component = vec![];
+        loop {
+            let member = self.stack.pop().unwrap();
+            self.on_stack.remove(member);
+            component.push(Box::<str>::from(member));
+            if member == name {
+                break;
+            }
+        }
+        if component.len() > 1 || self.references[name].iter().any(|reference| **reference == *name) {
+            component.sort();
+            self.cycles.push(component);
+        }
+    }
+}
+
+/// The state of the substitution of the values of the custom properties of
+/// an element.
+struct Substitution<'a> {
+    registry: &'a PropertyRegistry,
+    specified: BTreeMap<&'a str, &'a MatchedValue>,
+    computed: BTreeMap<Box<str>, ComputedValue>,
+}
+
+impl<'a> Substitution<'a> {
+    /// Returns the value of the property `name` with its references
+    /// substituted.
+    fn compute(&mut self, name: &str) -> ComputedValue {
+        if let Some(computed) = self.computed.get(name) {
+            return computed.clone();
+        }
+        let pending = match self.specified.get(name).cloned() {
+            Some(MatchedValue::PendingSubstitution(pending)) => pending,
+            Some(value) => return ComputedValue::Value(value.clone()),
+            None => return self.registry.initial_value(name),
+        };
+        let mut input = cssparser::ParserInput::new(&pending.source);
+        let mut input = Parser::new(&mut input);
+        let mut tokens = TokenStream::new();
+        let value = match self.substitute(&mut input, &mut tokens) {
+            Ok(()) => {
+                let universal = Descriptor::universal();
+                let syntax = self.registry.get(name).map_or(&universal, |registration| &registration.syntax);
+                let mut input = cssparser::ParserInput::new(&tokens.css);
+                syntax.parse_value(&mut Parser::new(&mut input)).ok()
+            }
+            Err(..) => None,
+        };
+        let value = match value {
+            Some(value) => ComputedValue::Value(value),
+            None => self.registry.initial_value(name),
+        };
+        self.computed.insert(name.into(), value.clone());
+        value
+    }
+
+    /// Appends `input` to `tokens` with its references substituted, failing
+    /// if one of them is to the guaranteed-invalid value and has no
+    /// fallback.
+    fn substitute<'i, 't>(
+        &mut self,
+        input: &mut Parser<'i, 't>,
+        tokens: &mut TokenStream,
+    ) -> Result<(), Error<'i>> {
+        loop {
+            let start = input.position();
+            let token = match input.next_including_whitespace_and_comments() {
+                Ok(token) => token.clone(),
+                Err(..) => return Ok(()),
+            };
+            let closing = match token {
+                Token::Function(ref name) if is_reference(name) => {
+                    let is_var = name.eq_ignore_ascii_case("var");
+                    input.parse_nested_block(|input| self.substitute_reference(is_var, input, tokens))?;
+                    continue;
+                }
+                Token::Function(..) | Token::ParenthesisBlock => ")",
+                Token::SquareBracketBlock => "]",
+                Token::CurlyBracketBlock => "}",
+                _ => "",
+            };
+            tokens.push(input.slice_from(start), token.serialization_type(), token.serialization_type());
+            if !closing.is_empty() {
+                input.parse_nested_block(|input| self.substitute(input, tokens))?;
+                let closing_type = Token::CloseParenthesis.serialization_type();
+                tokens.push(closing, closing_type, closing_type);
+            }
+        }
+    }
+
+    /// Substitutes the arguments of a `var()` or `env()` function. No
+    /// environment variables are known, so `env()` is always replaced by its
+    /// fallback.
+    fn substitute_reference<'i, 't>(
+        &mut self,
+        is_var: bool,
+        input: &mut Parser<'i, 't>,
+        tokens: &mut TokenStream,
+    ) -> Result<(), Error<'i>> {
+        let name = input.expect_ident_cloned()?;
+        if !is_var {
+            while input.try_parse(|input| input.expect_integer()).is_ok() {}
+        }
+        let value = if is_var { self.compute(&name) } else { ComputedValue::GuaranteedInvalid };
+        if let ComputedValue::Value(ref value) = value {
+            tokens.push_value(value);
+            while input.next_including_whitespace_and_comments().is_ok() {}
+            return Ok(());
+        }
+        input.expect_comma()?;
+        input.skip_whitespace();
+        self.substitute(input, tokens)
+    }
+}
+
+/// The custom properties of an element with their `var()` references
+/// substituted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SubstitutedValues {
+    /// The computed values of all the properties with a specified value.
+    pub values: BTreeMap<Box<str>, ComputedValue>,
+    /// The reference cycles, each as the names of the properties in it in
+    /// code point order, so that they can be reported.
+    pub cycles: Vec<Vec<Box<str>>>,
+}
+
+impl PropertyRegistry {
+    /// Substitutes the `var()` references in the specified values of the
+    /// custom properties of an element, returning their computed values.
+    ///
+    /// References to properties without a specified value are to their
+    /// initial value. The substituted value of a registered property is
+    /// matched against its syntax, and if it doesn't match, or a reference
+    /// is to the guaranteed-invalid value and has no fallback, the property
+    /// is invalid at computed-value time and takes its initial value, which
+    /// for unregistered properties is the guaranteed-invalid value. So do
+    /// the properties in a reference cycle, even through a fallback, without
+    /// substituting anything. Relative values like `1em` are not computed any
+    /// further.
+    ///
+    /// https://drafts.csswg.org/css-variables-2/#substitute-a-var
+    pub fn substitute_variables<'a, I>(&self, specified: I) -> SubstitutedValues
+    where
+        I: IntoIterator<Item = (&'a str, &'a MatchedValue)>,
+    {
+        let specified = specified.into_iter().collect();
+        let cycles = CycleFinder::find(&specified);
+        let mut substitution = Substitution { registry: self, specified, computed: BTreeMap::new() };
+        for name in cycles.iter().flatten() {
+            substitution.computed.insert(name.clone(), self.initial_value(name));
+        }
+        let names = substitution.specified.keys().cloned().collect::<Vec<_>>();
+        let values = names.into_iter().map(|name| (name.into(), substitution.compute(name))).collect();
+        SubstitutedValues { values, cycles }
+    }
+}
+
+#[test]
+fn references_test() {
+    fn parse(input: &str) -> Result<PendingSubstitution, ()> {
+        let mut input = cssparser::ParserInput::new(input);
+        let mut references = PendingSubstitution::default();
+        let result = Parser::new(&mut input).parse_entirely(|input| {
+            matching::consume_declaration_value(input, true, &mut references)
+        });
+        result.map(|()| references).map_err(|_| ())
+    }
+
+    let references = parse("var(--a) calc(var(--b, env(safe-area-inset-top, var(--a))) * 2) env(x 1 2)").unwrap();
+    assert_eq!(references.variables, ["--a".into(), "--b".into()]);
+    assert_eq!(references.environment_variables, ["safe-area-inset-top".into(), "x".into()]);
+    assert_eq!(parse("var(--a,)").unwrap().variables, ["--a".into()]);
+    assert!(!parse("foo(bar)").unwrap().has_references());
+    for invalid in &["var(a)", "var(--a --b)", "var()", "var(--a, ;)", "env(x -1)", "env(1)", "var(--a) ;"] {
+        assert_eq!(parse(invalid), Err(()), "{}", invalid);
+    }
+}
+
+#[test]
+fn substitute_variables_test() {
+    let mut registry = PropertyRegistry::new();
+    for &(name, syntax, initial_value) in &[
+        ("--length", "<length>", Some("3px")),
+        ("--cyclic-length", "<length>", Some("6px")),
+        ("--unset-length", "<length>", Some("4px")),
+        ("--any", "*", None),
+    ] {
+        registry.register(super::PropertyDefinition {
+            name: name.into(),
+            syntax: syntax.into(),
+            inherits: false,
+            initial_value: initial_value.map(Into::into),
+        }).unwrap();
+    }
+    let specify = |values: &[(&'static str, &str)]| {
+        values.iter().map(|&(name, value)| {
+            let syntax = registry.get(name).map_or_else(Descriptor::universal, |r| r.syntax.clone());
+            let mut input = cssparser::ParserInput::new(value);
+            (name, syntax.parse_value(&mut Parser::new(&mut input)).unwrap())
+        }).collect::<Vec<_>>()
+    };
+    let specified = specify(&[
+        ("--a", "1px"),
+        ("--b", "var(--a) 2px"),
+        ("--c", "var(--missing)"),
+        ("--d", "var(--missing,  fallback var(--a))"),
+        ("--e", "var(--a)var(--a)"),
+        ("--f", "var(--number)px"),
+        ("--number", "1"),
+        ("--g", "calc(var(--a) * 2) [var(--a)]"),
+        ("--h", "var(--unset-length) var(--length)"),
+        ("--i", "env(safe-area-inset-top, 5px) env(x 1)"),
+        ("--j", "env(safe-area-inset-top, 5px)"),
+        ("--x", "var(--y)"),
+        ("--y", "var(--x)"),
+        ("--z", "var(--missing, var(--z))"),
+        ("--p", "var(--q) var(--b)"),
+        ("--q", "var(--a, var(--p))"),
+        ("--k", "var(--x, ok)"),
+        ("--cyclic-length", "var(--cyclic-length)"),
+        ("--length", "var(--a, 7px)"),
+        ("--any", "var(--missing)"),
+    ]);
+    let computed = registry.substitute_variables(specified.iter().map(|(name, value)| (*name, value)));
+    let cycles = computed.cycles.iter().map(|cycle| cycle.iter().map(|name| &**name).collect::<Vec<_>>());
+    let expected: [&[&str]; 4] = [&["--cyclic-length"], &["--p", "--q"], &["--x", "--y"], &["--z"]];
+    assert_eq!(cycles.collect::<Vec<_>>(), expected);
+    let computed = computed.values.iter().map(|(name, value)| {
+        (&**name, value.as_value().map(MatchedValue::to_css_string))
+    });
+    let expected = [
+        ("--a", Some("1px")),
+        ("--any", None),
+        ("--b", Some("1px 2px")),
+        ("--c", None),
+        ("--cyclic-length", Some("6px")),
+        ("--d", Some("fallback 1px")),
+        ("--e", Some("1px/**/1px")),
+        ("--f", Some("1/**/px")),
+        ("--g", Some("calc(1px * 2) [1px]")),
+        ("--h", Some("4px 1px")),
+        ("--i", None),
+        ("--j", Some("5px")),
+        ("--k", Some("ok")),
+        ("--length", Some("1px")),
+        ("--number", Some("1")),
+        ("--p", None),
+        ("--q", None),
+        ("--x", None),
+        ("--y", None),
+        ("--z", None),
+    ];
+    let expected = expected.iter().map(|&(name, value)| (name, value.map(Into::into)));
+    assert_eq!(computed.collect::<Vec<_>>(), expected.collect::<Vec<_>>());
+
+    // Invalid substitutions give registered properties their initial value.
+    let specified = specify(&[("--length", "var(--a)"), ("--a", "red")]);
+    let computed = registry.substitute_variables(specified.iter().map(|(name, value)| (*name, value)));
+    assert_eq!(computed.values["--length"].to_css_string(), "3px");
+    assert_eq!(computed.values["--a"].to_css_string(), "red");
+    assert!(computed.cycles.is_empty());
+    let specified = specify(&[("--a", "var(--any)"), ("--any", "var(--missing)")]);
+    let computed = registry.substitute_variables(specified.iter().map(|(name, value)| (*name, value)));
+    assert_eq!(computed.values["--a"], ComputedValue::GuaranteedInvalid);
+}
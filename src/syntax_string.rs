@@ -0,0 +1,85 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use super::{parse_descriptor_with, Descriptor, Impl, ParseError};
+
+/// A syntax descriptor string as written by the author, which is parsed
+/// lazily the first time the descriptor is needed.
+///
+/// This is useful for CSSOM implementations, where
+/// `CSSPropertyRule.syntax` needs to return the original text, while the
+/// parsed form is used internally.
+#[derive(Clone)]
+pub struct SyntaxString<I: Impl> {
+    source: Box<str>,
+    parsed: OnceLock<Result<Descriptor<I>, ParseError>>,
+}
+
+impl<I: Impl> SyntaxString<I> {
+    /// Creates a syntax string from its source, without parsing it.
+    pub fn new(source: impl Into<Box<str>>) -> Self {
+        Self {
+            source: source.into(),
+            parsed: OnceLock::new(),
+        }
+    }
+
+    /// Returns the original text.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the parsed descriptor, parsing it if needed.
+    pub fn descriptor(&self) -> Result<&Descriptor<I>, &ParseError> {
+        self.parsed
+            .get_or_init(|| parse_descriptor_with(&self.source))
+            .as_ref()
+    }
+
+    /// Returns whether the descriptor has already been parsed.
+    #[inline]
+    pub fn is_parsed(&self) -> bool {
+        self.parsed.get().is_some()
+    }
+}
+
+impl<I: Impl> fmt::Debug for SyntaxString<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SyntaxString").field(&self.source).finish()
+    }
+}
+
+impl<I: Impl> fmt::Display for SyntaxString<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Syntax strings compare by their source text.
+impl<I: Impl> PartialEq for SyntaxString<I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl<I: Impl> Eq for SyntaxString<I> {}
+
+#[test]
+fn syntax_string_test() {
+    use super::{DefaultImpl, ParseErrorKind};
+
+    let syntax = SyntaxString::<DefaultImpl>::new(" <length>|auto ");
+    assert!(!syntax.is_parsed());
+    assert_eq!(syntax.as_str(), " <length>|auto ");
+    assert!(*syntax.descriptor().unwrap() == "<length> | auto");
+    assert!(syntax.is_parsed());
+    assert_eq!(syntax.to_string(), " <length>|auto ");
+
+    let invalid = SyntaxString::<DefaultImpl>::new("<foo>");
+    assert_eq!(
+        invalid.descriptor().unwrap_err().kind,
+        ParseErrorKind::UnknownDataTypeName("foo".into()),
+    );
+    assert_eq!(invalid.clone(), invalid);
+}
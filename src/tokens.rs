@@ -0,0 +1,112 @@
+//! `quote::ToTokens` implementations, behind the `codegen` feature.
+//!
+//! These expand to expressions that rebuild the value through the crate's
+//! public API, so build scripts can embed parsed descriptors in generated
+//! code. The generated code refers to the crate as `::css_typed_om_syntax`.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+
+use super::{Component, ComponentName, DataType, DefaultImpl, Descriptor, Multiplier};
+
+impl ToTokens for DataType {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let variant = Ident::new(&format!("{:?}", self), Span::call_site());
+        tokens.extend(quote! { ::css_typed_om_syntax::DataType::#variant });
+    }
+}
+
+impl ToTokens for Multiplier {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match *self {
+            Multiplier::Space => quote! { ::css_typed_om_syntax::Multiplier::Space },
+            Multiplier::Comma => quote! { ::css_typed_om_syntax::Multiplier::Comma },
+        });
+    }
+}
+
+impl ToTokens for ComponentName<DefaultImpl> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match *self {
+            ComponentName::DataType(ty) => quote! {
+                ::css_typed_om_syntax::ComponentName::<::css_typed_om_syntax::DefaultImpl>::DataType(#ty)
+            },
+            ComponentName::Ident(ref ident) => {
+                let ident = ident.as_str();
+                quote! {
+                    ::css_typed_om_syntax::ComponentName::<::css_typed_om_syntax::DefaultImpl>::Ident(
+                        ::css_typed_om_syntax::CustomIdent::from_ident(#ident).unwrap()
+                    )
+                }
+            }
+            ComponentName::UnknownDataType(ref name) => {
+                let name = &**name;
+                quote! {
+                    ::css_typed_om_syntax::ComponentName::<::css_typed_om_syntax::DefaultImpl>::UnknownDataType(
+                        #name.into()
+                    )
+                }
+            }
+        });
+    }
+}
+
+impl ToTokens for Component<DefaultImpl> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = self.name();
+        let multiplier = match self.multiplier() {
+            Some(multiplier) => quote! { Some(#multiplier) },
+            None => quote! { None },
+        };
+        tokens.extend(quote! {
+            ::css_typed_om_syntax::Component::new(#name, #multiplier).unwrap()
+        });
+    }
+}
+
+impl ToTokens for Descriptor<DefaultImpl> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        if self.is_universal() {
+            tokens.extend(quote! {
+                ::css_typed_om_syntax::Descriptor::<::css_typed_om_syntax::DefaultImpl>::universal()
+            });
+            return;
+        }
+        let components = self.iter().map(|component| {
+            let name = component.name();
+            match component.multiplier() {
+                Some(multiplier) => quote! { .component(#name).multiplier(#multiplier) },
+                None => quote! { .component(#name) },
+            }
+        });
+        tokens.extend(quote! {
+            ::css_typed_om_syntax::DescriptorBuilder::<::css_typed_om_syntax::DefaultImpl>::new()
+                #(#components)*
+                .build()
+                .unwrap()
+        });
+    }
+}
+
+#[test]
+fn to_tokens_test() {
+    use super::parse_descriptor;
+
+    let descriptor = parse_descriptor("<length># | auto").unwrap();
+    let expected = quote! {
+        ::css_typed_om_syntax::DescriptorBuilder::<::css_typed_om_syntax::DefaultImpl>::new()
+            .component(::css_typed_om_syntax::ComponentName::<::css_typed_om_syntax::DefaultImpl>::DataType(
+                ::css_typed_om_syntax::DataType::Length
+            ))
+            .multiplier(::css_typed_om_syntax::Multiplier::Comma)
+            .component(::css_typed_om_syntax::ComponentName::<::css_typed_om_syntax::DefaultImpl>::Ident(
+                ::css_typed_om_syntax::CustomIdent::from_ident("auto").unwrap()
+            ))
+            .build()
+            .unwrap()
+    };
+    assert_eq!(descriptor.to_token_stream().to_string(), expected.to_string());
+
+    let universal = Descriptor::<DefaultImpl>::universal().to_token_stream().to_string();
+    assert!(universal.ends_with(":: universal ()"), "{}", universal);
+}
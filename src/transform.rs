@@ -0,0 +1,343 @@
+//! Matching of `<transform-function>` values.
+//!
+//! https://drafts.csswg.org/css-transforms-2/#transform-functions
+
+use std::fmt;
+
+use cssparser::{Parser, Token};
+
+use super::calc::{self, CalcNode};
+use super::matching::{self, Error};
+use super::units::AngleUnit;
+use super::value::{MatchedValue, Output};
+use super::DataType;
+
+/// The name of a transform function.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TransformFunctionName {
+    Matrix,
+    Matrix3d,
+    Perspective,
+    Rotate,
+    Rotate3d,
+    RotateX,
+    RotateY,
+    RotateZ,
+    Scale,
+    Scale3d,
+    ScaleX,
+    ScaleY,
+    ScaleZ,
+    Skew,
+    SkewX,
+    SkewY,
+    Translate,
+    Translate3d,
+    TranslateX,
+    TranslateY,
+    TranslateZ,
+}
+
+/// The type of an argument of a transform function.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Argument {
+    Number,
+    NumberPercentage,
+    /// `[<angle> | <zero>]`.
+    Angle,
+    Length,
+    LengthPercentage,
+    /// `[<length [0,∞]> | none]`, which is the argument of `perspective()`.
+    Perspective,
+}
+
+impl TransformFunctionName {
+    pub const ALL: &'static [TransformFunctionName] = &[
+        TransformFunctionName::Matrix,
+        TransformFunctionName::Matrix3d,
+        TransformFunctionName::Perspective,
+        TransformFunctionName::Rotate,
+        TransformFunctionName::Rotate3d,
+        TransformFunctionName::RotateX,
+        TransformFunctionName::RotateY,
+        TransformFunctionName::RotateZ,
+        TransformFunctionName::Scale,
+        TransformFunctionName::Scale3d,
+        TransformFunctionName::ScaleX,
+        TransformFunctionName::ScaleY,
+        TransformFunctionName::ScaleZ,
+        TransformFunctionName::Skew,
+        TransformFunctionName::SkewX,
+        TransformFunctionName::SkewY,
+        TransformFunctionName::Translate,
+        TransformFunctionName::Translate3d,
+        TransformFunctionName::TranslateX,
+        TransformFunctionName::TranslateY,
+        TransformFunctionName::TranslateZ,
+    ];
+
+    /// Returns the name of the function, as spelled in the specifications.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            TransformFunctionName::Matrix => "matrix",
+            TransformFunctionName::Matrix3d => "matrix3d",
+            TransformFunctionName::Perspective => "perspective",
+            TransformFunctionName::Rotate => "rotate",
+            TransformFunctionName::Rotate3d => "rotate3d",
+            TransformFunctionName::RotateX => "rotateX",
+            TransformFunctionName::RotateY => "rotateY",
+            TransformFunctionName::RotateZ => "rotateZ",
+            TransformFunctionName::Scale => "scale",
+            TransformFunctionName::Scale3d => "scale3d",
+            TransformFunctionName::ScaleX => "scaleX",
+            TransformFunctionName::ScaleY => "scaleY",
+            TransformFunctionName::ScaleZ => "scaleZ",
+            TransformFunctionName::Skew => "skew",
+            TransformFunctionName::SkewX => "skewX",
+            TransformFunctionName::SkewY => "skewY",
+            TransformFunctionName::Translate => "translate",
+            TransformFunctionName::Translate3d => "translate3d",
+            TransformFunctionName::TranslateX => "translateX",
+            TransformFunctionName::TranslateY => "translateY",
+            TransformFunctionName::TranslateZ => "translateZ",
+        }
+    }
+
+    /// Returns the transform function with the given name, ASCII
+    /// case-insensitively.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|f| name.eq_ignore_ascii_case(f.as_str())).cloned()
+    }
+
+    /// Returns the types of the arguments of the function, and how many of
+    /// them are required.
+    fn arguments(&self) -> (&'static [Argument], usize) {
+        use self::Argument::*;
+        match *self {
+            TransformFunctionName::Matrix => (&[Number; 6], 6),
+            TransformFunctionName::Matrix3d => (&[Number; 16], 16),
+            TransformFunctionName::Perspective => (&[Perspective], 1),
+            TransformFunctionName::Rotate |
+            TransformFunctionName::RotateX |
+            TransformFunctionName::RotateY |
+            TransformFunctionName::RotateZ |
+            TransformFunctionName::SkewX |
+            TransformFunctionName::SkewY => (&[Angle], 1),
+            TransformFunctionName::Rotate3d => (&[Number, Number, Number, Angle], 4),
+            TransformFunctionName::Scale => (&[NumberPercentage, NumberPercentage], 1),
+            TransformFunctionName::Scale3d => (&[NumberPercentage; 3], 3),
+            TransformFunctionName::ScaleX |
+            TransformFunctionName::ScaleY |
+            TransformFunctionName::ScaleZ => (&[NumberPercentage], 1),
+            TransformFunctionName::Skew => (&[Angle, Angle], 1),
+            TransformFunctionName::Translate => (&[LengthPercentage, LengthPercentage], 1),
+            TransformFunctionName::Translate3d => (&[LengthPercentage, LengthPercentage, Length], 3),
+            TransformFunctionName::TranslateX | TransformFunctionName::TranslateY => (&[LengthPercentage], 1),
+            TransformFunctionName::TranslateZ => (&[Length], 1),
+        }
+    }
+}
+
+impl fmt::Display for TransformFunctionName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A matched `<transform-function>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformFunction {
+    pub name: TransformFunctionName,
+    /// The arguments of the function, as many as were given. Unitless zero
+    /// angles are matched as `0deg`, and `perspective(none)` has no
+    /// arguments.
+    pub arguments: Vec<MatchedValue>,
+}
+
+impl TransformFunction {
+    /// Returns whether any of the arguments is or contains a percentage.
+    pub fn has_percentage(&self) -> bool {
+        self.arguments.iter().any(MatchedValue::has_percentage)
+    }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_str(self.name.as_str())?;
+        dest.write_char('(')?;
+        if self.name == TransformFunctionName::Perspective && self.arguments.is_empty() {
+            dest.write_str("none")?;
+        }
+        for (i, argument) in self.arguments.iter().enumerate() {
+            if i > 0 {
+                dest.write_str(", ")?;
+            }
+            argument.to_css(dest)?;
+        }
+        dest.write_char(')')
+    }
+}
+
+impl cssparser::ToCss for TransformFunction {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        TransformFunction::to_css(self, dest)
+    }
+}
+
+/// Consumes a single argument of a transform function, returning `None` if
+/// it's a keyword with no value.
+fn parse_argument<'i, 't, O: Output>(argument: Argument, input: &mut Parser<'i, 't>) -> Result<Option<O>, Error<'i>> {
+    let ty = match argument {
+        Argument::Number => DataType::Number,
+        Argument::NumberPercentage => {
+            if let Ok(number) = input.try_parse(|input| matching::parse_data_type(DataType::Number, input)) {
+                return Ok(Some(number));
+            }
+            DataType::Percentage
+        }
+        Argument::Angle => {
+            let zero = input.try_parse(|input| match input.next() {
+                Ok(&Token::Number { value: 0., .. }) => Ok(()),
+                _ => Err(()),
+            });
+            if zero.is_ok() {
+                return Ok(Some(O::value(|| MatchedValue::Angle { value: 0., unit: AngleUnit::Deg })));
+            }
+            DataType::Angle
+        }
+        Argument::Length => DataType::Length,
+        Argument::LengthPercentage => DataType::LengthPercentage,
+        Argument::Perspective => {
+            if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+                return Ok(None);
+            }
+            // Math functions are range checked too if they can be simplified
+            // already.
+            let state = input.state();
+            let negative = match input.next().cloned() {
+                Ok(Token::Dimension { value, .. }) => value < 0.,
+                Ok(Token::Function(ref name)) if calc::is_math_function(name) => {
+                    let node = CalcNode::parse_function(name, input).ok();
+                    node.and_then(|node| node.to_canonical()).is_some_and(|value| value < 0.)
+                }
+                _ => false,
+            };
+            input.reset(&state);
+            if negative {
+                return Err(input.new_error_for_next_token());
+            }
+            DataType::Length
+        }
+    };
+    matching::parse_data_type(ty, input).map(Some)
+}
+
+/// Consumes a `<transform-function>`.
+pub(crate) fn parse_transform_function<'i, 't, O: Output>(input: &mut Parser<'i, 't>) -> Result<O, Error<'i>> {
+    let location = input.current_source_location();
+    let token = input.next()?.clone();
+    let name = match token {
+        Token::Function(ref name) => TransformFunctionName::from_str(name),
+        _ => None,
+    };
+    let name = match name {
+        Some(name) => name,
+        None => return Err(location.new_unexpected_token_error(token)),
+    };
+    let arguments = input.parse_nested_block(|input| {
+        let (types, required) = name.arguments();
+        let mut arguments = Vec::with_capacity(types.len());
+        for (i, &ty) in types.iter().enumerate() {
+            if i > 0 {
+                if i >= required && input.is_exhausted() {
+                    break;
+                }
+                input.expect_comma()?;
+            }
+            arguments.extend(parse_argument::<O>(ty, input)?);
+        }
+        Ok(arguments)
+    })?;
+    Ok(O::list(arguments, |arguments| {
+        MatchedValue::TransformFunction(TransformFunction { name, arguments })
+    }))
+}
+
+#[test]
+fn transform_test() {
+    use super::units::LengthUnit;
+
+    fn parse(input: &str) -> Result<TransformFunction, ()> {
+        let mut input = cssparser::ParserInput::new(input);
+        let result = Parser::new(&mut input).parse_entirely(parse_transform_function::<MatchedValue>);
+        match result {
+            Ok(MatchedValue::TransformFunction(function)) => Ok(function),
+            _ => Err(()),
+        }
+    }
+
+    assert_eq!(TransformFunctionName::from_str("TRANSLATEX"), Some(TransformFunctionName::TranslateX));
+    assert_eq!(TransformFunctionName::Matrix3d.to_string(), "matrix3d");
+    assert_eq!(
+        parse("translate(10px, 50%)"),
+        Ok(TransformFunction {
+            name: TransformFunctionName::Translate,
+            arguments: vec![
+                MatchedValue::Length { value: 10., unit: LengthUnit::Px },
+                MatchedValue::Percentage(0.5),
+            ],
+        }),
+    );
+    assert_eq!(
+        parse("rotate(0)"),
+        Ok(TransformFunction {
+            name: TransformFunctionName::Rotate,
+            arguments: vec![MatchedValue::Angle { value: 0., unit: AngleUnit::Deg }],
+        }),
+    );
+    assert_eq!(
+        parse("scaleY(150%)"),
+        Ok(TransformFunction {
+            name: TransformFunctionName::ScaleY,
+            arguments: vec![MatchedValue::Percentage(1.5)],
+        }),
+    );
+    assert_eq!(
+        parse("perspective(none)"),
+        Ok(TransformFunction { name: TransformFunctionName::Perspective, arguments: vec![] }),
+    );
+    for valid in &[
+        "matrix(1, 0, 0, 1, 0, 0)",
+        "matrix3d(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1)",
+        "perspective(100px)",
+        "perspective(calc(1px - 1em))",
+        "rotate3d(1, 0, 0, 0.5turn)",
+        "rotateZ(calc(45deg * 2))",
+        "scale(2, 50%)",
+        "scale3d(1, 2, 3)",
+        "skew(10deg)",
+        "skew(10deg, 0)",
+        "translate3d(1px, 2%, 3em)",
+        "translateY(calc(50% - 1em))",
+    ] {
+        assert!(parse(valid).is_ok(), "{}", valid);
+    }
+    for invalid in &[
+        "matrix(1, 0, 0, 1, 0)",
+        "perspective(-1px)",
+        "perspective(calc(-1px))",
+        "perspective(max(-1in, -2cm))",
+        "perspective(10%)",
+        "rotate(45)",
+        "rotate(1deg, 2deg)",
+        "scale()",
+        "scale(1 2)",
+        "skewX(1px)",
+        "translate(1px,)",
+        "translate3d(1px, 2px, 3%)",
+        "translateZ(0%)",
+        "foo(1px)",
+        "translate",
+    ] {
+        assert_eq!(parse(invalid), Err(()), "{}", invalid);
+    }
+}
@@ -0,0 +1,365 @@
+//! Color values.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#colorvalue-objects
+
+// The conversion matrices are kept exactly as in the specification.
+#![allow(clippy::excessive_precision)]
+
+use std::fmt;
+
+use crate::color::{AbsoluteColor, Color, ColorSpace};
+
+type Matrix3 = [[f64; 3]; 3];
+
+fn multiply(matrix: &Matrix3, [x, y, z]: [f64; 3]) -> [f64; 3] {
+    let row = |r: &[f64; 3]| r[0] * x + r[1] * y + r[2] * z;
+    [row(&matrix[0]), row(&matrix[1]), row(&matrix[2])]
+}
+
+// The conversion matrices and transfer functions are those of the sample
+// code of the specification.
+//
+// https://drafts.csswg.org/css-color-4/#color-conversion-code
+
+const SRGB_TO_XYZ: Matrix3 = [
+    [0.41239079926595934, 0.357584339383878, 0.1804807884018343],
+    [0.21263900587151027, 0.715168678767756, 0.07219231536073371],
+    [0.01933081871559182, 0.11919477979462598, 0.9505321522496607],
+];
+const XYZ_TO_SRGB: Matrix3 = [
+    [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+    [-0.9692436362808796, 1.8759675015077202, 0.04155505740717559],
+    [0.05563007969699366, -0.20397695888897652, 1.0569715142428786],
+];
+const DISPLAY_P3_TO_XYZ: Matrix3 = [
+    [0.4865709486482162, 0.26566769316909306, 0.1982172852343625],
+    [0.2289745640697488, 0.6917385218365064, 0.079286914093745],
+    [0., 0.04511338185890264, 1.043944368900976],
+];
+const XYZ_TO_DISPLAY_P3: Matrix3 = [
+    [2.493496911941425, -0.9313836179191239, -0.40271078445071684],
+    [-0.8294889695615747, 1.7626640603183463, 0.023624685841943577],
+    [0.03584583024378447, -0.07617238926804182, 0.9568845240076872],
+];
+const A98_RGB_TO_XYZ: Matrix3 = [
+    [0.5766690429101305, 0.1855582379065463, 0.1882286462349947],
+    [0.29734497525053605, 0.6273635662554661, 0.07529145849399788],
+    [0.02703136138641234, 0.07068885253582723, 0.9913375368376388],
+];
+const XYZ_TO_A98_RGB: Matrix3 = [
+    [2.0415879038107465, -0.5650069742788596, -0.34473135077832956],
+    [-0.9692436362808795, 1.8759675015077202, 0.04155505740717557],
+    [0.013444280632031142, -0.11836239223101838, 1.0151749943912054],
+];
+/// From linear ProPhoto RGB to XYZ relative to D50.
+const PROPHOTO_RGB_TO_XYZ: Matrix3 = [
+    [0.7977604896723027, 0.13518583717574031, 0.0313493495815248],
+    [0.2880711282292934, 0.7118432178101014, 0.00008565396060525902],
+    [0., 0., 0.8251046025104601],
+];
+const XYZ_TO_PROPHOTO_RGB: Matrix3 = [
+    [1.3457989731028281, -0.25558010007997534, -0.05110628506753401],
+    [-0.5446224939028347, 1.5082327413132781, 0.02053603239147973],
+    [0., 0., 1.2119675456389454],
+];
+const REC2020_TO_XYZ: Matrix3 = [
+    [0.6369580483012914, 0.14461690358620832, 0.1688809751641721],
+    [0.2627002120112671, 0.6779980715188708, 0.05930171646986196],
+    [0., 0.028072693049087428, 1.060985057710791],
+];
+const XYZ_TO_REC2020: Matrix3 = [
+    [1.716651187971268, -0.355670783776392, -0.253366281373660],
+    [-0.666684351832489, 1.616481236634939, 0.0157685458139111],
+    [0.017639857445311, -0.042770613257809, 0.942103121235474],
+];
+/// The Bradford chromatic adaptation from D65 to D50.
+const D65_TO_D50: Matrix3 = [
+    [1.0479297925449969, 0.022946870601609652, -0.05019226628920524],
+    [0.02962780877005599, 0.9904344267538799, -0.017073799063418826],
+    [-0.009243040646204504, 0.015055191490298152, 0.7518742814281371],
+];
+const D50_TO_D65: Matrix3 = [
+    [0.955473421488075, -0.02309845494876471, 0.06325924320057072],
+    [-0.0283697093338637, 1.0099953980813041, 0.021041441191917323],
+    [0.012314014864481998, -0.020507649298898964, 1.330365926242124],
+];
+const XYZ_TO_OKLAB_LMS: Matrix3 = [
+    [0.8190224379967030, 0.3619062600528904, -0.1288737815209879],
+    [0.0329836539323885, 0.9292868615863434, 0.0361446663506424],
+    [0.0481771893596242, 0.2642395317527308, 0.6335478284694309],
+];
+const OKLAB_LMS_TO_XYZ: Matrix3 = [
+    [1.2268798758459243, -0.5578149944602171, 0.2813910456659647],
+    [-0.0405757452148008, 1.1122868032803170, -0.0717110580655164],
+    [-0.0763729366746601, -0.4214933324022432, 1.5869240198367816],
+];
+const OKLAB_LMS_TO_OKLAB: Matrix3 = [
+    [0.2104542683093140, 0.7936177747023054, -0.0040720430116193],
+    [1.9779985324311684, -2.4285922420485799, 0.4505937096174110],
+    [0.0259040424655478, 0.7827717124575296, -0.8086757549230774],
+];
+const OKLAB_TO_OKLAB_LMS: Matrix3 = [
+    [1., 0.3963377773761749, 0.2158037573099136],
+    [1., -0.1055613458156586, -0.0638541728258133],
+    [1., -0.0894841775298119, -1.2914855480194092],
+];
+
+/// The D50 white point, which CIE Lab is relative to.
+const D50_WHITE: [f64; 3] = [0.3457 / 0.3585, 1., (1. - 0.3457 - 0.3585) / 0.3585];
+
+/// Applies `f` to the magnitude of each channel, keeping its sign, as the
+/// transfer functions extend to negative values that way.
+fn map_signed(channels: [f64; 3], f: impl Fn(f64) -> f64) -> [f64; 3] {
+    channels.map(|c| c.signum() * f(c.abs()))
+}
+
+fn srgb_to_linear(channels: [f64; 3]) -> [f64; 3] {
+    map_signed(channels, |c| if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) })
+}
+
+fn srgb_from_linear(channels: [f64; 3]) -> [f64; 3] {
+    map_signed(channels, |c| if c > 0.0031308 { 1.055 * c.powf(1. / 2.4) - 0.055 } else { 12.92 * c })
+}
+
+const REC2020_ALPHA: f64 = 1.09929682680944;
+const REC2020_BETA: f64 = 0.018053968510807;
+
+/// Converts the channels of a color in `space` to CIE XYZ relative to D65.
+/// The channels are in the order and units of the syntax of the space, with
+/// percentages resolved and hues in degrees.
+fn to_xyz_d65(space: ColorSpace, channels: [f64; 3]) -> [f64; 3] {
+    match space {
+        ColorSpace::Srgb => multiply(&SRGB_TO_XYZ, srgb_to_linear(channels)),
+        ColorSpace::SrgbLinear => multiply(&SRGB_TO_XYZ, channels),
+        ColorSpace::DisplayP3 => multiply(&DISPLAY_P3_TO_XYZ, srgb_to_linear(channels)),
+        ColorSpace::A98Rgb => multiply(&A98_RGB_TO_XYZ, map_signed(channels, |c| c.powf(563. / 256.))),
+        ColorSpace::ProphotoRgb => {
+            let linear = map_signed(channels, |c| if c <= 16. / 512. { c / 16. } else { c.powf(1.8) });
+            multiply(&D50_TO_D65, multiply(&PROPHOTO_RGB_TO_XYZ, linear))
+        }
+        ColorSpace::Rec2020 => {
+            let linear = map_signed(channels, |c| {
+                if c < REC2020_BETA * 4.5 {
+                    c / 4.5
+                } else {
+                    ((c + REC2020_ALPHA - 1.) / REC2020_ALPHA).powf(1. / 0.45)
+                }
+            });
+            multiply(&REC2020_TO_XYZ, linear)
+        }
+        ColorSpace::XyzD50 => multiply(&D50_TO_D65, channels),
+        ColorSpace::XyzD65 => channels,
+        ColorSpace::Lab => multiply(&D50_TO_D65, lab_to_xyz_d50(channels)),
+        ColorSpace::Lch => to_xyz_d65(ColorSpace::Lab, lch_to_lab(channels)),
+        ColorSpace::Oklab => {
+            let lms = multiply(&OKLAB_TO_OKLAB_LMS, channels).map(|c| c * c * c);
+            multiply(&OKLAB_LMS_TO_XYZ, lms)
+        }
+        ColorSpace::Oklch => to_xyz_d65(ColorSpace::Oklab, lch_to_lab(channels)),
+    }
+}
+
+fn from_xyz_d65(space: ColorSpace, xyz: [f64; 3]) -> [f64; 3] {
+    match space {
+        ColorSpace::Srgb => srgb_from_linear(multiply(&XYZ_TO_SRGB, xyz)),
+        ColorSpace::SrgbLinear => multiply(&XYZ_TO_SRGB, xyz),
+        ColorSpace::DisplayP3 => srgb_from_linear(multiply(&XYZ_TO_DISPLAY_P3, xyz)),
+        ColorSpace::A98Rgb => map_signed(multiply(&XYZ_TO_A98_RGB, xyz), |c| c.powf(256. / 563.)),
+        ColorSpace::ProphotoRgb => {
+            let linear = multiply(&XYZ_TO_PROPHOTO_RGB, multiply(&D65_TO_D50, xyz));
+            map_signed(linear, |c| if c >= 1. / 512. { c.powf(1. / 1.8) } else { 16. * c })
+        }
+        ColorSpace::Rec2020 => map_signed(multiply(&XYZ_TO_REC2020, xyz), |c| {
+            if c > REC2020_BETA {
+                REC2020_ALPHA * c.powf(0.45) - (REC2020_ALPHA - 1.)
+            } else {
+                4.5 * c
+            }
+        }),
+        ColorSpace::XyzD50 => multiply(&D65_TO_D50, xyz),
+        ColorSpace::XyzD65 => xyz,
+        ColorSpace::Lab => xyz_d50_to_lab(multiply(&D65_TO_D50, xyz)),
+        ColorSpace::Lch => lab_to_lch(from_xyz_d65(ColorSpace::Lab, xyz)),
+        ColorSpace::Oklab => {
+            let lms = multiply(&XYZ_TO_OKLAB_LMS, xyz).map(f64::cbrt);
+            multiply(&OKLAB_LMS_TO_OKLAB, lms)
+        }
+        ColorSpace::Oklch => lab_to_lch(from_xyz_d65(ColorSpace::Oklab, xyz)),
+    }
+}
+
+const LAB_EPSILON: f64 = 216. / 24389.;
+const LAB_KAPPA: f64 = 24389. / 27.;
+
+fn xyz_d50_to_lab(xyz: [f64; 3]) -> [f64; 3] {
+    let mut f = [0.; 3];
+    for i in 0..3 {
+        let value = xyz[i] / D50_WHITE[i];
+        f[i] = if value > LAB_EPSILON { value.cbrt() } else { (LAB_KAPPA * value + 16.) / 116. };
+    }
+    [116. * f[1] - 16., 500. * (f[0] - f[1]), 200. * (f[1] - f[2])]
+}
+
+fn lab_to_xyz_d50([l, a, b]: [f64; 3]) -> [f64; 3] {
+    let f1 = (l + 16.) / 116.;
+    let f0 = a / 500. + f1;
+    let f2 = f1 - b / 200.;
+    let component = |f: f64| if f * f * f > LAB_EPSILON { f * f * f } else { (116. * f - 16.) / LAB_KAPPA };
+    let y = if l > LAB_KAPPA * LAB_EPSILON { f1 * f1 * f1 } else { l / LAB_KAPPA };
+    [component(f0) * D50_WHITE[0], y * D50_WHITE[1], component(f2) * D50_WHITE[2]]
+}
+
+fn lab_to_lch([l, a, b]: [f64; 3]) -> [f64; 3] {
+    [l, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.)]
+}
+
+fn lch_to_lab([l, c, h]: [f64; 3]) -> [f64; 3] {
+    let h = h.to_radians();
+    [l, c * h.cos(), c * h.sin()]
+}
+
+/// A `<color>` other than `currentcolor`, which is reified as a keyword,
+/// with its channels in the color space it was specified in.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#csscolorvalue
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorValue {
+    pub space: ColorSpace,
+    /// The channels, in the order and units of the syntax of the color
+    /// space: between 0 and 1 for RGB spaces, and hues in degrees. `none`
+    /// channels are zero.
+    pub channels: [f64; 3],
+    /// The alpha channel, between 0 and 1.
+    pub alpha: f64,
+    /// Whether the color was specified with a legacy sRGB syntax, like a
+    /// named color or `rgb()`, and so is serialized with `rgb()`.
+    pub legacy: bool,
+}
+
+impl ColorValue {
+    /// Returns the value of a matched color, or `None` for `currentcolor`.
+    pub fn from_color(color: &Color) -> Option<Self> {
+        Some(match *color {
+            Color::CurrentColor => return None,
+            Color::Rgba(rgba) => Self {
+                space: ColorSpace::Srgb,
+                channels: [rgba.red_f32().into(), rgba.green_f32().into(), rgba.blue_f32().into()],
+                alpha: rgba.alpha_f32().into(),
+                legacy: true,
+            },
+            Color::Absolute(ref color) => Self {
+                space: color.space,
+                channels: color.components.map(f64::from),
+                alpha: color.alpha.into(),
+                legacy: false,
+            },
+        })
+    }
+
+    /// Returns this color converted to `space`, through CIE XYZ. The result
+    /// may be outside the gamut of `space`.
+    ///
+    /// https://drafts.csswg.org/css-color-4/#color-conversion
+    pub fn to(&self, space: ColorSpace) -> Self {
+        if space == self.space {
+            return *self;
+        }
+        let channels = from_xyz_d65(space, to_xyz_d65(self.space, self.channels));
+        Self { space, channels, alpha: self.alpha, legacy: false }
+    }
+
+    /// Returns the distance between this color and `other` in Oklab, which
+    /// is perceptually uniform, ignoring alpha. Colors are indistinguishable
+    /// below about `0.02`.
+    ///
+    /// https://drafts.csswg.org/css-color-4/#color-difference-OK
+    pub fn difference(&self, other: &ColorValue) -> f64 {
+        let [l1, a1, b1] = self.to(ColorSpace::Oklab).channels;
+        let [l2, a2, b2] = other.to(ColorSpace::Oklab).channels;
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    /// Returns the matched color equivalent to this value.
+    pub fn to_color(&self) -> Color {
+        if self.legacy && self.space == ColorSpace::Srgb {
+            let [r, g, b] = self.channels.map(|c| c as f32);
+            return Color::Rgba(cssparser::RGBA::from_floats(r, g, b, self.alpha as f32));
+        }
+        Color::Absolute(AbsoluteColor {
+            space: self.space,
+            components: self.channels.map(|c| c as f32),
+            alpha: self.alpha as f32,
+        })
+    }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        self.to_color().to_css(dest)
+    }
+}
+
+#[test]
+fn color_value_test() {
+    fn parse(value: &str) -> ColorValue {
+        let mut input = cssparser::ParserInput::new(value);
+        let color = crate::color::parse_color(&mut cssparser::Parser::new(&mut input)).unwrap();
+        ColorValue::from_color(&color).unwrap()
+    }
+
+    fn assert_close(actual: [f64; 3], expected: [f64; 3], tolerance: f64) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < tolerance, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    assert_eq!(ColorValue::from_color(&Color::CurrentColor), None);
+    let red = parse("red");
+    assert_eq!((red.space, red.channels, red.alpha, red.legacy), (ColorSpace::Srgb, [1., 0., 0.], 1., true));
+    assert_eq!(parse("lab(50 -20 none / 0.5)").channels, [50., -20., 0.]);
+
+    assert_close(red.to(ColorSpace::Oklab).channels, [0.627955, 0.224863, 0.125846], 1e-4);
+    assert_close(red.to(ColorSpace::Lab).channels, [54.29, 80.8, 69.89], 0.05);
+    assert_close(red.to(ColorSpace::Lch).channels, [54.29, 106.84, 40.86], 0.05);
+    assert_close(parse("white").to(ColorSpace::Lab).channels, [100., 0., 0.], 1e-3);
+    assert_close(parse("white").to(ColorSpace::XyzD65).channels, [0.9505, 1., 1.089], 1e-3);
+    assert_close(parse("color(srgb-linear 0.5 0.5 0.5)").to(ColorSpace::Srgb).channels, [0.7354; 3], 1e-4);
+
+    let spaces = [
+        ColorSpace::Lab,
+        ColorSpace::Lch,
+        ColorSpace::Oklab,
+        ColorSpace::Oklch,
+        ColorSpace::Srgb,
+        ColorSpace::SrgbLinear,
+        ColorSpace::DisplayP3,
+        ColorSpace::A98Rgb,
+        ColorSpace::ProphotoRgb,
+        ColorSpace::Rec2020,
+        ColorSpace::XyzD50,
+        ColorSpace::XyzD65,
+    ];
+    let color = parse("color(srgb 0.2 0.6 0.9 / 0.5)");
+    for &space in &spaces {
+        let converted = color.to(space);
+        assert_eq!(converted.alpha, 0.5);
+        assert_close(converted.to(ColorSpace::Srgb).channels, color.channels, 1e-6);
+        assert!(converted.difference(&color) < 1e-6, "{:?}", space);
+    }
+
+    assert!(parse("red").difference(&parse("rgb(254 0 0)")) < 0.02);
+    assert!(parse("red").difference(&parse("blue")) > 0.5);
+
+    for &(value, serialization) in &[
+        ("red", "rgb(255, 0, 0)"),
+        ("rgb(0 0 255 / 50%)", "rgba(0, 0, 255, 0.5)"),
+        ("lab(50 -20 10)", "lab(50 -20 10)"),
+        ("color(display-p3 1 0.5 0)", "color(display-p3 1 0.5 0)"),
+    ] {
+        let mut s = String::new();
+        parse(value).to_css(&mut s).unwrap();
+        assert_eq!(s, serialization);
+    }
+    assert_eq!(parse("oklch(0.5 0.2 180)").to_color(), {
+        let mut input = cssparser::ParserInput::new("oklch(0.5 0.2 180)");
+        crate::color::parse_color(&mut cssparser::Parser::new(&mut input)).unwrap()
+    });
+}
@@ -0,0 +1,302 @@
+//! Conversion of numeric values between units.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#numeric-value
+
+use super::numeric::{MathValue, NumericError, NumericValue, UnitValue};
+use super::numeric_type::NumericType;
+use super::unit::CssUnit;
+use crate::compute::{length_to_px, ComputeContext};
+use crate::units::LengthUnit;
+
+/// The units of an item of a sum value, with their exponents, sorted by
+/// unit and without zero exponents. Empty for plain numbers.
+type UnitMap = Vec<(CssUnit, i32)>;
+
+/// An item of a sum value.
+#[derive(Clone, Debug, PartialEq)]
+struct SumItem {
+    value: f64,
+    units: UnitMap,
+}
+
+impl SumItem {
+    /// Returns the item as a unit value, if it has at most one unit with an
+    /// exponent of one.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#create-a-cssunitvalue-from-a-sum-value-item
+    fn to_unit_value(&self) -> Option<UnitValue> {
+        match *self.units {
+            [] => Some(UnitValue::new(self.value, CssUnit::Number)),
+            [(unit, 1)] => Some(UnitValue::new(self.value, unit)),
+            _ => None,
+        }
+    }
+
+    fn numeric_type(&self) -> Option<NumericType> {
+        let mut ty = NumericType::default();
+        for &(unit, exponent) in &self.units {
+            let mut unit_type = NumericType::of_unit(unit);
+            if exponent < 0 {
+                unit_type = unit_type.invert();
+            }
+            for _ in 0..exponent.abs() {
+                ty = ty.multiply(unit_type)?;
+            }
+        }
+        Some(ty)
+    }
+}
+
+/// Returns the product of two unit maps.
+fn multiply_unit_maps(a: &UnitMap, b: &UnitMap) -> UnitMap {
+    let mut units = a.clone();
+    for &(unit, exponent) in b {
+        match units.iter_mut().find(|(u, _)| *u == unit) {
+            Some(entry) => entry.1 += exponent,
+            None => units.push((unit, exponent)),
+        }
+    }
+    units.retain(|&(_, exponent)| exponent != 0);
+    units.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+    units
+}
+
+/// Returns a single-item sum value, if `values` all are, and have the same
+/// units, with the result of `combine` on their values.
+fn combine_single_items(values: &[NumericValue], combine: impl Fn(&[f64]) -> f64) -> Option<Vec<SumItem>> {
+    let mut items = Vec::with_capacity(values.len());
+    for value in values {
+        let mut sum = sum_value(value)?;
+        if sum.len() != 1 {
+            return None;
+        }
+        items.push(sum.pop().unwrap());
+    }
+    if items.iter().any(|item| item.units != items[0].units) {
+        return None;
+    }
+    let values = items.iter().map(|item| item.value).collect::<Vec<_>>();
+    Some(vec![SumItem { value: combine(&values), units: items.swap_remove(0).units }])
+}
+
+/// Returns `value` as a sum of terms with units in their canonical unit
+/// where possible, or `None` if it can't be represented as one, like
+/// `min(1px, 1em)`.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#create-a-sum-value
+fn sum_value(value: &NumericValue) -> Option<Vec<SumItem>> {
+    let math = match *value {
+        NumericValue::Unit(ref value) => {
+            let (value, unit) = match value.unit.to_canonical() {
+                Some(ratio) => (value.value * ratio, value.unit.canonical()),
+                None => (value.value, value.unit),
+            };
+            let units = if unit == CssUnit::Number { vec![] } else { vec![(unit, 1)] };
+            return Some(vec![SumItem { value, units }]);
+        }
+        NumericValue::Math(ref math) => math,
+    };
+    Some(match *math {
+        MathValue::Sum(ref values) => {
+            let mut items: Vec<SumItem> = vec![];
+            for value in values {
+                for item in sum_value(value)? {
+                    match items.iter_mut().find(|i| i.units == item.units) {
+                        Some(existing) => existing.value += item.value,
+                        None => items.push(item),
+                    }
+                }
+            }
+            if let Some((first, rest)) = items.split_first() {
+                let mut ty = first.numeric_type()?;
+                for item in rest {
+                    ty = ty.add(item.numeric_type()?)?;
+                }
+            }
+            items
+        }
+        MathValue::Negate(ref value) => {
+            let mut items = sum_value(value)?;
+            for item in &mut items {
+                item.value = -item.value;
+            }
+            items
+        }
+        MathValue::Product(ref values) => {
+            let mut items = vec![SumItem { value: 1., units: vec![] }];
+            for value in values {
+                let factors = sum_value(value)?;
+                let mut products = Vec::with_capacity(items.len() * factors.len());
+                for item in &items {
+                    for factor in &factors {
+                        products.push(SumItem {
+                            value: item.value * factor.value,
+                            units: multiply_unit_maps(&item.units, &factor.units),
+                        });
+                    }
+                }
+                items = products;
+            }
+            items
+        }
+        MathValue::Invert(ref value) => {
+            let mut items = sum_value(value)?;
+            if items.len() != 1 {
+                return None;
+            }
+            let item = &mut items[0];
+            item.value = 1. / item.value;
+            for unit in &mut item.units {
+                unit.1 = -unit.1;
+            }
+            items
+        }
+        MathValue::Min(ref values) => {
+            combine_single_items(values, |values| values.iter().cloned().fold(f64::INFINITY, f64::min))?
+        }
+        MathValue::Max(ref values) => {
+            combine_single_items(values, |values| values.iter().cloned().fold(f64::NEG_INFINITY, f64::max))?
+        }
+        MathValue::Clamp { ref lower, ref value, ref upper } => {
+            let values = [(**lower).clone(), (**value).clone(), (**upper).clone()];
+            combine_single_items(&values, |values| values[0].max(values[1].min(values[2])))?
+        }
+    })
+}
+
+/// Converts `value` to `unit`, if they're the same or compatible units.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#convert-a-cssunitvalue
+fn convert(value: &UnitValue, unit: CssUnit) -> Option<UnitValue> {
+    if value.unit == unit {
+        return Some(value.clone());
+    }
+    if value.unit.canonical() != unit.canonical() {
+        return None;
+    }
+    let ratio = value.unit.to_canonical()? / unit.to_canonical()?;
+    Some(UnitValue::new(value.value * ratio, unit))
+}
+
+impl NumericValue {
+    /// Returns this value with its relative lengths converted to `px`
+    /// against `context`, so that it can be converted to absolute units.
+    pub fn absolutize(&self, context: &dyn ComputeContext) -> NumericValue {
+        let all = |values: &[NumericValue]| values.iter().map(|value| value.absolutize(context)).collect();
+        let one = |value: &NumericValue| Box::new(value.absolutize(context));
+        match *self {
+            NumericValue::Unit(UnitValue { value, unit: CssUnit::Length(unit) }) => {
+                let value = length_to_px(value, unit, context);
+                NumericValue::Unit(UnitValue::new(value, CssUnit::Length(LengthUnit::Px)))
+            }
+            NumericValue::Unit(..) => self.clone(),
+            NumericValue::Math(ref math) => NumericValue::Math(match *math {
+                MathValue::Sum(ref values) => MathValue::Sum(all(values)),
+                MathValue::Product(ref values) => MathValue::Product(all(values)),
+                MathValue::Negate(ref value) => MathValue::Negate(one(value)),
+                MathValue::Invert(ref value) => MathValue::Invert(one(value)),
+                MathValue::Min(ref values) => MathValue::Min(all(values)),
+                MathValue::Max(ref values) => MathValue::Max(all(values)),
+                MathValue::Clamp { ref lower, ref value, ref upper } => {
+                    MathValue::Clamp { lower: one(lower), value: one(value), upper: one(upper) }
+                }
+            }),
+        }
+    }
+
+    /// Converts this value to a single value of `unit`, like
+    /// `CSSNumericValue.to()`. Math expressions are simplified as far as
+    /// possible first.
+    ///
+    /// Fails if the value is of a different type, or if it's a sum that
+    /// can't be simplified to a single term, like `calc(1px + 1em)`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-to
+    pub fn to(&self, unit: CssUnit) -> Result<UnitValue, NumericError> {
+        let mut sum = sum_value(self).ok_or(NumericError::NotConvertible)?;
+        if sum.len() != 1 {
+            return Err(NumericError::NotConvertible);
+        }
+        let value = sum.pop().unwrap().to_unit_value().ok_or(NumericError::NotConvertible)?;
+        convert(&value, unit).ok_or(NumericError::NotConvertible)
+    }
+
+    /// Converts this value to a sum of values of `units`, like
+    /// `CSSNumericValue.toSum()`, in that order.
+    ///
+    /// With no `units`, the sum has a term for each unit in the value, in
+    /// canonical units where possible and sorted by unit.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-tosum
+    pub fn to_sum(&self, units: &[CssUnit]) -> Result<MathValue, NumericError> {
+        let sum = sum_value(self).ok_or(NumericError::NotConvertible)?;
+        let mut values = sum
+            .iter()
+            .map(SumItem::to_unit_value)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(NumericError::NotConvertible)?;
+        if units.is_empty() {
+            values.sort_by(|a, b| a.unit.as_str().cmp(b.unit.as_str()));
+            return Ok(MathValue::Sum(values.into_iter().map(NumericValue::Unit).collect()));
+        }
+        let mut result = Vec::with_capacity(units.len());
+        for &unit in units {
+            let mut total = UnitValue::new(0., unit);
+            values.retain(|value| match convert(value, unit) {
+                Some(value) => {
+                    total.value += value.value;
+                    false
+                }
+                None => true,
+            });
+            result.push(NumericValue::Unit(total));
+        }
+        if !values.is_empty() {
+            return Err(NumericError::NotConvertible);
+        }
+        Ok(MathValue::Sum(result.into()))
+    }
+}
+
+#[test]
+fn conversion_test() {
+    use crate::calc::CalcNode;
+    use crate::units::{AngleUnit, LengthUnit, TimeUnit};
+
+    fn parse(value: &str) -> NumericValue {
+        let mut input = cssparser::ParserInput::new(value);
+        let mut input = cssparser::Parser::new(&mut input);
+        let name = input.expect_function().unwrap().clone();
+        NumericValue::from_calc(&CalcNode::parse_function(&name, &mut input).unwrap()).unwrap()
+    }
+
+    let px = CssUnit::Length(LengthUnit::Px);
+    let cm = CssUnit::Length(LengthUnit::Cm);
+    let em = CssUnit::Length(LengthUnit::Em);
+    let deg = CssUnit::Angle(AngleUnit::Deg);
+    let s = CssUnit::Time(TimeUnit::S);
+    let unit = |value, unit| NumericValue::Unit(UnitValue::new(value, unit));
+
+    assert_eq!(unit(1., CssUnit::Angle(AngleUnit::Turn)).to(deg), Ok(UnitValue::new(360., deg)));
+    assert_eq!(unit(1500., CssUnit::Time(TimeUnit::Ms)).to(s), Ok(UnitValue::new(1.5, s)));
+    assert_eq!(unit(2.54, cm).to(px).map(|v| v.value.round()), Ok(96.));
+    assert_eq!(unit(1., em).to(em), Ok(UnitValue::new(1., em)));
+    assert_eq!(unit(1., em).to(px), Err(NumericError::NotConvertible));
+    assert_eq!(unit(1., px).to(deg), Err(NumericError::NotConvertible));
+    assert_eq!(parse("calc(1in + 4px)").to(px), Ok(UnitValue::new(100., px)));
+    assert_eq!(parse("calc(1px * 2 + 3px)").to(px), Ok(UnitValue::new(5., px)));
+    assert_eq!(parse("calc(1px + 1em)").to(px), Err(NumericError::NotConvertible));
+    assert_eq!(parse("min(1in, 100px)").to(px), Ok(UnitValue::new(96., px)));
+    assert_eq!(parse("clamp(1px, 3px, 2px)").to(px), Ok(UnitValue::new(2., px)));
+    assert_eq!(parse("min(1px, 1em)").to(px), Err(NumericError::NotConvertible));
+    assert_eq!(parse("calc(6 / 3)").to(CssUnit::Number), Ok(UnitValue::new(2., CssUnit::Number)));
+
+    let to_sum = |value: &str, units: &[CssUnit]| {
+        parse(value).to_sum(units).map(|sum| NumericValue::Math(sum).to_css_string())
+    };
+    assert_eq!(to_sum("calc(1px + 2em + 1in - 1em)", &[]), Ok("calc(1em + 97px)".into()));
+    assert_eq!(to_sum("calc(1px + 2em + 1in - 1em)", &[em, px]), Ok("calc(1em + 97px)".into()));
+    assert_eq!(to_sum("calc(1px + 2em)", &[em]), Err(NumericError::NotConvertible));
+    assert_eq!(to_sum("calc(1px * 1px)", &[]), Err(NumericError::NotConvertible));
+    assert_eq!(to_sum("calc(50% + 1turn)", &[CssUnit::Percent, deg]), Ok("calc(50% + 360deg)".into()));
+}
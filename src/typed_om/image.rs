@@ -0,0 +1,76 @@
+//! Image values.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#imagevalue-objects
+
+use std::fmt;
+
+use crate::image::Image;
+use crate::url::Url;
+
+/// An `<image>`, like `CSSImageValue`.
+///
+/// This is intentionally opaque, like in the Typed OM: it only keeps the
+/// serialization of the image, and the URLs it references so that embedders
+/// can load them.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#cssimagevalue
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ImageValue {
+    serialization: Box<str>,
+    urls: Vec<Url>,
+}
+
+/// Appends the URLs that `image` references to `urls`, in order.
+fn collect_urls(image: &Image, urls: &mut Vec<Url>) {
+    match *image {
+        Image::Url(ref url) => urls.push(url.clone()),
+        Image::Gradient(..) => {}
+        Image::ImageSet(ref options) => {
+            for option in options {
+                collect_urls(&option.image, urls);
+            }
+        }
+    }
+}
+
+impl ImageValue {
+    pub fn new(image: &Image) -> Self {
+        let mut urls = vec![];
+        collect_urls(image, &mut urls);
+        Self { serialization: image.to_css_string().into(), urls }
+    }
+
+    /// Returns the URLs that the image references, unresolved, in order of
+    /// appearance. Empty for gradients, and with one URL per option for
+    /// `image-set()`s.
+    pub fn urls(&self) -> &[Url] {
+        &self.urls
+    }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_str(&self.serialization)
+    }
+}
+
+#[test]
+fn image_value_test() {
+    fn parse(value: &str) -> ImageValue {
+        let mut input = cssparser::ParserInput::new(value);
+        ImageValue::new(&crate::image::parse_image(&mut cssparser::Parser::new(&mut input)).unwrap())
+    }
+
+    let image = parse("url(a.png)");
+    assert_eq!(image.urls(), [Url { url: "a.png".into(), quoted: false }]);
+    assert!(parse("linear-gradient(red, blue)").urls().is_empty());
+    let image = parse("image-set(url(a.png) 1x, \"b.png\" 2x type(\"image/png\"), linear-gradient(red, blue) 3x)");
+    let urls = image.urls().iter().map(|url| &*url.url).collect::<Vec<_>>();
+    assert_eq!(urls, ["a.png", "b.png"]);
+
+    let mut s = String::new();
+    image.to_css(&mut s).unwrap();
+    assert_eq!(
+        s,
+        "image-set(url(\"a.png\") 1x, url(\"b.png\") 2x type(\"image/png\"), \
+         linear-gradient(rgb(255, 0, 0), rgb(0, 0, 255)) 3x)",
+    );
+}
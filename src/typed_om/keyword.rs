@@ -0,0 +1,83 @@
+//! Keyword values.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#keywordvalue-objects
+
+use std::fmt;
+
+use crate::keyword::CssWideKeyword;
+
+/// A keyword, like `CSSKeywordValue`.
+///
+/// The value is kept as written for custom idents, and in lowercase for
+/// keywords from the syntax, which are ASCII case-insensitive.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#csskeywordvalue
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct KeywordValue {
+    pub value: Box<str>,
+}
+
+impl KeywordValue {
+    /// Returns a keyword with the given value, or `None` if it's empty,
+    /// which is a `TypeError` in the Typed OM.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-csskeywordvalue-csskeywordvalue
+    pub fn new(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            return None;
+        }
+        Some(Self { value: value.into() })
+    }
+
+    /// Returns the CSS-wide keyword that this keyword is, if any, ASCII
+    /// case-insensitively.
+    pub fn css_wide_keyword(&self) -> Option<CssWideKeyword> {
+        CssWideKeyword::from_ident(&self.value)
+    }
+
+    /// Serializes the keyword as an identifier, escaping it as needed so that
+    /// it parses back to the same value.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#keywordvalue-serialization
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        cssparser::serialize_identifier(&self.value, dest)
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+impl cssparser::ToCss for KeywordValue {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        KeywordValue::to_css(self, dest)
+    }
+}
+
+#[test]
+fn keyword_value_test() {
+    assert_eq!(KeywordValue::new(""), None);
+    assert_eq!(KeywordValue::new("Inherit").unwrap().css_wide_keyword(), Some(CssWideKeyword::Inherit));
+    assert_eq!(KeywordValue::new("auto").unwrap().css_wide_keyword(), None);
+
+    for &(value, serialization) in &[
+        ("auto", "auto"),
+        ("Foo", "Foo"),
+        ("123", "\\31 23"),
+        ("-", "\\-"),
+        ("--x", "--x"),
+        ("a b", "a\\ b"),
+        ("a.b", "a\\.b"),
+        ("caf\u{e9}", "caf\u{e9}"),
+    ] {
+        let keyword = KeywordValue::new(value).unwrap();
+        let serialized = keyword.to_css_string();
+        assert_eq!(serialized, serialization);
+        let mut input = cssparser::ParserInput::new(&serialized);
+        let mut input = cssparser::Parser::new(&mut input);
+        assert_eq!(&**input.expect_ident().unwrap(), value);
+        assert!(input.is_exhausted());
+    }
+}
@@ -0,0 +1,197 @@
+//! Transformation matrices.
+//!
+//! https://drafts.fxtf.org/geometry/#dommatrix
+
+use std::fmt;
+
+use crate::value::write_number;
+
+/// A 4×4 transformation matrix, like `DOMMatrix`.
+///
+/// https://drafts.fxtf.org/geometry/#dommatrixreadonly
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix {
+    /// The entries of the matrix in column-major order, `m11`, `m12`, …,
+    /// `m44`, which is the order of the arguments of `matrix3d()`.
+    pub entries: [f64; 16],
+    /// Whether the matrix was created as a 2D one, and only 2D operations
+    /// were applied to it since.
+    pub is_2d: bool,
+}
+
+impl Matrix {
+    pub fn identity() -> Self {
+        let mut entries = [0.; 16];
+        for i in 0..4 {
+            entries[i * 5] = 1.;
+        }
+        Self { entries, is_2d: true }
+    }
+
+    /// Returns the 2D matrix with the arguments of `matrix()`.
+    pub fn from_2d(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        let mut matrix = Self::identity();
+        matrix.entries[0] = a;
+        matrix.entries[1] = b;
+        matrix.entries[4] = c;
+        matrix.entries[5] = d;
+        matrix.entries[12] = e;
+        matrix.entries[13] = f;
+        matrix
+    }
+
+    /// Returns the 3D matrix with the arguments of `matrix3d()`.
+    pub fn from_3d(entries: [f64; 16]) -> Self {
+        Self { entries, is_2d: false }
+    }
+
+    /// Returns a translation.
+    ///
+    /// https://drafts.csswg.org/css-transforms-2/#Translate3dDefined
+    pub fn translate(x: f64, y: f64, z: f64) -> Self {
+        let mut matrix = Self::identity();
+        matrix.entries[12] = x;
+        matrix.entries[13] = y;
+        matrix.entries[14] = z;
+        matrix.is_2d = z == 0.;
+        matrix
+    }
+
+    /// Returns a scale.
+    ///
+    /// https://drafts.csswg.org/css-transforms-2/#Scale3dDefined
+    pub fn scale(x: f64, y: f64, z: f64) -> Self {
+        let mut matrix = Self::identity();
+        matrix.entries[0] = x;
+        matrix.entries[5] = y;
+        matrix.entries[10] = z;
+        matrix.is_2d = z == 1.;
+        matrix
+    }
+
+    /// Returns a clockwise rotation of `angle` degrees around the vector
+    /// `(x, y, z)`, or the identity if the vector can't be normalized.
+    ///
+    /// https://drafts.csswg.org/css-transforms-2/#Rotate3dDefined
+    pub fn rotate(x: f64, y: f64, z: f64, angle: f64) -> Self {
+        let mut matrix = Self::identity();
+        let length = (x * x + y * y + z * z).sqrt();
+        matrix.is_2d = x == 0. && y == 0.;
+        if length == 0. || !length.is_finite() {
+            return matrix;
+        }
+        let (x, y, z) = (x / length, y / length, z / length);
+        let half = angle.to_radians() / 2.;
+        let sc = half.sin() * half.cos();
+        let sq = half.sin() * half.sin();
+        matrix.entries[0] = 1. - 2. * (y * y + z * z) * sq;
+        matrix.entries[1] = 2. * (x * y * sq + z * sc);
+        matrix.entries[2] = 2. * (x * z * sq - y * sc);
+        matrix.entries[4] = 2. * (x * y * sq - z * sc);
+        matrix.entries[5] = 1. - 2. * (x * x + z * z) * sq;
+        matrix.entries[6] = 2. * (y * z * sq + x * sc);
+        matrix.entries[8] = 2. * (x * z * sq + y * sc);
+        matrix.entries[9] = 2. * (y * z * sq - x * sc);
+        matrix.entries[10] = 1. - 2. * (x * x + y * y) * sq;
+        matrix
+    }
+
+    /// Returns a skew of `ax` degrees along the x axis and `ay` degrees
+    /// along the y axis.
+    ///
+    /// https://drafts.csswg.org/css-transforms-1/#SkewDefined
+    pub fn skew(ax: f64, ay: f64) -> Self {
+        let mut matrix = Self::identity();
+        matrix.entries[1] = ay.to_radians().tan();
+        matrix.entries[4] = ax.to_radians().tan();
+        matrix
+    }
+
+    /// Returns a perspective projection with the given distance in `px`, or
+    /// the identity for `perspective(none)`. Distances under `1px` are
+    /// treated as `1px`.
+    ///
+    /// https://drafts.csswg.org/css-transforms-2/#PerspectiveDefined
+    pub fn perspective(distance: Option<f64>) -> Self {
+        let mut matrix = Self::identity();
+        if let Some(distance) = distance {
+            matrix.entries[11] = -1. / distance.max(1.);
+        }
+        matrix.is_2d = false;
+        matrix
+    }
+
+    /// Returns the product of this matrix and `other`, which applies
+    /// `other` first, like `DOMMatrix.multiply()`. The result is 2D if both
+    /// matrices are.
+    ///
+    /// https://drafts.fxtf.org/geometry/#dom-dommatrixreadonly-multiply
+    pub fn multiply(&self, other: &Matrix) -> Matrix {
+        let mut entries = [0.; 16];
+        for column in 0..4 {
+            for row in 0..4 {
+                entries[column * 4 + row] =
+                    (0..4).map(|k| self.entries[k * 4 + row] * other.entries[column * 4 + k]).sum();
+            }
+        }
+        Matrix { entries, is_2d: self.is_2d && other.is_2d }
+    }
+
+    /// Returns the entry `m<i><j>` of `DOMMatrix`, where `i` is the
+    /// one-based column and `j` the one-based row.
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        self.entries[(i - 1) * 4 + j - 1]
+    }
+
+    /// https://drafts.fxtf.org/geometry/#dommatrixreadonly-stringification-behavior
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        let entries: &[f64] = if self.is_2d {
+            dest.write_str("matrix(")?;
+            let m = &self.entries;
+            &[m[0], m[1], m[4], m[5], m[12], m[13]]
+        } else {
+            dest.write_str("matrix3d(")?;
+            &self.entries
+        };
+        for (i, &entry) in entries.iter().enumerate() {
+            if i > 0 {
+                dest.write_str(", ")?;
+            }
+            write_number(entry, dest)?;
+        }
+        dest.write_char(')')
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[test]
+fn matrix_test() {
+    let close = |a: &Matrix, b: &Matrix| {
+        a.entries.iter().zip(b.entries.iter()).all(|(a, b)| (a - b).abs() < 1e-9)
+    };
+
+    let translate = Matrix::translate(10., 20., 0.);
+    let scale = Matrix::scale(2., 3., 1.);
+    assert!(translate.is_2d && scale.is_2d);
+    assert_eq!(translate.multiply(&scale), Matrix::from_2d(2., 0., 0., 3., 10., 20.));
+    assert_eq!(scale.multiply(&translate), Matrix::from_2d(2., 0., 0., 3., 20., 60.));
+    assert_eq!(Matrix::identity().multiply(&translate), translate);
+    assert!(close(&Matrix::rotate(0., 0., 1., 90.), &Matrix::from_2d(0., 1., -1., 0., 0., 0.)));
+    assert!(close(&Matrix::rotate(0., 0., 2., 90.), &Matrix::rotate(0., 0., 1., 90.)));
+    assert!(!Matrix::rotate(1., 0., 0., 90.).is_2d);
+    assert_eq!(Matrix::rotate(0., 0., 0., 90.), Matrix::identity());
+    assert!(close(&Matrix::skew(45., 0.), &Matrix::from_2d(1., 0., 1., 1., 0., 0.)));
+    assert_eq!(Matrix::perspective(Some(100.)).get(3, 4), -0.01);
+    assert_eq!(Matrix::perspective(Some(0.)).get(3, 4), -1.);
+    assert!(!Matrix::perspective(None).is_2d);
+    assert_eq!(Matrix::translate(1., 2., 3.).multiply(&Matrix::identity()).get(4, 3), 3.);
+
+    let mut s = String::new();
+    translate.multiply(&scale).to_css(&mut s).unwrap();
+    assert_eq!(s, "matrix(2, 0, 0, 3, 10, 20)");
+}
@@ -0,0 +1,221 @@
+//! The CSS Typed OM objects that matched values reify to, so that they can
+//! back a `StylePropertyMap` implementation.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/
+
+use std::fmt;
+
+use crate::value::MatchedValue;
+
+mod color;
+mod conversion;
+mod image;
+mod keyword;
+mod matrix;
+mod numeric;
+mod numeric_type;
+#[cfg(feature = "experimental-position")]
+mod position;
+mod property_map;
+mod transform;
+mod unit;
+mod unparsed;
+
+pub use self::color::ColorValue;
+pub use self::image::ImageValue;
+pub use self::keyword::KeywordValue;
+pub use self::matrix::Matrix;
+pub use self::numeric::{MathValue, NumericArray, NumericError, NumericValue, UnitValue};
+pub use self::numeric_type::{BaseType, NumericType};
+#[cfg(feature = "experimental-position")]
+pub use self::position::PositionValue;
+pub use self::property_map::{PropertyMapError, PropertySyntaxes, StylePropertyMap};
+pub use self::transform::{TransformComponent, TransformValue};
+pub use self::unit::CssUnit;
+pub use self::unparsed::{UnparsedSegment, UnparsedValue, VariableReferenceValue};
+
+/// A reified value, like `CSSStyleValue` and its subclasses.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#cssstylevalue
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum StyleValue {
+    Numeric(NumericValue),
+    Keyword(KeywordValue),
+    Transform(TransformValue),
+    Unparsed(UnparsedValue),
+    Color(ColorValue),
+    Image(ImageValue),
+    #[cfg(feature = "experimental-position")]
+    Position(PositionValue),
+    /// A value with no more specific representation, like a `<url>`, which
+    /// is only a `CSSStyleValue`, as serialized.
+    Other(Box<str>),
+}
+
+impl StyleValue {
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            StyleValue::Numeric(ref value) => value.to_css(dest),
+            StyleValue::Keyword(ref value) => value.to_css(dest),
+            StyleValue::Transform(ref value) => value.to_css(dest),
+            StyleValue::Unparsed(ref value) => value.to_css(dest),
+            StyleValue::Color(ref value) => value.to_css(dest),
+            StyleValue::Image(ref value) => value.to_css(dest),
+            #[cfg(feature = "experimental-position")]
+            StyleValue::Position(ref value) => value.to_css(dest),
+            StyleValue::Other(ref value) => dest.write_str(value),
+        }
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+impl cssparser::ToCss for StyleValue {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        StyleValue::to_css(self, dest)
+    }
+}
+
+/// Reifies a matched numeric value, or returns `None` if it's not one, or
+/// it's a math function with no `MathValue` equivalent.
+fn numeric_value(value: &MatchedValue) -> Option<NumericValue> {
+    let (value, unit) = match *value {
+        MatchedValue::Length { value, unit } => (value, CssUnit::Length(unit)),
+        MatchedValue::Number(value) => (value, CssUnit::Number),
+        MatchedValue::Integer(value) => (value as f64, CssUnit::Number),
+        MatchedValue::Percentage(value) => (value * 100., CssUnit::Percent),
+        MatchedValue::Angle { value, unit } => (value, CssUnit::Angle(unit)),
+        MatchedValue::Time { value, unit } => (value, CssUnit::Time(unit)),
+        MatchedValue::Resolution { value, unit } => (value, CssUnit::Resolution(unit)),
+        MatchedValue::Calc(ref node) | MatchedValue::IntegerCalc(ref node) => {
+            return NumericValue::from_calc(node)
+        }
+        _ => return None,
+    };
+    Some(NumericValue::Unit(UnitValue::new(value, unit)))
+}
+
+/// Reifies a matched value into the values that a `StylePropertyMap` would
+/// return for a registered custom property with that value: one per item for
+/// lists, and a single one otherwise.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#reify-a-value
+pub fn reify(value: &MatchedValue) -> Vec<StyleValue> {
+    let value = match *value {
+        MatchedValue::List(ref items, ..) => return items.iter().flat_map(reify).collect(),
+        MatchedValue::Length { .. } |
+        MatchedValue::Number(..) |
+        MatchedValue::Integer(..) |
+        MatchedValue::Percentage(..) |
+        MatchedValue::Angle { .. } |
+        MatchedValue::Time { .. } |
+        MatchedValue::Resolution { .. } |
+        MatchedValue::Calc(..) |
+        MatchedValue::IntegerCalc(..) => match numeric_value(value) {
+            Some(value) => StyleValue::Numeric(value),
+            None => StyleValue::Other(value.to_css_string().into()),
+        },
+        MatchedValue::Ident(ref ident) | MatchedValue::CustomIdent(ref ident) => {
+            StyleValue::Keyword(KeywordValue { value: (**ident).into() })
+        }
+        MatchedValue::TransformFunction(ref function) => {
+            match TransformValue::from_functions(std::slice::from_ref(function)) {
+                Some(value) => StyleValue::Transform(value),
+                None => StyleValue::Other(value.to_css_string().into()),
+            }
+        }
+        MatchedValue::TransformList(ref functions) => match TransformValue::from_functions(functions) {
+            Some(value) => StyleValue::Transform(value),
+            None => StyleValue::Other(value.to_css_string().into()),
+        },
+        MatchedValue::Color(ref color) => match ColorValue::from_color(color) {
+            Some(color) => StyleValue::Color(color),
+            None => StyleValue::Keyword(KeywordValue { value: "currentcolor".into() }),
+        },
+        MatchedValue::Image(ref image) => StyleValue::Image(ImageValue::new(image)),
+        #[cfg(feature = "experimental-position")]
+        MatchedValue::Position(ref position) => match PositionValue::from_position(position) {
+            Some(value) => StyleValue::Position(value),
+            None => StyleValue::Other(value.to_css_string().into()),
+        },
+        MatchedValue::Unparsed(ref source) => StyleValue::Unparsed(UnparsedValue::parse_str(source)),
+        MatchedValue::PendingSubstitution(ref value) => {
+            StyleValue::Unparsed(UnparsedValue::parse_str(&value.source))
+        }
+        MatchedValue::Url(..) => StyleValue::Other(value.to_css_string().into()),
+    };
+    vec![value]
+}
+
+#[test]
+fn typed_om_test() {
+    fn reify_str(syntax: &str, value: &str) -> Vec<StyleValue> {
+        let descriptor = crate::parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        reify(&descriptor.parse_value(&mut cssparser::Parser::new(&mut input)).unwrap())
+    }
+
+    fn unit_value(value: f64, unit: CssUnit) -> StyleValue {
+        StyleValue::Numeric(NumericValue::Unit(UnitValue::new(value, unit)))
+    }
+
+    use crate::units::{AngleUnit, LengthUnit};
+
+    assert_eq!(reify_str("<length>", "10PX"), [unit_value(10., CssUnit::Length(LengthUnit::Px))]);
+    assert_eq!(reify_str("<percentage>", "50%"), [unit_value(50., CssUnit::Percent)]);
+    assert_eq!(
+        reify_str("<integer>#", "1, 2"),
+        [unit_value(1., CssUnit::Number), unit_value(2., CssUnit::Number)],
+    );
+    match reify_str("<angle>", "0.5turn")[0] {
+        StyleValue::Numeric(NumericValue::Unit(ref value)) => {
+            assert_eq!(value.to_canonical(), Some(UnitValue::new(180., CssUnit::Angle(AngleUnit::Deg))));
+        }
+        ref value => panic!("{:?}", value),
+    }
+    assert_eq!(
+        reify_str("auto | <length>", "AUTO"),
+        [StyleValue::Keyword(KeywordValue { value: "auto".into() })],
+    );
+    assert_eq!(reify_str("<transform-list>", "scale(2) rotate(0)").len(), 1);
+    match reify_str("*", "a var(--b)")[0] {
+        StyleValue::Unparsed(ref value) => {
+            assert_eq!(value.segments[0], UnparsedSegment::String("a ".into()));
+            assert_eq!(
+                value.segments[1],
+                UnparsedSegment::Variable(VariableReferenceValue::new("--b", None).unwrap()),
+            );
+        }
+        ref value => panic!("{:?}", value),
+    }
+
+    for &(syntax, value, serialization) in &[
+        ("<length>+", "1px 2EM", "1px|2em"),
+        ("<number>", "1.50", "1.5"),
+        ("<percentage>", "12.5%", "12.5%"),
+        ("<length-percentage>", "calc(100% - 1Em)", "calc(100% - 1em)"),
+        ("<length>", "round(1px, 2px)", "round(1px, 2px)"),
+        ("<custom-ident>", "Foo", "Foo"),
+        ("<custom-ident>", "\\31 23", "\\31 23"),
+        ("foo | <custom-ident>", "FOO", "foo"),
+        ("<custom-ident>+", "a\\.b c", "a\\.b|c"),
+        ("<transform-list>", "scale(2) rotate(0)", "scale(2, 2) rotate(0deg)"),
+        ("<transform-function>", "rotate(calc(sin(1deg) * 1deg))", "rotate(calc(sin(1deg) * 1deg))"),
+        ("<color>", "Red", "rgb(255, 0, 0)"),
+        ("<color>", "currentColor", "currentcolor"),
+        ("<color>", "lch(50% 10 20deg)", "lch(50 10 20)"),
+        ("<image>", "linear-gradient(red, blue)", "linear-gradient(rgb(255, 0, 0), rgb(0, 0, 255))"),
+        ("<url>", "url(a.png)", "url(\"a.png\")"),
+        ("*", " foo(bar) ", "foo(bar)"),
+        ("<length>", "var(--x)", "var(--x)"),
+        ("<length>", "calc(var(--x,1px) * 2)", "calc(var(--x, 1px) * 2)"),
+    ] {
+        let values = reify_str(syntax, value).iter().map(StyleValue::to_css_string).collect::<Vec<_>>();
+        assert_eq!(values.join("|"), serialization, "{:?} as {:?}", value, syntax);
+    }
+}
@@ -0,0 +1,619 @@
+//! Numeric values.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#numeric-objects
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Deref;
+
+use super::numeric_type::NumericType;
+use super::unit::CssUnit;
+use crate::calc::{CalcNode, MathFunction};
+use crate::value::write_dimension;
+
+/// A single number with a unit, like `CSSUnitValue`.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#cssunitvalue
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitValue {
+    pub value: f64,
+    pub unit: CssUnit,
+}
+
+impl UnitValue {
+    pub fn new(value: f64, unit: CssUnit) -> Self {
+        Self { value, unit }
+    }
+
+    /// Returns this value in the canonical unit of its type, or `None` if
+    /// it's a relative length.
+    pub fn to_canonical(&self) -> Option<Self> {
+        Some(Self::new(self.value * self.unit.to_canonical()?, self.unit.canonical()))
+    }
+
+    /// https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssunitvalue
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        write_dimension(self.value, self.unit.suffix(), dest)
+    }
+}
+
+impl From<f64> for UnitValue {
+    /// Returns a plain number.
+    fn from(value: f64) -> Self {
+        Self::new(value, CssUnit::Number)
+    }
+}
+
+/// A read-only list of numeric values, like `CSSNumericArray`, which holds
+/// the arguments of math expressions.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericarray
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NumericArray(Vec<NumericValue>);
+
+impl NumericArray {
+    /// Returns an array of `values`, rectifying plain numbers into `number`
+    /// unit values.
+    pub fn new<V: Into<NumericValue>>(values: impl IntoIterator<Item = V>) -> Self {
+        values.into_iter().collect()
+    }
+
+    pub fn into_vec(self) -> Vec<NumericValue> {
+        self.0
+    }
+}
+
+impl Deref for NumericArray {
+    type Target = [NumericValue];
+
+    #[inline]
+    fn deref(&self) -> &[NumericValue] {
+        &self.0
+    }
+}
+
+impl From<Vec<NumericValue>> for NumericArray {
+    fn from(values: Vec<NumericValue>) -> Self {
+        NumericArray(values)
+    }
+}
+
+impl<V: Into<NumericValue>> FromIterator<V> for NumericArray {
+    fn from_iter<I: IntoIterator<Item = V>>(values: I) -> Self {
+        NumericArray(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl IntoIterator for NumericArray {
+    type Item = NumericValue;
+    type IntoIter = std::vec::IntoIter<NumericValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a NumericArray {
+    type Item = &'a NumericValue;
+    type IntoIter = std::slice::Iter<'a, NumericValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// A math expression, like `CSSMathValue` and its subclasses.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#complex-numeric
+#[derive(Clone, Debug, PartialEq)]
+pub enum MathValue {
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmathsum
+    Sum(NumericArray),
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmathproduct
+    Product(NumericArray),
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmathnegate
+    Negate(Box<NumericValue>),
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmathinvert
+    Invert(Box<NumericValue>),
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmathmin
+    Min(NumericArray),
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmathmax
+    Max(NumericArray),
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmathclamp
+    Clamp {
+        lower: Box<NumericValue>,
+        value: Box<NumericValue>,
+        upper: Box<NumericValue>,
+    },
+}
+
+impl MathValue {
+    /// https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssmathvalue
+    fn write<W: fmt::Write>(&self, nested: bool, paren_less: bool, dest: &mut W) -> fmt::Result {
+        let (values, separator) = match *self {
+            MathValue::Min(ref values) => (values, "min("),
+            MathValue::Max(ref values) => (values, "max("),
+            MathValue::Clamp { ref lower, ref value, ref upper } => {
+                dest.write_str("clamp(")?;
+                lower.write(false, true, dest)?;
+                dest.write_str(", ")?;
+                value.write(false, true, dest)?;
+                dest.write_str(", ")?;
+                upper.write(false, true, dest)?;
+                return dest.write_char(')');
+            }
+            _ => {
+                if !paren_less {
+                    dest.write_str(if nested { "(" } else { "calc(" })?;
+                }
+                self.write_operation(dest)?;
+                return if paren_less { Ok(()) } else { dest.write_char(')') };
+            }
+        };
+        dest.write_str(separator)?;
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                dest.write_str(", ")?;
+            }
+            value.write(false, true, dest)?;
+        }
+        dest.write_char(')')
+    }
+
+    /// Serializes a sum, product, negation or inversion without the
+    /// parentheses around it.
+    fn write_operation<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            MathValue::Sum(ref values) => {
+                for (i, value) in values.iter().enumerate() {
+                    match *value {
+                        NumericValue::Math(MathValue::Negate(ref value)) if i > 0 => {
+                            dest.write_str(" - ")?;
+                            value.write(true, false, dest)?;
+                        }
+                        _ => {
+                            if i > 0 {
+                                dest.write_str(" + ")?;
+                            }
+                            value.write(true, false, dest)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            MathValue::Product(ref values) => {
+                for (i, value) in values.iter().enumerate() {
+                    match *value {
+                        NumericValue::Math(MathValue::Invert(ref value)) if i > 0 => {
+                            dest.write_str(" / ")?;
+                            value.write(true, false, dest)?;
+                        }
+                        _ => {
+                            if i > 0 {
+                                dest.write_str(" * ")?;
+                            }
+                            value.write(true, false, dest)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            MathValue::Negate(ref value) => {
+                dest.write_char('-')?;
+                value.write(true, false, dest)
+            }
+            MathValue::Invert(ref value) => {
+                dest.write_str("1 / ")?;
+                value.write(true, false, dest)
+            }
+            MathValue::Min(..) | MathValue::Max(..) | MathValue::Clamp { .. } => unreachable!(),
+        }
+    }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        self.write(false, false, dest)
+    }
+
+    /// Returns the type of this expression, or `None` if it's invalid, like
+    /// a sum of a length and a time.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-type
+    pub fn numeric_type(&self) -> Option<NumericType> {
+        let all = |values: &[NumericValue], combine: fn(NumericType, NumericType) -> Option<NumericType>| {
+            let mut ty = values.first()?.numeric_type()?;
+            for value in &values[1..] {
+                ty = combine(ty, value.numeric_type()?)?;
+            }
+            Some(ty)
+        };
+        match *self {
+            MathValue::Sum(ref values) | MathValue::Min(ref values) | MathValue::Max(ref values) => {
+                all(values, NumericType::add)
+            }
+            MathValue::Product(ref values) => all(values, NumericType::multiply),
+            MathValue::Negate(ref value) => value.numeric_type(),
+            MathValue::Invert(ref value) => Some(value.numeric_type()?.invert()),
+            MathValue::Clamp { ref lower, ref value, ref upper } => {
+                lower.numeric_type()?.add(value.numeric_type()?)?.add(upper.numeric_type()?)
+            }
+        }
+    }
+}
+
+/// A numeric value, like `CSSNumericValue`: either a single number with a
+/// unit, or a math expression.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue
+#[derive(Clone, Debug, PartialEq)]
+pub enum NumericValue {
+    Unit(UnitValue),
+    Math(MathValue),
+}
+
+/// An error doing arithmetic with numeric values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NumericError {
+    /// The values have types that can't be combined, like when adding a
+    /// length to a time. This is a `TypeError` in the Typed OM.
+    IncompatibleTypes,
+    /// A number was divided by zero. This is a `RangeError` in the Typed OM.
+    DivisionByZero,
+    /// The value can't be converted to the requested units, like `1em` to
+    /// `px`. This is a `TypeError` in the Typed OM.
+    NotConvertible,
+}
+
+impl fmt::Display for NumericError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NumericError::IncompatibleTypes => f.write_str("incompatible numeric types"),
+            NumericError::DivisionByZero => f.write_str("division by zero"),
+            NumericError::NotConvertible => f.write_str("value not convertible to the requested units"),
+        }
+    }
+}
+
+impl std::error::Error for NumericError {}
+
+/// Rectifies a numberish value, so that plain numbers can be used where
+/// numeric values are expected.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#rectify-a-numberish-value
+impl From<f64> for NumericValue {
+    fn from(value: f64) -> Self {
+        NumericValue::Unit(value.into())
+    }
+}
+
+impl From<UnitValue> for NumericValue {
+    fn from(value: UnitValue) -> Self {
+        NumericValue::Unit(value)
+    }
+}
+
+impl From<MathValue> for NumericValue {
+    fn from(value: MathValue) -> Self {
+        NumericValue::Math(value)
+    }
+}
+
+impl NumericValue {
+    /// Reifies a matched math function into a math expression that keeps
+    /// its structure, or returns `None` if it uses math functions other than
+    /// `calc()`, `min()`, `max()` and `clamp()`, which the Typed OM can't
+    /// represent.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#reify-a-math-expression
+    pub fn from_calc(node: &CalcNode) -> Option<Self> {
+        let all = |nodes: &[CalcNode]| nodes.iter().map(Self::from_calc).collect::<Option<NumericArray>>();
+        let unit = |value, unit| Some(NumericValue::Unit(UnitValue::new(value, unit)));
+        let math = match *node {
+            CalcNode::Number(value) => return unit(value, CssUnit::Number),
+            CalcNode::Percentage(value) => return unit(value * 100., CssUnit::Percent),
+            CalcNode::Length(value, u) => return unit(value, CssUnit::Length(u)),
+            CalcNode::Angle(value, u) => return unit(value, CssUnit::Angle(u)),
+            CalcNode::Time(value, u) => return unit(value, CssUnit::Time(u)),
+            CalcNode::Resolution(value, u) => return unit(value, CssUnit::Resolution(u)),
+            CalcNode::Sum(ref nodes) => MathValue::Sum(all(nodes)?),
+            CalcNode::Product(ref nodes) => MathValue::Product(all(nodes)?),
+            CalcNode::Negate(ref node) => MathValue::Negate(Box::new(Self::from_calc(node)?)),
+            CalcNode::Invert(ref node) => MathValue::Invert(Box::new(Self::from_calc(node)?)),
+            CalcNode::Function(MathFunction::Min, ref arguments) => MathValue::Min(all(arguments)?),
+            CalcNode::Function(MathFunction::Max, ref arguments) => MathValue::Max(all(arguments)?),
+            CalcNode::Function(MathFunction::Clamp, ref arguments) => {
+                let mut arguments = all(arguments)?.into_iter().map(Box::new);
+                MathValue::Clamp {
+                    lower: arguments.next()?,
+                    value: arguments.next()?,
+                    upper: arguments.next()?,
+                }
+            }
+            CalcNode::Function(..) => return None,
+        };
+        Some(NumericValue::Math(math))
+    }
+
+    /// Returns the type of this value, like `CSSNumericValue.type()`, or
+    /// `None` if it's an invalid math expression.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-type
+    pub fn numeric_type(&self) -> Option<NumericType> {
+        match *self {
+            NumericValue::Unit(ref value) => Some(NumericType::of_unit(value.unit)),
+            NumericValue::Math(ref value) => value.numeric_type(),
+        }
+    }
+
+    /// Returns the unit values of `values`, if they all are unit values of
+    /// the same unit.
+    fn same_unit_values(values: &[NumericValue]) -> Option<(CssUnit, impl Iterator<Item = f64> + '_)> {
+        let unit = match *values.first()? {
+            NumericValue::Unit(ref value) => value.unit,
+            NumericValue::Math(..) => return None,
+        };
+        let same = values.iter().all(|value| matches!(*value, NumericValue::Unit(ref v) if v.unit == unit));
+        if !same {
+            return None;
+        }
+        Some((unit, values.iter().map(|value| match *value {
+            NumericValue::Unit(ref value) => value.value,
+            NumericValue::Math(..) => unreachable!(),
+        })))
+    }
+
+    /// Returns `self` followed by `values`, where `self` is replaced by its
+    /// arguments if it's a math expression of the kind that `arguments`
+    /// returns them for.
+    fn prepend_to<V: Clone + Into<NumericValue>>(
+        &self,
+        values: &[V],
+        arguments: fn(&MathValue) -> Option<&[NumericValue]>,
+    ) -> Vec<NumericValue> {
+        let mut all = match *self {
+            NumericValue::Math(ref math) => match arguments(math) {
+                Some(arguments) => arguments.to_vec(),
+                None => vec![self.clone()],
+            },
+            NumericValue::Unit(..) => vec![self.clone()],
+        };
+        all.extend(values.iter().cloned().map(Into::into));
+        all
+    }
+
+    /// Checks that values of all the types of `values` can be combined with
+    /// `combine`.
+    fn check_types(
+        values: &[NumericValue],
+        combine: fn(NumericType, NumericType) -> Option<NumericType>,
+    ) -> Result<(), NumericError> {
+        let mut ty = None;
+        for value in values {
+            let value_type = value.numeric_type().ok_or(NumericError::IncompatibleTypes)?;
+            ty = Some(match ty {
+                Some(ty) => combine(ty, value_type).ok_or(NumericError::IncompatibleTypes)?,
+                None => value_type,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the sum of this value and `values`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-add
+    pub fn add<V: Clone + Into<NumericValue>>(&self, values: &[V]) -> Result<NumericValue, NumericError> {
+        let values = self.prepend_to(values, |math| match *math {
+            MathValue::Sum(ref values) => Some(&values[..]),
+            _ => None,
+        });
+        if let Some((unit, values)) = Self::same_unit_values(&values) {
+            return Ok(NumericValue::Unit(UnitValue::new(values.sum(), unit)));
+        }
+        Self::check_types(&values, NumericType::add)?;
+        Ok(NumericValue::Math(MathValue::Sum(values.into())))
+    }
+
+    /// Returns the difference of this value and `values`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-sub
+    pub fn sub<V: Clone + Into<NumericValue>>(&self, values: &[V]) -> Result<NumericValue, NumericError> {
+        let values = values.iter().map(|value| value.clone().into().negate()).collect::<Vec<_>>();
+        self.add(&values)
+    }
+
+    /// Returns the product of this value and `values`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-mul
+    pub fn mul<V: Clone + Into<NumericValue>>(&self, values: &[V]) -> Result<NumericValue, NumericError> {
+        let values = self.prepend_to(values, |math| match *math {
+            MathValue::Product(ref values) => Some(&values[..]),
+            _ => None,
+        });
+        let mut product = 1.;
+        let mut unit = CssUnit::Number;
+        let mut all_units = true;
+        for value in &values {
+            match *value {
+                NumericValue::Unit(ref value) if value.unit == CssUnit::Number => product *= value.value,
+                NumericValue::Unit(ref value) if unit == CssUnit::Number => {
+                    product *= value.value;
+                    unit = value.unit;
+                }
+                _ => {
+                    all_units = false;
+                    break;
+                }
+            }
+        }
+        if all_units {
+            return Ok(NumericValue::Unit(UnitValue::new(product, unit)));
+        }
+        Self::check_types(&values, NumericType::multiply)?;
+        Ok(NumericValue::Math(MathValue::Product(values.into())))
+    }
+
+    /// Returns the quotient of this value and `values`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-div
+    pub fn div<V: Clone + Into<NumericValue>>(&self, values: &[V]) -> Result<NumericValue, NumericError> {
+        let values = values.iter().map(|value| value.clone().into().invert()).collect::<Result<Vec<_>, _>>()?;
+        self.mul(&values)
+    }
+
+    /// Returns the minimum of this value and `values`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-min
+    pub fn min<V: Clone + Into<NumericValue>>(&self, values: &[V]) -> Result<NumericValue, NumericError> {
+        let values = self.prepend_to(values, |math| match *math {
+            MathValue::Min(ref values) => Some(&values[..]),
+            _ => None,
+        });
+        if let Some((unit, values)) = Self::same_unit_values(&values) {
+            return Ok(NumericValue::Unit(UnitValue::new(values.fold(f64::INFINITY, f64::min), unit)));
+        }
+        Self::check_types(&values, NumericType::add)?;
+        Ok(NumericValue::Math(MathValue::Min(values.into())))
+    }
+
+    /// Returns the maximum of this value and `values`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-max
+    pub fn max<V: Clone + Into<NumericValue>>(&self, values: &[V]) -> Result<NumericValue, NumericError> {
+        let values = self.prepend_to(values, |math| match *math {
+            MathValue::Max(ref values) => Some(&values[..]),
+            _ => None,
+        });
+        if let Some((unit, values)) = Self::same_unit_values(&values) {
+            return Ok(NumericValue::Unit(UnitValue::new(values.fold(f64::NEG_INFINITY, f64::max), unit)));
+        }
+        Self::check_types(&values, NumericType::add)?;
+        Ok(NumericValue::Math(MathValue::Max(values.into())))
+    }
+
+    /// Returns the negation of this value.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmath-negate
+    pub fn negate(&self) -> NumericValue {
+        match *self {
+            NumericValue::Math(MathValue::Negate(ref value)) => (**value).clone(),
+            NumericValue::Unit(ref value) => NumericValue::Unit(UnitValue::new(-value.value, value.unit)),
+            NumericValue::Math(..) => NumericValue::Math(MathValue::Negate(Box::new(self.clone()))),
+        }
+    }
+
+    /// Returns the inverse of this value, failing if it's the number zero.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmath-invert
+    pub fn invert(&self) -> Result<NumericValue, NumericError> {
+        Ok(match *self {
+            NumericValue::Math(MathValue::Invert(ref value)) => (**value).clone(),
+            NumericValue::Unit(ref value) if value.unit == CssUnit::Number => {
+                if value.value == 0. {
+                    return Err(NumericError::DivisionByZero);
+                }
+                NumericValue::Unit(UnitValue::new(1. / value.value, CssUnit::Number))
+            }
+            _ => NumericValue::Math(MathValue::Invert(Box::new(self.clone()))),
+        })
+    }
+
+    fn write<W: fmt::Write>(&self, nested: bool, paren_less: bool, dest: &mut W) -> fmt::Result {
+        match *self {
+            NumericValue::Unit(ref value) => value.to_css(dest),
+            NumericValue::Math(ref value) => value.write(nested, paren_less, dest),
+        }
+    }
+
+    /// https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssnumericvalue
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        self.write(false, false, dest)
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+#[test]
+fn numeric_test() {
+    use crate::units::{LengthUnit, TimeUnit};
+
+    let px = |value| NumericValue::Unit(UnitValue::new(value, CssUnit::Length(LengthUnit::Px)));
+    let em = |value| NumericValue::Unit(UnitValue::new(value, CssUnit::Length(LengthUnit::Em)));
+    let number = |value| NumericValue::Unit(UnitValue::new(value, CssUnit::Number));
+    let percent = |value| NumericValue::Unit(UnitValue::new(value, CssUnit::Percent));
+    let s = NumericValue::Unit(UnitValue::new(1., CssUnit::Time(TimeUnit::S)));
+
+    assert_eq!(px(1.).add(&[px(2.), px(3.)]), Ok(px(6.)));
+    assert_eq!(px(1.).sub(&[px(3.)]), Ok(px(-2.)));
+    assert_eq!(number(2.).mul(&[px(3.), number(4.)]), Ok(px(24.)));
+    assert_eq!(px(6.).div(&[number(4.)]), Ok(px(1.5)));
+    assert_eq!(px(1.).min(&[px(-2.)]), Ok(px(-2.)));
+    assert_eq!(px(1.).max(&[px(-2.)]), Ok(px(1.)));
+    assert_eq!(px(1.).add(std::slice::from_ref(&s)), Err(NumericError::IncompatibleTypes));
+    assert_eq!(px(1.).min(std::slice::from_ref(&s)), Err(NumericError::IncompatibleTypes));
+    assert_eq!(px(1.).div(&[number(0.)]), Err(NumericError::DivisionByZero));
+
+    let sum = px(1.).add(&[em(2.)]).unwrap();
+    assert_eq!(sum.to_css_string(), "calc(1px + 2em)");
+    assert_eq!(sum.add(&[percent(50.)]).unwrap().to_css_string(), "calc(1px + 2em + 50%)");
+    assert_eq!(sum.sub(std::slice::from_ref(&sum)).unwrap().to_css_string(), "calc(1px + 2em - (1px + 2em))");
+    assert_eq!(sum.mul(&[number(2.)]).unwrap().to_css_string(), "calc((1px + 2em) * 2)");
+    assert_eq!(sum.div(&[em(1.)]).unwrap().to_css_string(), "calc((1px + 2em) / 1em)");
+    assert_eq!(px(1.).mul(&[px(2.)]).unwrap().to_css_string(), "calc(1px * 2px)");
+    assert_eq!(sum.negate().to_css_string(), "calc(-(1px + 2em))");
+    assert_eq!(sum.negate().negate(), sum);
+    assert_eq!(px(1.).max(&[em(1.), sum.clone()]).unwrap().to_css_string(), "max(1px, 1em, 1px + 2em)");
+    // Percentages can resolve against anything.
+    assert!(percent(10.).add(std::slice::from_ref(&s)).is_ok());
+    assert_eq!(percent(10.).add(&[s.clone(), px(1.)]), Err(NumericError::IncompatibleTypes));
+    // Lengths times lengths can't be added to lengths.
+    let area = px(1.).mul(&[em(2.)]).unwrap();
+    assert_eq!(area.add(&[px(1.)]), Err(NumericError::IncompatibleTypes));
+    assert!(area.div(&[px(1.)]).unwrap().add(&[px(1.)]).is_ok());
+
+    // Plain numbers are rectified into numbers.
+    assert_eq!(NumericValue::from(2.), number(2.));
+    assert_eq!(px(3.).mul(&[2., 0.5]), Ok(px(3.)));
+    assert_eq!(number(1.).add(&[2.]), Ok(number(3.)));
+    assert_eq!(px(1.).sub(&[1.]), Err(NumericError::IncompatibleTypes));
+    assert_eq!(px(1.).div(&[0.]), Err(NumericError::DivisionByZero));
+    let array = NumericArray::new(vec![1., 2.]);
+    assert_eq!(array.len(), 2);
+    assert_eq!(array[1], number(2.));
+    assert_eq!(number(0.).max(&array).unwrap(), number(2.));
+    match sum {
+        NumericValue::Math(MathValue::Sum(ref values)) => assert_eq!(&values[..], [px(1.), em(2.)]),
+        ref value => panic!("{:?}", value),
+    }
+
+    fn from_calc(value: &str) -> Option<NumericValue> {
+        let mut input = cssparser::ParserInput::new(value);
+        let mut input = cssparser::Parser::new(&mut input);
+        let name = input.expect_function().unwrap().clone();
+        NumericValue::from_calc(&CalcNode::parse_function(&name, &mut input).unwrap())
+    }
+
+    assert_eq!(
+        from_calc("calc(1px - 2em)"),
+        Some(NumericValue::Math(MathValue::Sum(NumericArray::new(vec![
+            px(1.),
+            NumericValue::Math(MathValue::Negate(Box::new(em(2.)))),
+        ])))),
+    );
+    for &(value, serialization) in &[
+        ("calc(1px - 2em)", "calc(1px - 2em)"),
+        ("calc(50% + 1px * 3)", "calc(50% + (1px * 3))"),
+        ("calc((1px + 2px) / 3)", "calc((1px + 2px) / 3)"),
+        ("min(1px, 2em + 3%)", "min(1px, 2em + 3%)"),
+        ("clamp(1PX, 10%, 1vw)", "clamp(1px, 10%, 1vw)"),
+        ("calc(max(1px, 2px) * 2)", "calc(max(1px, 2px) * 2)"),
+    ] {
+        assert_eq!(from_calc(value).unwrap().to_css_string(), serialization, "{}", value);
+    }
+    assert_eq!(from_calc("round(1px, 2px)"), None);
+    assert_eq!(from_calc("calc(1px + abs(1px))"), None);
+    let clamp = from_calc("clamp(1px, 2px, 3px)").unwrap();
+    assert_eq!(clamp.add(&[s]), Err(NumericError::IncompatibleTypes));
+}
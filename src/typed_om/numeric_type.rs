@@ -0,0 +1,251 @@
+//! The types of numeric values, which say what they can be added to or
+//! multiplied by.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#numeric-typing
+
+use super::unit::CssUnit;
+
+/// The base types that the type of a numeric value has exponents for.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-base-type
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BaseType {
+    Length,
+    Angle,
+    Time,
+    Frequency,
+    Resolution,
+    Flex,
+    Percent,
+}
+
+impl BaseType {
+    pub const ALL: [BaseType; 7] = [
+        BaseType::Length,
+        BaseType::Angle,
+        BaseType::Time,
+        BaseType::Frequency,
+        BaseType::Resolution,
+        BaseType::Flex,
+        BaseType::Percent,
+    ];
+
+    /// Returns the name of the base type, as in `CSSNumericType`.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            BaseType::Length => "length",
+            BaseType::Angle => "angle",
+            BaseType::Time => "time",
+            BaseType::Frequency => "frequency",
+            BaseType::Resolution => "resolution",
+            BaseType::Flex => "flex",
+            BaseType::Percent => "percent",
+        }
+    }
+}
+
+/// The type of a numeric value, like `CSSNumericType`: the exponent of each
+/// base type, and what percentages in it resolve against, if known.
+///
+/// The default type is the type of a plain number, with no non-zero
+/// exponents.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#dictdef-cssnumerictype
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct NumericType {
+    exponents: [i32; 7],
+    percent_hint: Option<BaseType>,
+}
+
+impl NumericType {
+    /// Returns the type of values of `unit`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-create-a-type
+    pub fn of_unit(unit: CssUnit) -> Self {
+        let base = match unit {
+            CssUnit::Number => return Self::default(),
+            CssUnit::Percent => BaseType::Percent,
+            CssUnit::Length(..) => BaseType::Length,
+            CssUnit::Angle(..) => BaseType::Angle,
+            CssUnit::Time(..) => BaseType::Time,
+            CssUnit::Frequency(..) => BaseType::Frequency,
+            CssUnit::Resolution(..) => BaseType::Resolution,
+            CssUnit::Flex => BaseType::Flex,
+        };
+        let mut ty = Self::default();
+        ty.exponents[base as usize] = 1;
+        ty
+    }
+
+    /// Returns the exponent of `base` in this type.
+    pub fn exponent(&self, base: BaseType) -> i32 {
+        self.exponents[base as usize]
+    }
+
+    pub fn length(&self) -> i32 {
+        self.exponent(BaseType::Length)
+    }
+
+    pub fn angle(&self) -> i32 {
+        self.exponent(BaseType::Angle)
+    }
+
+    pub fn time(&self) -> i32 {
+        self.exponent(BaseType::Time)
+    }
+
+    pub fn frequency(&self) -> i32 {
+        self.exponent(BaseType::Frequency)
+    }
+
+    pub fn resolution(&self) -> i32 {
+        self.exponent(BaseType::Resolution)
+    }
+
+    pub fn flex(&self) -> i32 {
+        self.exponent(BaseType::Flex)
+    }
+
+    pub fn percent(&self) -> i32 {
+        self.exponent(BaseType::Percent)
+    }
+
+    /// Returns the base type that percentages in values of this type resolve
+    /// against, if it's known because they were added to something of that
+    /// type.
+    pub fn percent_hint(&self) -> Option<BaseType> {
+        self.percent_hint
+    }
+
+    /// Returns whether this is the type of a plain number.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-match
+    pub fn is_number(&self) -> bool {
+        self.exponents.iter().all(|&exponent| exponent == 0) && self.percent_hint.is_none()
+    }
+
+    /// Returns whether this type matches `base`: its only non-zero exponent
+    /// is `base` with a value of one.
+    ///
+    /// If `percentages` is true, percentages resolving against `base` match
+    /// too, like for `<length-percentage>`. Otherwise the percent hint must
+    /// be unset.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-match
+    pub fn matches(&self, base: BaseType, percentages: bool) -> bool {
+        let hint_matches = match self.percent_hint {
+            None => true,
+            Some(hint) => percentages && hint == base,
+        };
+        hint_matches &&
+            BaseType::ALL
+                .iter()
+                .all(|&other| self.exponent(other) == if other == base { 1 } else { 0 })
+    }
+
+    /// https://drafts.css-houdini.org/css-typed-om-1/#apply-the-percent-hint
+    fn apply_percent_hint(&mut self, hint: BaseType) {
+        if hint != BaseType::Percent {
+            self.exponents[hint as usize] += self.exponents[BaseType::Percent as usize];
+            self.exponents[BaseType::Percent as usize] = 0;
+        }
+        self.percent_hint = Some(hint);
+    }
+
+    /// Applies the percent hints of `a` and `b` to each other, failing if
+    /// they're different.
+    fn reconcile_percent_hints(a: &mut Self, b: &mut Self) -> Option<()> {
+        match (a.percent_hint, b.percent_hint) {
+            (Some(x), Some(y)) if x != y => return None,
+            (Some(hint), None) => b.apply_percent_hint(hint),
+            (None, Some(hint)) => a.apply_percent_hint(hint),
+            _ => {}
+        }
+        Some(())
+    }
+
+    /// Returns the type of the sum of values of these types, if they can be
+    /// added.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-add-two-types
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, mut other: Self) -> Option<Self> {
+        Self::reconcile_percent_hints(&mut self, &mut other)?;
+        if self.exponents == other.exponents {
+            return Some(self);
+        }
+        let has_percent = |ty: &Self| ty.exponent(BaseType::Percent) != 0;
+        let has_other = |ty: &Self| {
+            BaseType::ALL.iter().any(|&base| base != BaseType::Percent && ty.exponent(base) != 0)
+        };
+        if !(has_percent(&self) || has_percent(&other)) || !(has_other(&self) || has_other(&other)) {
+            return None;
+        }
+        for &hint in &BaseType::ALL[..BaseType::ALL.len() - 1] {
+            let (mut a, mut b) = (self, other);
+            a.apply_percent_hint(hint);
+            b.apply_percent_hint(hint);
+            if a.exponents == b.exponents {
+                return Some(a);
+            }
+        }
+        None
+    }
+
+    /// Returns the type of the product of values of these types.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssnumericvalue-multiply-two-types
+    pub fn multiply(mut self, mut other: Self) -> Option<Self> {
+        Self::reconcile_percent_hints(&mut self, &mut other)?;
+        for (exponent, other) in self.exponents.iter_mut().zip(other.exponents.iter()) {
+            *exponent += other;
+        }
+        Some(self)
+    }
+
+    /// Returns the type of the inverse of values of this type.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmathinvert-type
+    pub fn invert(mut self) -> Self {
+        for exponent in &mut self.exponents {
+            *exponent = -*exponent;
+        }
+        self
+    }
+}
+
+#[test]
+fn numeric_type_test() {
+    use crate::units::{LengthUnit, TimeUnit};
+
+    let number = NumericType::default();
+    let length = NumericType::of_unit(CssUnit::Length(LengthUnit::Px));
+    let percent = NumericType::of_unit(CssUnit::Percent);
+    let time = NumericType::of_unit(CssUnit::Time(TimeUnit::S));
+
+    assert!(number.is_number());
+    assert!(length.matches(BaseType::Length, false));
+    assert!(percent.matches(BaseType::Percent, false));
+    assert_eq!(length.add(length), Some(length));
+    assert_eq!(length.add(time), None);
+    assert_eq!(number.add(length), None);
+
+    let length_percentage = length.add(percent).unwrap();
+    assert_eq!(length_percentage.length(), 1);
+    assert_eq!(length_percentage.percent(), 0);
+    assert_eq!(length_percentage.percent_hint(), Some(BaseType::Length));
+    assert!(length_percentage.matches(BaseType::Length, true));
+    assert!(!length_percentage.matches(BaseType::Length, false));
+    assert_eq!(length_percentage.add(time), None);
+    assert_eq!(percent.add(length_percentage), Some(length_percentage));
+
+    let area = length.multiply(length).unwrap();
+    assert_eq!(area.length(), 2);
+    assert!(!area.matches(BaseType::Length, false));
+    assert!(area.multiply(length.invert()).unwrap().matches(BaseType::Length, false));
+    assert!(length.multiply(length.invert()).unwrap().is_number());
+    assert_eq!(length.multiply(number), Some(length));
+    assert_eq!(time.invert().frequency(), 0);
+    assert_eq!(time.invert().time(), -1);
+    assert_eq!(BaseType::Resolution.as_str(), "resolution");
+}
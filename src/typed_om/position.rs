@@ -0,0 +1,75 @@
+//! Position values, behind the `experimental-position` feature.
+//!
+//! https://www.w3.org/TR/2016/WD-css-typed-om-1-20160607/#positionvalue-objects
+
+use std::fmt;
+
+use super::{numeric_value, CssUnit, NumericValue, UnitValue};
+use crate::position::{Position, PositionComponent, PositionKeyword};
+
+/// A `<position>` as offsets from the top left corner, like the
+/// `CSSPositionValue` of earlier drafts of the Typed OM.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionValue {
+    pub x: NumericValue,
+    pub y: NumericValue,
+}
+
+/// Returns the offset from the start of the axis of `component`, or `None`
+/// if it's a math function with no `MathValue` equivalent.
+fn offset(component: &PositionComponent) -> Option<NumericValue> {
+    let percent = |value: f64| NumericValue::Unit(UnitValue::new(value * 100., CssUnit::Percent));
+    let offset = match component.offset {
+        Some(ref offset) => numeric_value(offset)?,
+        None => return Some(percent(component.keyword?.to_percentage())),
+    };
+    match component.keyword {
+        Some(PositionKeyword::Right) | Some(PositionKeyword::Bottom) => percent(1.).sub(&[offset]).ok(),
+        _ => Some(offset),
+    }
+}
+
+impl PositionValue {
+    /// Returns the offsets of a matched position, with `right` and `bottom`
+    /// offsets subtracted from `100%`, or `None` if one of them can't be
+    /// reified.
+    pub fn from_position(position: &Position) -> Option<Self> {
+        Some(Self { x: offset(&position.horizontal)?, y: offset(&position.vertical)? })
+    }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        self.x.to_css(dest)?;
+        dest.write_char(' ')?;
+        self.y.to_css(dest)
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+#[test]
+fn position_value_test() {
+    use crate::value::MatchedValue;
+
+    fn reify(value: &str) -> Option<PositionValue> {
+        let descriptor = crate::parse_descriptor("<position>").unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        match descriptor.parse_value(&mut cssparser::Parser::new(&mut input)).unwrap() {
+            MatchedValue::Position(ref position) => PositionValue::from_position(position),
+            value => panic!("{:?}", value),
+        }
+    }
+
+    for &(value, serialization) in &[
+        ("center", "50% 50%"),
+        ("bottom left", "0% 100%"),
+        ("10px", "10px 50%"),
+        ("right 10px top 5%", "calc(100% + -10px) 5%"),
+        ("left calc(1em + 10%) bottom 0", "calc(1em + 10%) calc(100% + 0px)"),
+    ] {
+        assert_eq!(reify(value).unwrap().to_css_string(), serialization, "{:?}", value);
+    }
+}
@@ -0,0 +1,254 @@
+//! A typed store of custom property values, like `StylePropertyMap`.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#the-stylepropertymap
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::BuildHasher;
+
+use super::{reify, StyleValue};
+use crate::matching::MatchError;
+use crate::value::MatchedValue;
+use crate::{DefaultImpl, Descriptor};
+
+/// The syntaxes of the registered custom properties, which the values of a
+/// `StylePropertyMap` are validated against.
+pub trait PropertySyntaxes {
+    /// Returns the syntax of the custom property `name`, including the
+    /// leading `--`, or `None` if it's not registered.
+    fn syntax(&self, name: &str) -> Option<&Descriptor<DefaultImpl>>;
+}
+
+impl<S: BuildHasher> PropertySyntaxes for HashMap<String, Descriptor<DefaultImpl>, S> {
+    fn syntax(&self, name: &str) -> Option<&Descriptor<DefaultImpl>> {
+        self.get(name)
+    }
+}
+
+/// Why values can't be set on a `StylePropertyMap`. These are all a
+/// `TypeError` in the Typed OM.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyMapError {
+    /// The property is not a custom property.
+    InvalidName,
+    /// More than one value was given for a property whose syntax has no
+    /// multiplier.
+    NotListValued,
+    /// The values don't match the syntax of the property.
+    Mismatch(MatchError),
+}
+
+impl fmt::Display for PropertyMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PropertyMapError::InvalidName => f.write_str("not a custom property name"),
+            PropertyMapError::NotListValued => f.write_str("property doesn't take a list of values"),
+            PropertyMapError::Mismatch(ref error) => write!(f, "value doesn't match the syntax: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PropertyMapError {}
+
+/// The values of custom properties, validated against their registered
+/// syntax, or the universal syntax descriptor for unregistered ones.
+///
+/// Values are kept as matched, and reified when they're read.
+#[derive(Clone)]
+pub struct StylePropertyMap<'a> {
+    syntaxes: &'a dyn PropertySyntaxes,
+    declarations: BTreeMap<Box<str>, MatchedValue>,
+}
+
+impl<'a> StylePropertyMap<'a> {
+    pub fn new(syntaxes: &'a dyn PropertySyntaxes) -> Self {
+        Self { syntaxes, declarations: BTreeMap::new() }
+    }
+
+    /// Matches `values` against the syntax of `name`. More than one value
+    /// is only accepted if the syntax has a component with a multiplier, in
+    /// which case they're matched as a list separated per each multiplier
+    /// in turn.
+    fn match_values(&self, name: &str, values: &[StyleValue]) -> Result<MatchedValue, PropertyMapError> {
+        if !name.starts_with("--") {
+            return Err(PropertyMapError::InvalidName);
+        }
+        let universal = Descriptor::universal();
+        let descriptor = self.syntaxes.syntax(name).unwrap_or(&universal);
+        let parse = |source: &str| {
+            let mut input = cssparser::ParserInput::new(source);
+            descriptor.parse_value(&mut cssparser::Parser::new(&mut input))
+        };
+        if values.len() <= 1 {
+            let source = values.first().map_or_else(String::new, StyleValue::to_css_string);
+            return parse(&source).map_err(PropertyMapError::Mismatch);
+        }
+        let values = values.iter().map(StyleValue::to_css_string).collect::<Vec<_>>();
+        let mut result = Err(PropertyMapError::NotListValued);
+        for multiplier in descriptor.iter().filter_map(|component| component.multiplier()) {
+            match parse(&values.join(multiplier.separator())) {
+                Ok(value) => return Ok(value),
+                Err(error) => result = Err(PropertyMapError::Mismatch(error)),
+            }
+        }
+        result
+    }
+
+    /// Sets the value of the custom property `name`, replacing any previous
+    /// value.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-set
+    pub fn set(&mut self, name: &str, values: &[StyleValue]) -> Result<(), PropertyMapError> {
+        let value = self.match_values(name, values)?;
+        self.declarations.insert(name.into(), value);
+        Ok(())
+    }
+
+    /// Appends `values` to the list of values of the custom property
+    /// `name`, which must take a list.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-append
+    pub fn append(&mut self, name: &str, values: &[StyleValue]) -> Result<(), PropertyMapError> {
+        let takes_list = self
+            .syntaxes
+            .syntax(name)
+            .is_some_and(|descriptor| descriptor.iter().any(|component| component.multiplier().is_some()));
+        if name.starts_with("--") && !takes_list {
+            return Err(PropertyMapError::NotListValued);
+        }
+        let mut all = self.get_all(name);
+        all.extend_from_slice(values);
+        self.set(name, &all)
+    }
+
+    /// Removes the value of the custom property `name`, returning whether it
+    /// had one.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-delete
+    pub fn delete(&mut self, name: &str) -> bool {
+        self.declarations.remove(name).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.declarations.clear();
+    }
+
+    /// Returns the first value of the custom property `name`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-get
+    pub fn get(&self, name: &str) -> Option<StyleValue> {
+        self.get_all(name).into_iter().next()
+    }
+
+    /// Returns the values of the custom property `name`, which is more than
+    /// one only for lists.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-getall
+    pub fn get_all(&self, name: &str) -> Vec<StyleValue> {
+        self.declarations.get(name).map_or_else(Vec::new, reify)
+    }
+
+    /// Returns the matched value of the custom property `name`.
+    pub fn get_matched(&self, name: &str) -> Option<&MatchedValue> {
+        self.declarations.get(name)
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.declarations.contains_key(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.declarations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.declarations.is_empty()
+    }
+
+    /// Returns the names of the properties with a value and their values,
+    /// in code point order of the names.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Vec<StyleValue>)> + '_ {
+        self.declarations.iter().map(|(name, value)| (&**name, reify(value)))
+    }
+
+    /// Serializes the values as a declaration block, like
+    /// `--a: 1px; --b: red;`.
+    ///
+    /// https://drafts.csswg.org/cssom-1/#serialize-a-css-declaration-block
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        for (i, (name, value)) in self.declarations.iter().enumerate() {
+            if i > 0 {
+                dest.write_char(' ')?;
+            }
+            cssparser::serialize_identifier(name, dest)?;
+            dest.write_str(": ")?;
+            value.to_css(dest)?;
+            dest.write_char(';')?;
+        }
+        Ok(())
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+impl<'a> fmt::Debug for StylePropertyMap<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.declarations.iter()).finish()
+    }
+}
+
+#[test]
+fn style_property_map_test() {
+    use super::{CssUnit, KeywordValue, NumericValue, UnitValue};
+    use crate::units::LengthUnit;
+
+    let px = |value: f64| {
+        StyleValue::Numeric(NumericValue::Unit(UnitValue::new(value, CssUnit::Length(LengthUnit::Px))))
+    };
+    let keyword = |value: &str| StyleValue::Keyword(KeywordValue::new(value).unwrap());
+
+    let mut syntaxes = HashMap::new();
+    for &(name, syntax) in &[
+        ("--length", "<length> | auto"),
+        ("--list", "<length>#"),
+        ("--idents", "<custom-ident>+"),
+    ] {
+        syntaxes.insert(name.to_owned(), crate::parse_descriptor(syntax).unwrap());
+    }
+    let mut map = StylePropertyMap::new(&syntaxes);
+    assert!(map.is_empty());
+
+    map.set("--length", &[px(10.)]).unwrap();
+    map.set("--length", &[keyword("AUTO")]).unwrap();
+    assert_eq!(map.get("--length"), Some(keyword("auto")));
+    assert!(matches!(map.set("--length", &[keyword("none")]), Err(PropertyMapError::Mismatch(..))));
+    assert_eq!(map.set("--length", &[px(1.), px(2.)]), Err(PropertyMapError::NotListValued));
+    assert_eq!(map.append("--length", &[px(1.)]), Err(PropertyMapError::NotListValued));
+    assert_eq!(map.set("length", &[px(1.)]), Err(PropertyMapError::InvalidName));
+
+    map.set("--list", &[px(1.), px(2.)]).unwrap();
+    map.append("--list", &[px(3.)]).unwrap();
+    assert_eq!(map.get_all("--list"), [px(1.), px(2.), px(3.)]);
+    map.append("--idents", &[keyword("a"), keyword("b")]).unwrap();
+    assert!(map.append("--idents", &[px(1.)]).is_err());
+    assert_eq!(map.get_all("--idents").len(), 2);
+
+    // Unregistered properties take anything.
+    assert_eq!(map.set("--other", &[px(1.), keyword("b")]), Err(PropertyMapError::NotListValued));
+    map.set("--other", &[keyword("b")]).unwrap();
+    assert!(matches!(map.get("--other"), Some(StyleValue::Unparsed(..))));
+    assert!(map.has("--other"));
+
+    assert_eq!(map.len(), 4);
+    let names = map.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    assert_eq!(names, ["--idents", "--length", "--list", "--other"]);
+    assert_eq!(map.to_css_string(), "--idents: a b; --length: auto; --list: 1px, 2px, 3px; --other: b;");
+    assert!(map.delete("--other"));
+    assert!(!map.delete("--other"));
+    map.clear();
+    assert_eq!(map.to_css_string(), "");
+}
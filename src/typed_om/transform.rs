@@ -0,0 +1,442 @@
+//! Transform values.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#transformvalue-objects
+
+use std::fmt;
+
+use super::matrix::Matrix;
+use super::numeric::{NumericError, NumericValue, UnitValue};
+use super::numeric_value;
+use super::unit::CssUnit;
+use crate::compute::ComputeContext;
+use crate::matching::round_to_integer;
+use crate::transform::{TransformFunction, TransformFunctionName};
+use crate::units::{AngleUnit, LengthUnit};
+use crate::value::MatchedValue;
+
+/// A single transform function, like `CSSTransformComponent` and its
+/// subclasses.
+///
+/// Lengths may be percentages, and the arguments of `rotate()` and
+/// `scale()` other than the angle are numbers.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#csstransformcomponent
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransformComponent {
+    /// https://drafts.css-houdini.org/css-typed-om-1/#csstranslate
+    Translate {
+        x: NumericValue,
+        y: NumericValue,
+        z: NumericValue,
+        is_2d: bool,
+    },
+    /// A rotation of `angle` around the vector `(x, y, z)`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssrotate
+    Rotate {
+        x: NumericValue,
+        y: NumericValue,
+        z: NumericValue,
+        angle: NumericValue,
+        is_2d: bool,
+    },
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssscale
+    Scale {
+        x: NumericValue,
+        y: NumericValue,
+        z: NumericValue,
+        is_2d: bool,
+    },
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssskew
+    Skew { ax: NumericValue, ay: NumericValue },
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssskewx
+    SkewX { ax: NumericValue },
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssskewy
+    SkewY { ay: NumericValue },
+    /// A perspective with the given length, or `none`.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssperspective
+    Perspective { length: Option<NumericValue> },
+    /// https://drafts.css-houdini.org/css-typed-om-1/#cssmatrixcomponent
+    Matrix(Matrix),
+}
+
+fn number(value: f64) -> NumericValue {
+    NumericValue::Unit(UnitValue::new(value, CssUnit::Number))
+}
+
+fn px(value: f64) -> NumericValue {
+    NumericValue::Unit(UnitValue::new(value, CssUnit::Length(LengthUnit::Px)))
+}
+
+/// Returns whether `value` is a unit value of zero, which is omitted from
+/// the serialization of some components.
+fn is_zero(value: &NumericValue) -> bool {
+    matches!(*value, NumericValue::Unit(ref value) if value.value == 0.)
+}
+
+/// Returns the value of a matched number, which may be a math function.
+fn to_number(value: &MatchedValue) -> Option<f64> {
+    match *value {
+        MatchedValue::Number(value) => Some(value),
+        MatchedValue::Integer(value) => Some(value as f64),
+        MatchedValue::Calc(ref node) => node.to_number(),
+        MatchedValue::IntegerCalc(ref node) => node.to_number().map(|value| round_to_integer(value) as f64),
+        _ => None,
+    }
+}
+
+/// Returns the argument of `scale()` and related functions as a number,
+/// which percentages are equivalent to.
+fn scale_factor(value: &MatchedValue) -> Option<NumericValue> {
+    match *value {
+        MatchedValue::Percentage(value) => Some(number(value)),
+        ref value => numeric_value(value),
+    }
+}
+
+impl TransformComponent {
+    /// Returns the component for a matched transform function, or `None` if
+    /// any of its arguments is a math function that can't be reified.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#reify-a-transform-list
+    pub fn from_function(function: &TransformFunction) -> Option<Self> {
+        use self::TransformFunctionName::*;
+
+        let arguments = &function.arguments;
+        let numeric = |i: usize| numeric_value(&arguments[i]);
+        let optional = |i: usize, default: NumericValue| match arguments.get(i) {
+            Some(argument) => numeric_value(argument),
+            None => Some(default),
+        };
+        let translate = |x, y, z, is_2d| TransformComponent::Translate { x, y, z, is_2d };
+        let scale = |x, y, z, is_2d| TransformComponent::Scale { x, y, z, is_2d };
+        let scale_factor = |i: usize| scale_factor(&arguments[i]);
+        let rotate = |x, y, z, is_2d| {
+            let angle = numeric(0)?;
+            Some(TransformComponent::Rotate { x: number(x), y: number(y), z: number(z), angle, is_2d })
+        };
+        Some(match function.name {
+            Matrix => {
+                let mut entries = [0.; 6];
+                for (entry, argument) in entries.iter_mut().zip(arguments) {
+                    *entry = to_number(argument)?;
+                }
+                let [a, b, c, d, e, f] = entries;
+                TransformComponent::Matrix(self::Matrix::from_2d(a, b, c, d, e, f))
+            }
+            Matrix3d => {
+                let mut entries = [0.; 16];
+                for (entry, argument) in entries.iter_mut().zip(arguments) {
+                    *entry = to_number(argument)?;
+                }
+                TransformComponent::Matrix(self::Matrix::from_3d(entries))
+            }
+            Translate => translate(numeric(0)?, optional(1, px(0.))?, px(0.), true),
+            TranslateX => translate(numeric(0)?, px(0.), px(0.), true),
+            TranslateY => translate(px(0.), numeric(0)?, px(0.), true),
+            TranslateZ => translate(px(0.), px(0.), numeric(0)?, false),
+            Translate3d => translate(numeric(0)?, numeric(1)?, numeric(2)?, false),
+            Scale => {
+                let x = scale_factor(0)?;
+                let y = match arguments.get(1) {
+                    Some(..) => scale_factor(1)?,
+                    None => x.clone(),
+                };
+                scale(x, y, number(1.), true)
+            }
+            ScaleX => scale(scale_factor(0)?, number(1.), number(1.), true),
+            ScaleY => scale(number(1.), scale_factor(0)?, number(1.), true),
+            ScaleZ => scale(number(1.), number(1.), scale_factor(0)?, false),
+            Scale3d => scale(scale_factor(0)?, scale_factor(1)?, scale_factor(2)?, false),
+            Rotate => rotate(0., 0., 1., true)?,
+            RotateX => rotate(1., 0., 0., false)?,
+            RotateY => rotate(0., 1., 0., false)?,
+            RotateZ => rotate(0., 0., 1., false)?,
+            Rotate3d => TransformComponent::Rotate {
+                x: numeric(0)?,
+                y: numeric(1)?,
+                z: numeric(2)?,
+                angle: numeric(3)?,
+                is_2d: false,
+            },
+            Skew => TransformComponent::Skew {
+                ax: numeric(0)?,
+                ay: optional(1, NumericValue::Unit(UnitValue::new(0., CssUnit::Angle(AngleUnit::Deg))))?,
+            },
+            SkewX => TransformComponent::SkewX { ax: numeric(0)? },
+            SkewY => TransformComponent::SkewY { ay: numeric(0)? },
+            Perspective => TransformComponent::Perspective {
+                length: match arguments.first() {
+                    Some(length) => Some(numeric_value(length)?),
+                    None => None,
+                },
+            },
+        })
+    }
+
+    /// Returns whether the component is a 2D transform, like
+    /// `CSSTransformComponent.is2D`. Skews always are, and perspectives
+    /// never are.
+    pub fn is_2d(&self) -> bool {
+        match *self {
+            TransformComponent::Translate { is_2d, .. } |
+            TransformComponent::Rotate { is_2d, .. } |
+            TransformComponent::Scale { is_2d, .. } => is_2d,
+            TransformComponent::Skew { .. } |
+            TransformComponent::SkewX { .. } |
+            TransformComponent::SkewY { .. } => true,
+            TransformComponent::Perspective { .. } => false,
+            TransformComponent::Matrix(ref matrix) => matrix.is_2d,
+        }
+    }
+
+    /// Returns the matrix that this component represents, like
+    /// `CSSTransformComponent.toMatrix()`, resolving relative lengths against
+    /// `context`.
+    ///
+    /// Fails if any of the values is of the wrong type, or contains a
+    /// percentage, which can't be resolved without a reference box.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformcomponent-tomatrix
+    pub fn to_matrix(&self, context: &dyn ComputeContext) -> Result<Matrix, NumericError> {
+        let to = |value: &NumericValue, unit| -> Result<f64, NumericError> {
+            Ok(value.absolutize(context).to(unit)?.value)
+        };
+        let px = |value| to(value, CssUnit::Length(LengthUnit::Px));
+        let deg = |value| to(value, CssUnit::Angle(AngleUnit::Deg));
+        let number = |value| to(value, CssUnit::Number);
+        let mut matrix = match *self {
+            TransformComponent::Translate { ref x, ref y, ref z, is_2d } => {
+                let z = if is_2d { 0. } else { px(z)? };
+                Matrix::translate(px(x)?, px(y)?, z)
+            }
+            TransformComponent::Rotate { ref x, ref y, ref z, ref angle, is_2d } => {
+                if is_2d {
+                    Matrix::rotate(0., 0., 1., deg(angle)?)
+                } else {
+                    Matrix::rotate(number(x)?, number(y)?, number(z)?, deg(angle)?)
+                }
+            }
+            TransformComponent::Scale { ref x, ref y, ref z, is_2d } => {
+                let z = if is_2d { 1. } else { number(z)? };
+                Matrix::scale(number(x)?, number(y)?, z)
+            }
+            TransformComponent::Skew { ref ax, ref ay } => Matrix::skew(deg(ax)?, deg(ay)?),
+            TransformComponent::SkewX { ref ax } => Matrix::skew(deg(ax)?, 0.),
+            TransformComponent::SkewY { ref ay } => Matrix::skew(0., deg(ay)?),
+            TransformComponent::Perspective { ref length } => match *length {
+                Some(ref length) => Matrix::perspective(Some(px(length)?)),
+                None => Matrix::perspective(None),
+            },
+            TransformComponent::Matrix(matrix) => matrix,
+        };
+        matrix.is_2d = self.is_2d();
+        Ok(matrix)
+    }
+
+    /// https://drafts.css-houdini.org/css-typed-om-1/#transformvalue-serialization
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        let mut write_function = |name: &str, arguments: &[&NumericValue]| -> fmt::Result {
+            dest.write_str(name)?;
+            dest.write_char('(')?;
+            for (i, argument) in arguments.iter().enumerate() {
+                if i > 0 {
+                    dest.write_str(", ")?;
+                }
+                argument.to_css(dest)?;
+            }
+            dest.write_char(')')
+        };
+        match *self {
+            TransformComponent::Translate { ref x, ref y, ref z, is_2d } => {
+                if is_2d {
+                    write_function("translate", &[x, y])
+                } else {
+                    write_function("translate3d", &[x, y, z])
+                }
+            }
+            TransformComponent::Rotate { ref x, ref y, ref z, ref angle, is_2d } => {
+                if is_2d {
+                    write_function("rotate", &[angle])
+                } else {
+                    write_function("rotate3d", &[x, y, z, angle])
+                }
+            }
+            TransformComponent::Scale { ref x, ref y, ref z, is_2d } => {
+                if is_2d {
+                    write_function("scale", &[x, y])
+                } else {
+                    write_function("scale3d", &[x, y, z])
+                }
+            }
+            TransformComponent::Skew { ref ax, ref ay } => {
+                if is_zero(ay) {
+                    write_function("skew", &[ax])
+                } else {
+                    write_function("skew", &[ax, ay])
+                }
+            }
+            TransformComponent::SkewX { ref ax } => write_function("skewX", &[ax]),
+            TransformComponent::SkewY { ref ay } => write_function("skewY", &[ay]),
+            TransformComponent::Perspective { ref length } => match *length {
+                Some(ref length) => write_function("perspective", &[length]),
+                None => dest.write_str("perspective(none)"),
+            },
+            TransformComponent::Matrix(ref matrix) => matrix.to_css(dest),
+        }
+    }
+}
+
+/// A list of transform components, like `CSSTransformValue`.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#csstransformvalue
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformValue {
+    /// The transform components, which are never empty.
+    pub components: Vec<TransformComponent>,
+}
+
+impl TransformValue {
+    /// Returns a transform value with the given components, or `None` if
+    /// there are none, which is a `TypeError` in the Typed OM.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformvalue-csstransformvalue
+    pub fn new(components: Vec<TransformComponent>) -> Option<Self> {
+        if components.is_empty() {
+            return None;
+        }
+        Some(Self { components })
+    }
+
+    /// Returns the transform value for matched transform functions, or
+    /// `None` if any of them can't be reified.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#reify-a-transform-list
+    pub fn from_functions(functions: &[TransformFunction]) -> Option<Self> {
+        Self::new(functions.iter().map(TransformComponent::from_function).collect::<Option<_>>()?)
+    }
+
+    /// Returns the product of the matrices of the components, like
+    /// `CSSTransformValue.toMatrix()`, resolving relative lengths against
+    /// `context`. The matrix is 2D if all the components are.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformvalue-tomatrix
+    pub fn to_matrix(&self, context: &dyn ComputeContext) -> Result<Matrix, NumericError> {
+        let mut matrix = Matrix::identity();
+        for component in &self.components {
+            matrix = matrix.multiply(&component.to_matrix(context)?);
+        }
+        Ok(matrix)
+    }
+
+    /// Returns whether all the components are 2D transforms, like
+    /// `CSSTransformValue.is2D`.
+    pub fn is_2d(&self) -> bool {
+        self.components.iter().all(TransformComponent::is_2d)
+    }
+
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            if i > 0 {
+                dest.write_char(' ')?;
+            }
+            component.to_css(dest)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+#[test]
+fn transform_value_test() {
+    fn parse(value: &str) -> TransformValue {
+        let descriptor = crate::parse_descriptor("<transform-list>").unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        match descriptor.parse_value(&mut cssparser::Parser::new(&mut input)).unwrap() {
+            MatchedValue::TransformList(ref functions) => TransformValue::from_functions(functions).unwrap(),
+            value => panic!("{:?}", value),
+        }
+    }
+
+    assert!(TransformValue::new(vec![]).is_none());
+    assert_eq!(
+        parse("translateX(10px)").components,
+        [TransformComponent::Translate { x: px(10.), y: px(0.), z: px(0.), is_2d: true }],
+    );
+    assert_eq!(
+        parse("scale(50%)").components,
+        [TransformComponent::Scale { x: number(0.5), y: number(0.5), z: number(1.), is_2d: true }],
+    );
+
+    for &(value, serialization, is_2d) in &[
+        ("translate(1px)", "translate(1px, 0px)", true),
+        ("translateY(50%)", "translate(0px, 50%)", true),
+        ("translateZ(1em)", "translate3d(0px, 0px, 1em)", false),
+        ("translate3d(1px, 2px, 3px)", "translate3d(1px, 2px, 3px)", false),
+        ("rotate(0)", "rotate(0deg)", true),
+        ("rotateX(1turn)", "rotate3d(1, 0, 0, 1turn)", false),
+        ("rotate3d(1, 2, 3, 45deg)", "rotate3d(1, 2, 3, 45deg)", false),
+        ("scaleY(2)", "scale(1, 2)", true),
+        ("scale3d(1, 2, 3)", "scale3d(1, 2, 3)", false),
+        ("skew(10deg)", "skew(10deg)", true),
+        ("skew(10deg, 20deg)", "skew(10deg, 20deg)", true),
+        ("skewY(1rad)", "skewY(1rad)", true),
+        ("perspective(none)", "perspective(none)", false),
+        ("perspective(10px)", "perspective(10px)", false),
+        ("matrix(1, 2, 3, 4, 5, calc(3 * 2))", "matrix(1, 2, 3, 4, 5, 6)", true),
+        (
+            "matrix3d(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 2)",
+            "matrix3d(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 2)",
+            false,
+        ),
+        ("translateX(calc(50% - 1em))", "translate(calc(50% - 1em), 0px)", true),
+        ("scale(2) rotate(45deg) skewX(5deg)", "scale(2, 2) rotate(45deg) skewX(5deg)", true),
+        ("rotate(45deg) translateZ(1px)", "rotate(45deg) translate3d(0px, 0px, 1px)", false),
+    ] {
+        let transform = parse(value);
+        assert_eq!(transform.to_css_string(), serialization, "{}", value);
+        assert_eq!(transform.is_2d(), is_2d, "{}", value);
+    }
+
+    let matrix = Matrix::from_2d(1., 2., 3., 4., 5., 6.);
+    assert_eq!((matrix.get(2, 1), matrix.get(4, 2)), (3., 6.));
+
+    struct Context;
+
+    impl ComputeContext for Context {
+        fn font_size(&self) -> f64 {
+            16.
+        }
+
+        fn root_font_size(&self) -> f64 {
+            10.
+        }
+
+        fn viewport_size(&self) -> (f64, f64) {
+            (800., 600.)
+        }
+    }
+
+    let to_matrix = |value: &str| parse(value).to_matrix(&Context);
+    assert_eq!(to_matrix("translate(10px, 1em)"), Ok(Matrix::from_2d(1., 0., 0., 1., 10., 16.)));
+    assert_eq!(to_matrix("translate(1in) scale(2)"), Ok(Matrix::from_2d(2., 0., 0., 2., 96., 0.)));
+    assert_eq!(
+        to_matrix("scale(2) translate(calc(1rem + 10vw))"),
+        Ok(Matrix::from_2d(2., 0., 0., 2., 180., 0.)),
+    );
+    assert_eq!(to_matrix("matrix(1, 2, 3, 4, 5, 6)"), Ok(Matrix::from_2d(1., 2., 3., 4., 5., 6.)));
+    assert_eq!(to_matrix("translate(50%)"), Err(NumericError::NotConvertible));
+    let rotated = to_matrix("rotate(0.25turn) skewX(0)").unwrap();
+    assert!(rotated.is_2d);
+    assert!((rotated.get(1, 2) - 1.).abs() < 1e-9 && rotated.get(1, 1).abs() < 1e-9);
+    let perspective = to_matrix("perspective(100px) translateZ(1px)").unwrap();
+    assert!(!perspective.is_2d);
+    assert_eq!((perspective.get(3, 4), perspective.get(4, 3), perspective.get(4, 4)), (-0.01, 1., 0.99));
+    assert!(!to_matrix("translate3d(1px, 2px, 0px)").unwrap().is_2d);
+}
@@ -0,0 +1,138 @@
+//! The units of numeric values.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#numeric-value
+
+use std::fmt;
+
+use crate::units::{AngleUnit, FrequencyUnit, LengthUnit, ResolutionUnit, TimeUnit};
+
+/// The unit of a `UnitValue`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CssUnit {
+    /// A plain number, spelled `number`.
+    Number,
+    /// A percentage, spelled `percent`.
+    Percent,
+    Length(LengthUnit),
+    Angle(AngleUnit),
+    Time(TimeUnit),
+    Frequency(FrequencyUnit),
+    Resolution(ResolutionUnit),
+    /// https://drafts.csswg.org/css-grid-2/#fr-unit
+    Flex,
+}
+
+impl CssUnit {
+    /// Returns the unit as spelled in the Typed OM, which is how it's
+    /// written in CSS except for `number` and `percent`.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            CssUnit::Number => "number",
+            CssUnit::Percent => "percent",
+            CssUnit::Length(unit) => unit.as_str(),
+            CssUnit::Angle(unit) => unit.as_str(),
+            CssUnit::Time(unit) => unit.as_str(),
+            CssUnit::Frequency(unit) => unit.as_str(),
+            CssUnit::Resolution(unit) => unit.as_str(),
+            CssUnit::Flex => "fr",
+        }
+    }
+
+    /// Returns the unit with the given Typed OM name, ASCII
+    /// case-insensitively. `%` is accepted as `percent` too.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-cssunitvalue
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(unit: &str) -> Option<Self> {
+        if unit.eq_ignore_ascii_case("number") {
+            return Some(CssUnit::Number);
+        }
+        if unit.eq_ignore_ascii_case("percent") || unit == "%" {
+            return Some(CssUnit::Percent);
+        }
+        if unit.eq_ignore_ascii_case("fr") {
+            return Some(CssUnit::Flex);
+        }
+        LengthUnit::from_str(unit)
+            .map(CssUnit::Length)
+            .or_else(|| AngleUnit::from_str(unit).map(CssUnit::Angle))
+            .or_else(|| TimeUnit::from_str(unit).map(CssUnit::Time))
+            .or_else(|| FrequencyUnit::from_str(unit).map(CssUnit::Frequency))
+            .or_else(|| ResolutionUnit::from_str(unit).map(CssUnit::Resolution))
+    }
+
+    /// Returns what's written after the number when serializing a value of
+    /// this unit.
+    pub fn suffix(&self) -> &'static str {
+        match *self {
+            CssUnit::Number => "",
+            CssUnit::Percent => "%",
+            _ => self.as_str(),
+        }
+    }
+
+    /// Returns the canonical unit of the type of this unit: `px` for
+    /// lengths, `deg` for angles, `s` for times, `hz` for frequencies and
+    /// `dppx` for resolutions. Numbers, percentages and flexes are their own
+    /// canonical unit.
+    ///
+    /// https://drafts.csswg.org/css-values-4/#canonical-unit
+    pub fn canonical(&self) -> Self {
+        match *self {
+            CssUnit::Number | CssUnit::Percent | CssUnit::Flex => *self,
+            CssUnit::Length(..) => CssUnit::Length(LengthUnit::Px),
+            CssUnit::Angle(..) => CssUnit::Angle(AngleUnit::CANONICAL),
+            CssUnit::Time(..) => CssUnit::Time(TimeUnit::CANONICAL),
+            CssUnit::Frequency(..) => CssUnit::Frequency(FrequencyUnit::CANONICAL),
+            CssUnit::Resolution(..) => CssUnit::Resolution(ResolutionUnit::CANONICAL),
+        }
+    }
+
+    /// Returns whether this is the canonical unit of its type.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical() == *self
+    }
+
+    /// Returns how many of the canonical unit one of this unit is, or `None`
+    /// for relative lengths, which can't be converted to `px` without
+    /// knowing what they're relative to.
+    pub fn to_canonical(&self) -> Option<f64> {
+        Some(match *self {
+            CssUnit::Number | CssUnit::Percent | CssUnit::Flex => 1.,
+            CssUnit::Length(unit) => unit.to_px()?,
+            CssUnit::Angle(unit) => unit.to_canonical(),
+            CssUnit::Time(unit) => unit.to_canonical(),
+            CssUnit::Frequency(unit) => unit.to_canonical(),
+            CssUnit::Resolution(unit) => unit.to_canonical(),
+        })
+    }
+}
+
+impl fmt::Display for CssUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[test]
+fn css_unit_test() {
+    assert_eq!(CssUnit::from_str("PX"), Some(CssUnit::Length(LengthUnit::Px)));
+    assert_eq!(CssUnit::from_str("%"), Some(CssUnit::Percent));
+    assert_eq!(CssUnit::from_str("Number"), Some(CssUnit::Number));
+    assert_eq!(CssUnit::from_str("kHz"), Some(CssUnit::Frequency(FrequencyUnit::Khz)));
+    assert_eq!(CssUnit::from_str("fr"), Some(CssUnit::Flex));
+    assert_eq!(CssUnit::from_str("x"), Some(CssUnit::Resolution(ResolutionUnit::X)));
+    assert_eq!(CssUnit::from_str("foo"), None);
+    assert_eq!(CssUnit::from_str(""), None);
+
+    assert_eq!(CssUnit::Length(LengthUnit::Em).canonical(), CssUnit::Length(LengthUnit::Px));
+    assert_eq!(CssUnit::Angle(AngleUnit::Turn).canonical(), CssUnit::Angle(AngleUnit::Deg));
+    assert_eq!(CssUnit::Time(TimeUnit::Ms).canonical(), CssUnit::Time(TimeUnit::S));
+    assert_eq!(CssUnit::Resolution(ResolutionUnit::X).canonical(), CssUnit::Resolution(ResolutionUnit::Dppx));
+    assert!(CssUnit::Percent.is_canonical());
+    assert!(!CssUnit::Frequency(FrequencyUnit::Khz).is_canonical());
+    assert_eq!(CssUnit::Length(LengthUnit::In).to_canonical(), Some(96.));
+    assert_eq!(CssUnit::Length(LengthUnit::Vw).to_canonical(), None);
+    assert_eq!(CssUnit::Percent.suffix(), "%");
+    assert_eq!(CssUnit::Number.to_string(), "number");
+}
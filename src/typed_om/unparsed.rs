@@ -0,0 +1,200 @@
+//! Unparsed values, and the `var()` references in them.
+//!
+//! https://drafts.css-houdini.org/css-typed-om-1/#unparsedvalue-objects
+
+use std::fmt;
+
+use cssparser::{Parser, Token};
+
+use crate::matching::Error;
+
+/// A part of an unparsed value.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum UnparsedSegment {
+    /// Tokens other than `var()` references, as written.
+    String(Box<str>),
+    Variable(VariableReferenceValue),
+}
+
+/// A value that's only known as a sequence of tokens, like
+/// `CSSUnparsedValue`: strings of tokens interleaved with the `var()`
+/// references in them.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#cssunparsedvalue
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UnparsedValue {
+    pub segments: Vec<UnparsedSegment>,
+}
+
+/// A `var()` reference, like `CSSVariableReferenceValue`.
+///
+/// https://drafts.css-houdini.org/css-typed-om-1/#cssvariablereferencevalue
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct VariableReferenceValue {
+    /// The name of the custom property, including the leading `--`.
+    pub variable: Box<str>,
+    pub fallback: Option<UnparsedValue>,
+}
+
+impl VariableReferenceValue {
+    /// Returns a reference to `variable`, or `None` if it's not a custom
+    /// property name, which is a `TypeError` in the Typed OM.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#dom-cssvariablereferencevalue-cssvariablereferencevalue
+    pub fn new(variable: &str, fallback: Option<UnparsedValue>) -> Option<Self> {
+        if !variable.starts_with("--") {
+            return None;
+        }
+        Some(Self { variable: variable.into(), fallback })
+    }
+
+    /// https://drafts.css-houdini.org/css-typed-om-1/#unparsedvalue-serialization
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_str("var(")?;
+        dest.write_str(&self.variable)?;
+        if let Some(ref fallback) = self.fallback {
+            dest.write_str(", ")?;
+            fallback.to_css(dest)?;
+        }
+        dest.write_str(")")
+    }
+
+    /// Parses the arguments of a `var()` function, whose opening token has
+    /// just been consumed.
+    fn parse_arguments<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, Error<'i>> {
+        let location = input.current_source_location();
+        let variable = input.expect_ident_cloned()?;
+        if !variable.starts_with("--") {
+            return Err(location.new_unexpected_token_error(Token::Ident(variable)));
+        }
+        let mut fallback = None;
+        if input.try_parse(|input| input.expect_comma()).is_ok() {
+            let start = input.position();
+            while input.next_including_whitespace_and_comments().is_ok() {}
+            fallback = Some(UnparsedValue::parse_str(input.slice_from(start).trim_start()));
+        }
+        input.expect_exhausted()?;
+        Ok(Self { variable: (*variable).into(), fallback })
+    }
+}
+
+/// Returns the closing token of a block opened by `token`, if it opens one.
+fn closing_token(token: &Token) -> Option<&'static str> {
+    Some(match *token {
+        Token::Function(..) | Token::ParenthesisBlock => ")",
+        Token::SquareBracketBlock => "]",
+        Token::CurlyBracketBlock => "}",
+        _ => return None,
+    })
+}
+
+impl UnparsedValue {
+    /// Splits a value into strings and the `var()` references in it,
+    /// including the ones nested in other functions and blocks. Malformed
+    /// references are kept as strings.
+    ///
+    /// https://drafts.css-houdini.org/css-typed-om-1/#reify-a-list-of-component-values
+    pub fn parse_str(value: &str) -> Self {
+        let mut input = cssparser::ParserInput::new(value);
+        let mut result = Self::default();
+        result.parse_into(&mut Parser::new(&mut input));
+        result
+    }
+
+    fn push_str(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        if let Some(UnparsedSegment::String(ref mut last)) = self.segments.last_mut() {
+            *last = format!("{}{}", last, s).into();
+            return;
+        }
+        self.segments.push(UnparsedSegment::String(s.into()));
+    }
+
+    fn parse_into(&mut self, input: &mut Parser) {
+        loop {
+            let start = input.position();
+            let token = match input.next_including_whitespace_and_comments() {
+                Ok(token) => token.clone(),
+                Err(..) => break,
+            };
+            let closing = match closing_token(&token) {
+                Some(closing) => closing,
+                None => {
+                    self.push_str(input.slice_from(start));
+                    continue;
+                }
+            };
+            if let Token::Function(ref name) = token {
+                if name.eq_ignore_ascii_case("var") {
+                    let state = input.state();
+                    if let Ok(reference) = input.parse_nested_block(VariableReferenceValue::parse_arguments) {
+                        self.segments.push(UnparsedSegment::Variable(reference));
+                        continue;
+                    }
+                    input.reset(&state);
+                }
+            }
+            self.push_str(input.slice_from(start));
+            let _ = input.parse_nested_block(|input| -> Result<(), Error> {
+                self.parse_into(input);
+                Ok(())
+            });
+            self.push_str(closing);
+        }
+    }
+
+    /// https://drafts.css-houdini.org/css-typed-om-1/#unparsedvalue-serialization
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        for segment in &self.segments {
+            match *segment {
+                UnparsedSegment::String(ref s) => dest.write_str(s)?,
+                UnparsedSegment::Variable(ref reference) => reference.to_css(dest)?,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+#[test]
+fn unparsed_value_test() {
+    let string = |s: &str| UnparsedSegment::String(s.into());
+    let reference = |variable: &str, fallback: Option<Vec<UnparsedSegment>>| {
+        let fallback = fallback.map(|segments| UnparsedValue { segments });
+        UnparsedSegment::Variable(VariableReferenceValue::new(variable, fallback).unwrap())
+    };
+
+    assert_eq!(UnparsedValue::parse_str("foo bar").segments, [string("foo bar")]);
+    assert_eq!(
+        UnparsedValue::parse_str("1px VAR(--a) var(--b, var(--c) 2)").segments,
+        [
+            string("1px "),
+            reference("--a", None),
+            string(" "),
+            reference("--b", Some(vec![reference("--c", None), string(" 2")])),
+        ],
+    );
+    assert_eq!(
+        UnparsedValue::parse_str("calc(var(--a) * [2])").segments,
+        [string("calc("), reference("--a", None), string(" * [2])")],
+    );
+    assert_eq!(UnparsedValue::parse_str("var(--a,)").segments, [reference("--a", Some(vec![]))]);
+    assert_eq!(UnparsedValue::parse_str("var(a) var(--b c)").segments, [string("var(a) var(--b c)")]);
+    assert!(VariableReferenceValue::new("a", None).is_none());
+
+    for &(value, serialization) in &[
+        ("var(--a,1px)", "var(--a, 1px)"),
+        ("foo(var(--a, ) /**/ {x})", "foo(var(--a, ) /**/ {x})"),
+        ("env(x, var(--a))", "env(x, var(--a))"),
+        ("(unclosed", "(unclosed)"),
+    ] {
+        assert_eq!(UnparsedValue::parse_str(value).to_css_string(), serialization);
+    }
+}
@@ -0,0 +1,459 @@
+//! The units of the dimension data types.
+
+use std::fmt;
+
+/// https://drafts.csswg.org/css-values-4/#lengths
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum LengthUnit {
+    Px,
+    Cm,
+    Mm,
+    Q,
+    In,
+    Pt,
+    Pc,
+    Em,
+    Rem,
+    Ex,
+    Rex,
+    Cap,
+    Rcap,
+    Ch,
+    Rch,
+    Ic,
+    Ric,
+    Lh,
+    Rlh,
+    Vw,
+    Vh,
+    Vi,
+    Vb,
+    Vmin,
+    Vmax,
+    Svw,
+    Svh,
+    Svi,
+    Svb,
+    Svmin,
+    Svmax,
+    Lvw,
+    Lvh,
+    Lvi,
+    Lvb,
+    Lvmin,
+    Lvmax,
+    Dvw,
+    Dvh,
+    Dvi,
+    Dvb,
+    Dvmin,
+    Dvmax,
+    Cqw,
+    Cqh,
+    Cqi,
+    Cqb,
+    Cqmin,
+    Cqmax,
+}
+
+/// What a length unit is relative to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LengthUnitKind {
+    /// https://drafts.csswg.org/css-values-4/#absolute-lengths
+    Absolute,
+    /// https://drafts.csswg.org/css-values-4/#font-relative-lengths
+    FontRelative,
+    /// https://drafts.csswg.org/css-values-4/#viewport-relative-lengths
+    ViewportPercentage,
+    /// https://drafts.csswg.org/css-contain-3/#container-lengths
+    ContainerRelative,
+}
+
+impl LengthUnit {
+    /// All the length units.
+    pub const ALL: &'static [LengthUnit] = &[
+        LengthUnit::Px,
+        LengthUnit::Cm,
+        LengthUnit::Mm,
+        LengthUnit::Q,
+        LengthUnit::In,
+        LengthUnit::Pt,
+        LengthUnit::Pc,
+        LengthUnit::Em,
+        LengthUnit::Rem,
+        LengthUnit::Ex,
+        LengthUnit::Rex,
+        LengthUnit::Cap,
+        LengthUnit::Rcap,
+        LengthUnit::Ch,
+        LengthUnit::Rch,
+        LengthUnit::Ic,
+        LengthUnit::Ric,
+        LengthUnit::Lh,
+        LengthUnit::Rlh,
+        LengthUnit::Vw,
+        LengthUnit::Vh,
+        LengthUnit::Vi,
+        LengthUnit::Vb,
+        LengthUnit::Vmin,
+        LengthUnit::Vmax,
+        LengthUnit::Svw,
+        LengthUnit::Svh,
+        LengthUnit::Svi,
+        LengthUnit::Svb,
+        LengthUnit::Svmin,
+        LengthUnit::Svmax,
+        LengthUnit::Lvw,
+        LengthUnit::Lvh,
+        LengthUnit::Lvi,
+        LengthUnit::Lvb,
+        LengthUnit::Lvmin,
+        LengthUnit::Lvmax,
+        LengthUnit::Dvw,
+        LengthUnit::Dvh,
+        LengthUnit::Dvi,
+        LengthUnit::Dvb,
+        LengthUnit::Dvmin,
+        LengthUnit::Dvmax,
+        LengthUnit::Cqw,
+        LengthUnit::Cqh,
+        LengthUnit::Cqi,
+        LengthUnit::Cqb,
+        LengthUnit::Cqmin,
+        LengthUnit::Cqmax,
+    ];
+
+    /// Returns the unit as written in CSS, in lowercase.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            LengthUnit::Px => "px",
+            LengthUnit::Cm => "cm",
+            LengthUnit::Mm => "mm",
+            LengthUnit::Q => "q",
+            LengthUnit::In => "in",
+            LengthUnit::Pt => "pt",
+            LengthUnit::Pc => "pc",
+            LengthUnit::Em => "em",
+            LengthUnit::Rem => "rem",
+            LengthUnit::Ex => "ex",
+            LengthUnit::Rex => "rex",
+            LengthUnit::Cap => "cap",
+            LengthUnit::Rcap => "rcap",
+            LengthUnit::Ch => "ch",
+            LengthUnit::Rch => "rch",
+            LengthUnit::Ic => "ic",
+            LengthUnit::Ric => "ric",
+            LengthUnit::Lh => "lh",
+            LengthUnit::Rlh => "rlh",
+            LengthUnit::Vw => "vw",
+            LengthUnit::Vh => "vh",
+            LengthUnit::Vi => "vi",
+            LengthUnit::Vb => "vb",
+            LengthUnit::Vmin => "vmin",
+            LengthUnit::Vmax => "vmax",
+            LengthUnit::Svw => "svw",
+            LengthUnit::Svh => "svh",
+            LengthUnit::Svi => "svi",
+            LengthUnit::Svb => "svb",
+            LengthUnit::Svmin => "svmin",
+            LengthUnit::Svmax => "svmax",
+            LengthUnit::Lvw => "lvw",
+            LengthUnit::Lvh => "lvh",
+            LengthUnit::Lvi => "lvi",
+            LengthUnit::Lvb => "lvb",
+            LengthUnit::Lvmin => "lvmin",
+            LengthUnit::Lvmax => "lvmax",
+            LengthUnit::Dvw => "dvw",
+            LengthUnit::Dvh => "dvh",
+            LengthUnit::Dvi => "dvi",
+            LengthUnit::Dvb => "dvb",
+            LengthUnit::Dvmin => "dvmin",
+            LengthUnit::Dvmax => "dvmax",
+            LengthUnit::Cqw => "cqw",
+            LengthUnit::Cqh => "cqh",
+            LengthUnit::Cqi => "cqi",
+            LengthUnit::Cqb => "cqb",
+            LengthUnit::Cqmin => "cqmin",
+            LengthUnit::Cqmax => "cqmax",
+        }
+    }
+
+    /// Returns the length unit with the given name, ASCII case-insensitively.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(unit: &str) -> Option<Self> {
+        Self::ALL.iter().find(|u| unit.eq_ignore_ascii_case(u.as_str())).cloned()
+    }
+
+    /// Returns what this unit is relative to.
+    pub fn kind(&self) -> LengthUnitKind {
+        match *self {
+            LengthUnit::Px |
+            LengthUnit::Cm |
+            LengthUnit::Mm |
+            LengthUnit::Q |
+            LengthUnit::In |
+            LengthUnit::Pt |
+            LengthUnit::Pc => LengthUnitKind::Absolute,
+            LengthUnit::Em |
+            LengthUnit::Rem |
+            LengthUnit::Ex |
+            LengthUnit::Rex |
+            LengthUnit::Cap |
+            LengthUnit::Rcap |
+            LengthUnit::Ch |
+            LengthUnit::Rch |
+            LengthUnit::Ic |
+            LengthUnit::Ric |
+            LengthUnit::Lh |
+            LengthUnit::Rlh => LengthUnitKind::FontRelative,
+            LengthUnit::Cqw |
+            LengthUnit::Cqh |
+            LengthUnit::Cqi |
+            LengthUnit::Cqb |
+            LengthUnit::Cqmin |
+            LengthUnit::Cqmax => LengthUnitKind::ContainerRelative,
+            _ => LengthUnitKind::ViewportPercentage,
+        }
+    }
+
+    /// Returns how many pixels one of this unit is, if it's an absolute unit.
+    pub fn to_px(&self) -> Option<f64> {
+        Some(match *self {
+            LengthUnit::Px => 1.,
+            LengthUnit::Cm => 96. / 2.54,
+            LengthUnit::Mm => 96. / 25.4,
+            LengthUnit::Q => 96. / 101.6,
+            LengthUnit::In => 96.,
+            LengthUnit::Pt => 96. / 72.,
+            LengthUnit::Pc => 96. / 6.,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// https://drafts.csswg.org/css-values-4/#angles
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AngleUnit {
+    Deg,
+    Grad,
+    Rad,
+    Turn,
+}
+
+impl AngleUnit {
+    /// All the angle units.
+    pub const ALL: &'static [AngleUnit] = &[AngleUnit::Deg, AngleUnit::Grad, AngleUnit::Rad, AngleUnit::Turn];
+
+    /// The canonical angle unit.
+    pub const CANONICAL: AngleUnit = AngleUnit::Deg;
+
+    /// Returns the unit as written in CSS, in lowercase.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AngleUnit::Deg => "deg",
+            AngleUnit::Grad => "grad",
+            AngleUnit::Rad => "rad",
+            AngleUnit::Turn => "turn",
+        }
+    }
+
+    /// Returns the angle unit with the given name, ASCII case-insensitively.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(unit: &str) -> Option<Self> {
+        Self::ALL.iter().find(|u| unit.eq_ignore_ascii_case(u.as_str())).cloned()
+    }
+
+    /// Returns how many of the canonical unit one of this unit is.
+    pub fn to_canonical(&self) -> f64 {
+        match *self {
+            AngleUnit::Deg => 1.,
+            AngleUnit::Grad => 360. / 400.,
+            AngleUnit::Rad => 180. / std::f64::consts::PI,
+            AngleUnit::Turn => 360.,
+        }
+    }
+}
+
+/// https://drafts.csswg.org/css-values-4/#time
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TimeUnit {
+    S,
+    Ms,
+}
+
+impl TimeUnit {
+    /// All the time units.
+    pub const ALL: &'static [TimeUnit] = &[TimeUnit::S, TimeUnit::Ms];
+
+    /// The canonical time unit.
+    pub const CANONICAL: TimeUnit = TimeUnit::S;
+
+    /// Returns the unit as written in CSS, in lowercase.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            TimeUnit::S => "s",
+            TimeUnit::Ms => "ms",
+        }
+    }
+
+    /// Returns the time unit with the given name, ASCII case-insensitively.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(unit: &str) -> Option<Self> {
+        Self::ALL.iter().find(|u| unit.eq_ignore_ascii_case(u.as_str())).cloned()
+    }
+
+    /// Returns how many of the canonical unit one of this unit is.
+    pub fn to_canonical(&self) -> f64 {
+        match *self {
+            TimeUnit::S => 1.,
+            TimeUnit::Ms => 0.001,
+        }
+    }
+}
+
+/// https://drafts.csswg.org/css-values-4/#frequency
+///
+/// There's no `<frequency>` data type in syntax descriptors, but the Typed OM
+/// has numeric values of these units.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum FrequencyUnit {
+    Hz,
+    Khz,
+}
+
+impl FrequencyUnit {
+    /// All the frequency units.
+    pub const ALL: &'static [FrequencyUnit] = &[FrequencyUnit::Hz, FrequencyUnit::Khz];
+
+    /// The canonical frequency unit.
+    pub const CANONICAL: FrequencyUnit = FrequencyUnit::Hz;
+
+    /// Returns the unit as written in CSS, in lowercase.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            FrequencyUnit::Hz => "hz",
+            FrequencyUnit::Khz => "khz",
+        }
+    }
+
+    /// Returns the frequency unit with the given name, ASCII
+    /// case-insensitively.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(unit: &str) -> Option<Self> {
+        Self::ALL.iter().find(|u| unit.eq_ignore_ascii_case(u.as_str())).cloned()
+    }
+
+    /// Returns how many of the canonical unit one of this unit is.
+    pub fn to_canonical(&self) -> f64 {
+        match *self {
+            FrequencyUnit::Hz => 1.,
+            FrequencyUnit::Khz => 1000.,
+        }
+    }
+}
+
+/// https://drafts.csswg.org/css-values-4/#resolution
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ResolutionUnit {
+    Dpi,
+    Dpcm,
+    Dppx,
+    /// An alias of `dppx`.
+    X,
+}
+
+impl ResolutionUnit {
+    /// All the resolution units.
+    pub const ALL: &'static [ResolutionUnit] = &[
+        ResolutionUnit::Dpi,
+        ResolutionUnit::Dpcm,
+        ResolutionUnit::Dppx,
+        ResolutionUnit::X,
+    ];
+
+    /// The canonical resolution unit.
+    pub const CANONICAL: ResolutionUnit = ResolutionUnit::Dppx;
+
+    /// Returns the unit as written in CSS, in lowercase.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ResolutionUnit::Dpi => "dpi",
+            ResolutionUnit::Dpcm => "dpcm",
+            ResolutionUnit::Dppx => "dppx",
+            ResolutionUnit::X => "x",
+        }
+    }
+
+    /// Returns the resolution unit with the given name, ASCII
+    /// case-insensitively.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(unit: &str) -> Option<Self> {
+        Self::ALL.iter().find(|u| unit.eq_ignore_ascii_case(u.as_str())).cloned()
+    }
+
+    /// Returns how many of the canonical unit one of this unit is.
+    pub fn to_canonical(&self) -> f64 {
+        match *self {
+            ResolutionUnit::Dpi => 1. / 96.,
+            ResolutionUnit::Dpcm => 2.54 / 96.,
+            ResolutionUnit::Dppx | ResolutionUnit::X => 1.,
+        }
+    }
+}
+
+impl fmt::Display for AngleUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for FrequencyUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for ResolutionUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[test]
+fn units_test() {
+    for unit in LengthUnit::ALL {
+        assert_eq!(LengthUnit::from_str(unit.as_str()), Some(*unit));
+    }
+    assert_eq!(LengthUnit::from_str("PX"), Some(LengthUnit::Px));
+    assert_eq!(LengthUnit::from_str("dvMin"), Some(LengthUnit::Dvmin));
+    assert_eq!(LengthUnit::from_str("deg"), None);
+    assert_eq!(LengthUnit::Rlh.kind(), LengthUnitKind::FontRelative);
+    assert_eq!(LengthUnit::Svh.kind(), LengthUnitKind::ViewportPercentage);
+    assert_eq!(LengthUnit::Cqi.kind(), LengthUnitKind::ContainerRelative);
+    assert_eq!(LengthUnit::In.to_px(), Some(96.));
+    assert_eq!(LengthUnit::Em.to_px(), None);
+
+    assert_eq!(AngleUnit::from_str("TURN"), Some(AngleUnit::Turn));
+    assert_eq!(AngleUnit::Turn.to_canonical(), 360.);
+    assert_eq!(AngleUnit::CANONICAL.to_canonical(), 1.);
+    assert_eq!(TimeUnit::from_str("ms"), Some(TimeUnit::Ms));
+    assert_eq!(TimeUnit::Ms.to_canonical(), 0.001);
+    assert_eq!(FrequencyUnit::from_str("kHz"), Some(FrequencyUnit::Khz));
+    assert_eq!(FrequencyUnit::Khz.to_canonical(), 1000.);
+    assert_eq!(ResolutionUnit::from_str("x"), Some(ResolutionUnit::X));
+    assert_eq!(ResolutionUnit::Dpi.to_canonical() * 96., 1.);
+    assert_eq!(ResolutionUnit::CANONICAL, ResolutionUnit::Dppx);
+}
@@ -0,0 +1,84 @@
+//! Matching of `<url>` values.
+//!
+//! https://drafts.csswg.org/css-values-4/#urls
+
+use std::fmt;
+
+use cssparser::{Parser, Token};
+
+use super::matching::Error;
+
+/// A matched `<url>`.
+///
+/// The URL is not resolved against any base URL, that's left to the embedder.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Url {
+    /// The URL string, with escapes resolved.
+    pub url: Box<str>,
+    /// Whether the URL was given as a quoted string, as in `url("foo.png")`
+    /// or `src("foo.png")`, rather than as in `url(foo.png)`.
+    pub quoted: bool,
+}
+
+impl Url {
+    /// Consumes the rest of a `url()` or `src()` function whose name has
+    /// already been consumed, if `name` is one of those.
+    pub(crate) fn parse_function<'i, 't>(name: &str, input: &mut Parser<'i, 't>) -> Option<Result<Self, Error<'i>>> {
+        if !name.eq_ignore_ascii_case("url") && !name.eq_ignore_ascii_case("src") {
+            return None;
+        }
+        // TODO: `src()` can take `<url-modifier>`s after the string, but no
+        // modifiers are defined yet.
+        let url = input.parse_nested_block(|input| {
+            input.expect_string_cloned().map_err(Into::<Error<'i>>::into)
+        });
+        Some(url.map(|url| Url { url: url.as_ref().into(), quoted: true }))
+    }
+
+    /// Serializes the URL as a `url()` with a quoted string.
+    ///
+    /// https://drafts.csswg.org/cssom-1/#serialize-a-url
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        dest.write_str("url(")?;
+        cssparser::serialize_string(&self.url, dest)?;
+        dest.write_char(')')
+    }
+}
+
+impl cssparser::ToCss for Url {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        Url::to_css(self, dest)
+    }
+}
+
+/// Consumes a `<url>`.
+pub(crate) fn parse_url<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Url, Error<'i>> {
+    let location = input.current_source_location();
+    let token = input.next()?.clone();
+    match token {
+        Token::UnquotedUrl(ref url) => return Ok(Url { url: url.as_ref().into(), quoted: false }),
+        Token::Function(ref name) => {
+            if let Some(url) = Url::parse_function(name, input) {
+                return url;
+            }
+        }
+        _ => {}
+    }
+    Err(location.new_unexpected_token_error(token))
+}
+
+#[test]
+fn url_test() {
+    fn parse(input: &str) -> Result<Url, ()> {
+        let mut input = cssparser::ParserInput::new(input);
+        Parser::new(&mut input).parse_entirely(parse_url).map_err(|_| ())
+    }
+
+    assert_eq!(parse("url(foo.png)"), Ok(Url { url: "foo.png".into(), quoted: false }));
+    assert_eq!(parse("URL( 'a b.png' )"), Ok(Url { url: "a b.png".into(), quoted: true }));
+    assert_eq!(parse("url(\\66 oo)"), Ok(Url { url: "foo".into(), quoted: false }));
+    assert_eq!(parse("src(\"foo.png\")"), Ok(Url { url: "foo.png".into(), quoted: true }));
+    for invalid in &["src(foo.png)", "url('a' 'b')", "'foo.png'", "image(foo.png)"] {
+        assert_eq!(parse(invalid), Err(()), "{}", invalid);
+    }
+}
@@ -0,0 +1,471 @@
+//! The result of matching a value against a syntax descriptor.
+
+use std::fmt;
+
+use super::{AngleUnit, CalcNode, Color, CustomIdent, Image, LengthUnit, Multiplier, ResolutionUnit, TimeUnit};
+use super::{PendingSubstitution, TransformFunction, Url};
+use super::substitution::References;
+#[cfg(feature = "experimental-position")]
+use super::Position;
+
+/// A value that matched a syntax descriptor, describing what it matched.
+///
+/// Values of data types that need computation to be meaningful, like
+/// lengths, are kept as specified.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum MatchedValue {
+    /// A `<length>`, or a `<length-percentage>` that is a length.
+    Length { value: f64, unit: LengthUnit },
+    /// A `<number>`.
+    Number(f64),
+    /// An `<integer>`.
+    Integer(i64),
+    /// A `<percentage>`, or a `<length-percentage>` that is a percentage,
+    /// where `1.0` is `100%`.
+    Percentage(f64),
+    /// An `<angle>`.
+    Angle { value: f64, unit: AngleUnit },
+    /// A `<time>`.
+    Time { value: f64, unit: TimeUnit },
+    /// A `<resolution>`.
+    Resolution { value: f64, unit: ResolutionUnit },
+    /// A `<color>`.
+    Color(Color),
+    /// An `<image>`.
+    Image(Image),
+    /// A `<url>`, unresolved.
+    Url(Url),
+    /// A `<transform-function>`.
+    TransformFunction(TransformFunction),
+    /// A `<transform-list>`, which is never empty.
+    TransformList(Vec<TransformFunction>),
+    /// A `<position>`.
+    #[cfg(feature = "experimental-position")]
+    Position(Position),
+    /// A `<custom-ident>`, as written, which is never a CSS-wide keyword nor
+    /// `default`.
+    CustomIdent(CustomIdent),
+    /// One of the identifiers of the syntax descriptor, spelled as in the
+    /// descriptor rather than as in the value.
+    Ident(CustomIdent),
+    /// A list of values matching a component with a multiplier, which says
+    /// how they were separated. It's never empty.
+    List(Vec<MatchedValue>, Multiplier),
+    /// A math function, like `calc(100% - 1em)`, that resolves to the
+    /// expected data type.
+    Calc(Box<CalcNode>),
+    /// A math function that resolves to a number where an `<integer>` is
+    /// expected, but can't be simplified to one until it's computed, like
+    /// `calc(1em / 1px)`. It's rounded to the nearest integer then.
+    IntegerCalc(Box<CalcNode>),
+    /// A value matched by the universal syntax descriptor, as written.
+    Unparsed(Box<str>),
+    /// A value with `var()` or `env()` references, which can't be matched
+    /// until they're substituted.
+    PendingSubstitution(PendingSubstitution),
+}
+
+impl MatchedValue {
+    /// Returns whether this value is or contains a percentage, in which case
+    /// it may need to be resolved against something to be computed.
+    pub fn has_percentage(&self) -> bool {
+        match *self {
+            MatchedValue::Percentage(..) => true,
+            MatchedValue::Calc(ref node) | MatchedValue::IntegerCalc(ref node) => node.has_percentage(),
+            MatchedValue::List(ref items, ..) => items.iter().any(MatchedValue::has_percentage),
+            MatchedValue::TransformFunction(ref function) => function.has_percentage(),
+            MatchedValue::TransformList(ref functions) => functions.iter().any(TransformFunction::has_percentage),
+            #[cfg(feature = "experimental-position")]
+            MatchedValue::Position(ref position) => position.has_percentage(),
+            _ => false,
+        }
+    }
+
+    /// Returns the items of this value if it's a list.
+    pub fn as_list(&self) -> Option<&[MatchedValue]> {
+        match *self {
+            MatchedValue::List(ref items, ..) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns how the items of this value were separated if it's a list.
+    pub fn list_separator(&self) -> Option<Multiplier> {
+        match *self {
+            MatchedValue::List(_, multiplier) => Some(multiplier),
+            _ => None,
+        }
+    }
+
+    /// Returns the transform functions of this value if it's a transform
+    /// list.
+    pub fn as_transform_list(&self) -> Option<&[TransformFunction]> {
+        match *self {
+            MatchedValue::TransformList(ref functions) => Some(functions),
+            _ => None,
+        }
+    }
+}
+
+impl MatchedValue {
+    /// Serializes this value, with numbers in their shortest form and
+    /// keywords in lowercase.
+    ///
+    /// Math functions are serialized as written, without the simplification
+    /// css-values-4 calls for, so `calc(1px + 2px)` isn't turned into
+    /// `calc(3px)`. Computing the value simplifies them instead.
+    ///
+    /// https://drafts.csswg.org/cssom-1/#serializing-css-values
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            MatchedValue::Length { value, unit } => write_dimension(value, unit.as_str(), dest),
+            MatchedValue::Number(value) => write_dimension(value, "", dest),
+            MatchedValue::Integer(value) => write!(dest, "{}", value),
+            MatchedValue::Percentage(value) => write_percentage(value, dest),
+            MatchedValue::Angle { value, unit } => write_dimension(value, unit.as_str(), dest),
+            MatchedValue::Time { value, unit } => write_dimension(value, unit.as_str(), dest),
+            MatchedValue::Resolution { value, unit } => write_dimension(value, unit.as_str(), dest),
+            MatchedValue::Color(ref color) => color.to_css(dest),
+            MatchedValue::Image(ref image) => image.to_css(dest),
+            MatchedValue::Url(ref url) => url.to_css(dest),
+            MatchedValue::TransformFunction(ref function) => function.to_css(dest),
+            MatchedValue::TransformList(ref functions) => {
+                for (i, function) in functions.iter().enumerate() {
+                    if i > 0 {
+                        dest.write_char(' ')?;
+                    }
+                    function.to_css(dest)?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "experimental-position")]
+            MatchedValue::Position(ref position) => position.to_css(dest),
+            MatchedValue::CustomIdent(ref ident) => cssparser::serialize_identifier(ident, dest),
+            MatchedValue::Ident(ref ident) => {
+                cssparser::serialize_identifier(&ident.to_ascii_lowercase(), dest)
+            }
+            MatchedValue::List(ref items, multiplier) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        dest.write_str(multiplier.separator())?;
+                    }
+                    item.to_css(dest)?;
+                }
+                Ok(())
+            }
+            MatchedValue::Calc(ref node) | MatchedValue::IntegerCalc(ref node) => node.to_css(dest),
+            MatchedValue::Unparsed(ref source) => dest.write_str(source),
+            MatchedValue::PendingSubstitution(ref value) => dest.write_str(&value.source),
+        }
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+impl cssparser::ToCss for MatchedValue {
+    fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        MatchedValue::to_css(self, dest)
+    }
+}
+
+/// The computed value of a custom property, which can be the
+/// guaranteed-invalid value besides a value that matched its syntax.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComputedValue {
+    Value(MatchedValue),
+    /// The initial value of unregistered custom properties, and of
+    /// registered ones with no initial value. `var()` references to it are
+    /// replaced by their fallback, and make the value that contains them
+    /// invalid at computed-value time if they have none.
+    ///
+    /// https://drafts.csswg.org/css-variables-2/#guaranteed-invalid-value
+    GuaranteedInvalid,
+}
+
+impl ComputedValue {
+    pub fn is_guaranteed_invalid(&self) -> bool {
+        matches!(*self, ComputedValue::GuaranteedInvalid)
+    }
+
+    pub fn as_value(&self) -> Option<&MatchedValue> {
+        match *self {
+            ComputedValue::Value(ref value) => Some(value),
+            ComputedValue::GuaranteedInvalid => None,
+        }
+    }
+
+    pub fn into_value(self) -> Option<MatchedValue> {
+        match self {
+            ComputedValue::Value(value) => Some(value),
+            ComputedValue::GuaranteedInvalid => None,
+        }
+    }
+
+    /// Serializes this value, with the guaranteed-invalid value serialized as
+    /// the empty string.
+    pub fn to_css<W: fmt::Write>(&self, dest: &mut W) -> fmt::Result {
+        match *self {
+            ComputedValue::Value(ref value) => value.to_css(dest),
+            ComputedValue::GuaranteedInvalid => Ok(()),
+        }
+    }
+
+    pub fn to_css_string(&self) -> String {
+        let mut s = String::new();
+        self.to_css(&mut s).unwrap();
+        s
+    }
+}
+
+/// `None` is the guaranteed-invalid value.
+impl From<Option<MatchedValue>> for ComputedValue {
+    fn from(value: Option<MatchedValue>) -> Self {
+        value.map_or(ComputedValue::GuaranteedInvalid, ComputedValue::Value)
+    }
+}
+
+/// Serializes a number in its shortest form, with infinities and NaN spelled
+/// as the `calc()` constants.
+///
+/// Numbers are serialized with the precision of an `f32`, which is that of
+/// the numbers we get from the tokenizer, so that neither converting them to
+/// `f64` nor doing math with them makes up digits.
+pub(crate) fn write_number<W: fmt::Write>(value: f64, dest: &mut W) -> fmt::Result {
+    if value.is_nan() {
+        return dest.write_str("NaN");
+    }
+    if value.is_infinite() {
+        return dest.write_str(if value < 0. { "-infinity" } else { "infinity" });
+    }
+    if value == 0. {
+        return dest.write_char('0');
+    }
+    let single = value as f32;
+    if single.is_finite() {
+        write!(dest, "{}", single)
+    } else {
+        write!(dest, "{}", value)
+    }
+}
+
+/// Serializes a number followed by `unit`, which may be empty. Numbers that
+/// aren't finite can only be written in math functions, so they're wrapped
+/// in a `calc()`.
+pub(crate) fn write_dimension<W: fmt::Write>(value: f64, unit: &str, dest: &mut W) -> fmt::Result {
+    if value.is_finite() {
+        write_number(value, dest)?;
+        return dest.write_str(unit);
+    }
+    dest.write_str("calc(")?;
+    write_number(value, dest)?;
+    if !unit.is_empty() {
+        dest.write_str(" * 1")?;
+        dest.write_str(unit)?;
+    }
+    dest.write_char(')')
+}
+
+/// Serializes a percentage, where `1.0` is `100%`.
+pub(crate) fn write_percentage<W: fmt::Write>(unit_value: f64, dest: &mut W) -> fmt::Result {
+    write_dimension(unit_value * 100., "%", dest)
+}
+
+/// What matching a value produces: either a `MatchedValue`, or nothing when
+/// only validity matters, so that `Descriptor::value_matches` doesn't build
+/// values it throws away.
+pub(crate) trait Output: Sized {
+    /// Where the `var()` and `env()` references of a value are recorded.
+    type References: References;
+
+    /// Returns the result for a single value, calling `build` only if it's
+    /// needed.
+    fn value(build: impl FnOnce() -> MatchedValue) -> Self;
+
+    /// Returns the result for a list of results, calling `build` with the
+    /// matched items only if it's needed.
+    fn list(items: Vec<Self>, build: impl FnOnce(Vec<MatchedValue>) -> MatchedValue) -> Self;
+
+    /// Returns the result for a value with `references`, written as `source`.
+    fn pending_substitution(references: Self::References, source: &str) -> Self;
+}
+
+impl Output for () {
+    type References = bool;
+
+    #[inline]
+    fn value(_: impl FnOnce() -> MatchedValue) {}
+
+    #[inline]
+    fn list(_: Vec<()>, _: impl FnOnce(Vec<MatchedValue>) -> MatchedValue) {}
+
+    #[inline]
+    fn pending_substitution(_: bool, _: &str) {}
+}
+
+impl Output for MatchedValue {
+    type References = PendingSubstitution;
+
+    #[inline]
+    fn value(build: impl FnOnce() -> MatchedValue) -> Self {
+        build()
+    }
+
+    #[inline]
+    fn list(items: Vec<Self>, build: impl FnOnce(Vec<MatchedValue>) -> MatchedValue) -> Self {
+        build(items)
+    }
+
+    #[inline]
+    fn pending_substitution(references: PendingSubstitution, source: &str) -> Self {
+        MatchedValue::PendingSubstitution(PendingSubstitution { source: source.into(), ..references })
+    }
+}
+
+#[test]
+fn matched_value_test() {
+    use super::{parse_descriptor, TransformFunctionName};
+
+    fn parse(syntax: &str, value: &str) -> MatchedValue {
+        let descriptor = parse_descriptor(syntax).unwrap();
+        let mut input = cssparser::ParserInput::new(value);
+        descriptor.parse_value(&mut cssparser::Parser::new(&mut input)).unwrap()
+    }
+
+    assert_eq!(
+        parse("<length> | auto", "10px"),
+        MatchedValue::Length { value: 10., unit: LengthUnit::Px },
+    );
+    assert_eq!(
+        parse("<length> | auto", "auto"),
+        MatchedValue::Ident(CustomIdent::from_ident("auto").unwrap()),
+    );
+    assert_eq!(
+        parse("fooBar | <custom-ident>", "FOOBAR"),
+        MatchedValue::Ident(CustomIdent::from_ident("fooBar").unwrap()),
+    );
+    assert_eq!(
+        parse("<custom-ident>", "FooBar"),
+        MatchedValue::CustomIdent(CustomIdent::from_ident("FooBar").unwrap()),
+    );
+    assert_eq!(parse("<length-percentage>", "50%"), MatchedValue::Percentage(0.5));
+    assert_eq!(
+        parse("<length-percentage>", "2REM"),
+        MatchedValue::Length { value: 2., unit: LengthUnit::Rem },
+    );
+    assert_eq!(parse("<length>", "0"), MatchedValue::Length { value: 0., unit: LengthUnit::Px });
+    let calc = parse("<length-percentage>", "calc(100% - 1em)");
+    assert_eq!(calc, MatchedValue::Calc(Box::new(CalcNode::Sum(vec![
+        CalcNode::Percentage(1.),
+        CalcNode::Negate(Box::new(CalcNode::Length(1., LengthUnit::Em))),
+    ]))));
+    assert!(calc.has_percentage());
+    assert_eq!(parse("<length>", "calc(1px + 2px)").to_css_string(), "calc(1px + 2px)");
+    assert!(!parse("<length-percentage>", "calc(1em * 2)").has_percentage());
+    assert!(parse("<length-percentage>#", "1px, 2%").has_percentage());
+    assert_eq!(parse("<time>", "20ms"), MatchedValue::Time { value: 20., unit: TimeUnit::Ms });
+    assert_eq!(
+        parse("<resolution>", "2x"),
+        MatchedValue::Resolution { value: 2., unit: ResolutionUnit::X },
+    );
+    assert_eq!(parse("<angle>", "1turn"), MatchedValue::Angle { value: 1., unit: AngleUnit::Turn });
+    assert_eq!(parse("<integer> | <number>", "3"), MatchedValue::Integer(3));
+    assert_eq!(parse("<integer> | <number>", "3.5"), MatchedValue::Number(3.5));
+    assert_eq!(parse("<integer>", "calc(5 / 2)"), MatchedValue::Integer(3));
+    assert_eq!(parse("<integer>", "calc(-5 / 2)"), MatchedValue::Integer(-2));
+    assert_eq!(parse("<number>", "calc(5 / 2)"), MatchedValue::Number(2.5));
+    assert_eq!(parse("<url>", "url('a.png')"), MatchedValue::Url(Url { url: "a.png".into(), quoted: true }));
+    assert_eq!(
+        parse("<color>", "red"),
+        MatchedValue::Color(Color::Rgba(cssparser::RGBA::new(255, 0, 0, 255))),
+    );
+    assert_eq!(
+        parse("<number>#", "1, 2"),
+        MatchedValue::List(vec![MatchedValue::Number(1.), MatchedValue::Number(2.)], Multiplier::Comma),
+    );
+    let list = parse("<length>+", "1px 2px 3px");
+    assert_eq!(list.as_list().map(<[_]>::len), Some(3));
+    assert_eq!(list.list_separator(), Some(Multiplier::Space));
+    assert_eq!(parse("<length>", "1px").list_separator(), None);
+
+    let list = parse("<transform-list>", "scale(2) translateX( 1px )");
+    assert_eq!(list.as_list(), None);
+    assert_eq!(list.as_transform_list().unwrap(), [
+        TransformFunction {
+            name: TransformFunctionName::Scale,
+            arguments: vec![MatchedValue::Number(2.)],
+        },
+        TransformFunction {
+            name: TransformFunctionName::TranslateX,
+            arguments: vec![MatchedValue::Length { value: 1., unit: LengthUnit::Px }],
+        },
+    ]);
+    assert!(!list.has_percentage());
+    assert!(parse("<transform-list>", "translate(50%)").has_percentage());
+    assert_eq!(parse("*", "  foo(bar) "), MatchedValue::Unparsed("foo(bar)".into()));
+
+    for &(syntax, value, serialization) in &[
+        ("<length>", "1.50PX", "1.5px"),
+        ("<length>", "0", "0px"),
+        ("<number>", "+.1", "0.1"),
+        ("<number>", "calc(infinity)", "calc(infinity)"),
+        ("<number>", "1e3", "1000"),
+        ("<integer>", "calc(-5 / 2)", "-2"),
+        ("<percentage>", "10.0%", "10%"),
+        ("<length-percentage>#", "33.3%,1Em", "33.3%, 1em"),
+        ("<angle>+", "1TURN  -0.5rad", "1turn -0.5rad"),
+        ("<resolution>", "2X", "2x"),
+        ("<length-percentage>", "calc( 100% -  2 * 1px )", "calc(100% - 2 * 1px)"),
+        ("<length>", "calc((1px + 2px) * 3 / (4 + 5))", "calc((1px + 2px) * 3 / (4 + 5))"),
+        ("<length>", "MIN(1px, 2Vw)", "min(1px, 2vw)"),
+        ("<length>", "round(UP, 1px, 2px)", "round(up, 1px, 2px)"),
+        ("<color>", "RED", "rgb(255, 0, 0)"),
+        ("<color>", "currentColor", "currentcolor"),
+        ("<color>", "LAB(50% -20 none / 50%)", "lab(50 -20 0 / 0.5)"),
+        ("<color>", "color(xyz 0.5 0 1)", "color(xyz-d65 0.5 0 1)"),
+        ("<url>", "url(a.png)", "url(\"a.png\")"),
+        (
+            "<image>",
+            "Linear-Gradient(to left, red 10%, 50%, blue)",
+            "linear-gradient(to left, rgb(255, 0, 0) 10%, 50%, rgb(0, 0, 255))",
+        ),
+        (
+            "<image>",
+            "repeating-radial-gradient(red,blue 1px 2px)",
+            "repeating-radial-gradient(rgb(255, 0, 0), rgb(0, 0, 255) 1px 2px)",
+        ),
+        (
+            "<image>",
+            "image-set('a.png' 2x type('image/png'), url(b.png))",
+            "image-set(url(\"a.png\") 2x type(\"image/png\"), url(\"b.png\"))",
+        ),
+        ("<transform-list>", "TRANSLATEX(1px)  rotate(0)", "translateX(1px) rotate(0deg)"),
+        ("<transform-function>", "matrix(1,0,0,1,0,0)", "matrix(1, 0, 0, 1, 0, 0)"),
+        ("AUTO | none", "Auto", "auto"),
+        ("<custom-ident>+", "Foo bar\\ baz", "Foo bar\\ baz"),
+        ("*", " { a } ", "{ a }"),
+        ("<length>", "var(--A,  1px) ", "var(--A,  1px)"),
+    ] {
+        assert_eq!(parse(syntax, value).to_css_string(), serialization, "{:?} as {:?}", value, syntax);
+    }
+    assert_eq!(
+        parse("<length>#", " 1px, var(--a, var(--b)) "),
+        MatchedValue::PendingSubstitution(PendingSubstitution {
+            source: "1px, var(--a, var(--b))".into(),
+            variables: vec!["--a".into(), "--b".into()],
+            environment_variables: vec![],
+        }),
+    );
+
+    let value = ComputedValue::from(Some(parse("<length>", "1px")));
+    assert_eq!(value.to_css_string(), "1px");
+    assert_eq!(value.into_value(), Some(MatchedValue::Length { value: 1., unit: super::LengthUnit::Px }));
+    let value = ComputedValue::from(None);
+    assert!(value.is_guaranteed_invalid());
+    assert_eq!(value.as_value(), None);
+    assert_eq!(value.to_css_string(), "");
+}
@@ -0,0 +1,105 @@
+use super::{Component, ComponentName, Descriptor, Impl, Multiplier};
+
+/// A visitor over the components of a syntax descriptor.
+///
+/// All methods have default implementations, so visitors only need to
+/// override the ones for the parts they care about.
+pub trait ComponentVisitor<I: Impl> {
+    /// Visits a component. By default visits its name and multiplier.
+    fn visit_component(&mut self, component: &Component<I>) {
+        self.visit_name(component.name());
+        if let Some(multiplier) = component.multiplier() {
+            self.visit_multiplier(multiplier);
+        }
+    }
+
+    /// Visits a component name. By default dispatches to the method for the
+    /// kind of name.
+    fn visit_name(&mut self, name: &ComponentName<I>) {
+        match *name {
+            ComponentName::DataType(ref ty) => self.visit_data_type(ty),
+            ComponentName::Ident(ref ident) => self.visit_ident(ident),
+            ComponentName::UnknownDataType(ref name) => self.visit_unknown_data_type(name),
+        }
+    }
+
+    /// Visits a data type name.
+    fn visit_data_type(&mut self, _data_type: &I::DataType) {}
+
+    /// Visits an identifier.
+    fn visit_ident(&mut self, _ident: &I::CustomIdent) {}
+
+    /// Visits a data type name not known by the implementation.
+    fn visit_unknown_data_type(&mut self, _name: &str) {}
+
+    /// Visits the multiplier of a component.
+    fn visit_multiplier(&mut self, _multiplier: Multiplier) {}
+}
+
+impl<I: Impl> Descriptor<I> {
+    /// Visits each component of this descriptor in order.
+    pub fn visit<V: ComponentVisitor<I>>(&self, visitor: &mut V) {
+        for component in self.iter() {
+            visitor.visit_component(component);
+        }
+    }
+
+    /// Returns a new descriptor with each component replaced by the result
+    /// of `f`. The universal descriptor maps to itself.
+    ///
+    /// The new descriptor can use a different implementation, which allows
+    /// converting between them.
+    pub fn map_components<J, F>(&self, f: F) -> Descriptor<J>
+    where
+        J: Impl,
+        F: FnMut(&Component<I>) -> Component<J>,
+    {
+        Descriptor(self.iter().map(f).collect())
+    }
+}
+
+#[test]
+fn visitor_test() {
+    use super::{parse_descriptor, CustomIdent, DataType, DefaultImpl};
+
+    #[derive(Default)]
+    struct Collector {
+        data_types: Vec<DataType>,
+        idents: Vec<String>,
+        multipliers: usize,
+    }
+
+    impl ComponentVisitor<DefaultImpl> for Collector {
+        fn visit_data_type(&mut self, data_type: &DataType) {
+            self.data_types.push(*data_type);
+        }
+
+        fn visit_ident(&mut self, ident: &CustomIdent) {
+            self.idents.push(ident.to_string());
+        }
+
+        fn visit_multiplier(&mut self, _: Multiplier) {
+            self.multipliers += 1;
+        }
+    }
+
+    let descriptor = parse_descriptor("<length># | auto | <color> | none+").unwrap();
+    let mut collector = Collector::default();
+    descriptor.visit(&mut collector);
+    assert_eq!(collector.data_types, [DataType::Length, DataType::Color]);
+    assert_eq!(collector.idents, ["auto", "none"]);
+    assert_eq!(collector.multipliers, 2);
+
+    let renamed = descriptor.map_components(|component| match component.name() {
+        ComponentName::Ident(ref ident) if ident.as_str() == "auto" => Component::new(
+            ComponentName::Ident(CustomIdent::from_ident("normal").unwrap()),
+            component.multiplier(),
+        )
+        .unwrap(),
+        _ => component.clone(),
+    });
+    assert!(renamed == "<length># | normal | <color> | none+");
+
+    let universal = parse_descriptor("*").unwrap();
+    assert!(universal.map_components::<DefaultImpl, _>(|c| c.clone()).is_universal());
+}
@@ -0,0 +1,112 @@
+//! Conversions to and from JavaScript values, behind the `wasm` feature.
+//!
+//! Descriptors are represented as an array of
+//! `{ type, name, multiplier }` objects, where `type` is one of `"data-type"`,
+//! `"ident"` or `"unknown-data-type"`, `name` is the data type name (without
+//! angle brackets) or identifier, and `multiplier` is `null`, `"space"` or
+//! `"comma"`. The universal descriptor is an empty array.
+
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{Error, Serializer};
+use wasm_bindgen::JsValue;
+
+use super::{
+    BuildError, Component, ComponentName, CustomIdent, DataType, DefaultImpl, Descriptor,
+    Multiplier,
+};
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum JsComponentType {
+    DataType,
+    Ident,
+    UnknownDataType,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct JsComponent {
+    #[serde(rename = "type")]
+    ty: JsComponentType,
+    name: String,
+    multiplier: Option<Multiplier>,
+}
+
+impl JsComponent {
+    fn new(component: &Component<DefaultImpl>) -> Self {
+        let (ty, name) = match component.name {
+            ComponentName::DataType(ty) => (JsComponentType::DataType, ty.as_str()),
+            ComponentName::Ident(ref ident) => (JsComponentType::Ident, ident.as_str()),
+            ComponentName::UnknownDataType(ref name) => (JsComponentType::UnknownDataType, &**name),
+        };
+        JsComponent {
+            ty,
+            name: name.to_owned(),
+            multiplier: component.multiplier,
+        }
+    }
+
+    fn to_component(&self) -> Result<Component<DefaultImpl>, Error> {
+        let name = match self.ty {
+            JsComponentType::DataType => ComponentName::DataType(
+                DataType::from_str(&self.name)
+                    .ok_or_else(|| Error::new(format_args!("unknown data type name '{}'", self.name)))?,
+            ),
+            JsComponentType::Ident => ComponentName::Ident(
+                CustomIdent::from_ident(&self.name)
+                    .ok_or_else(|| Error::new(BuildError::InvalidCustomIdent(self.name.as_str().into())))?,
+            ),
+            JsComponentType::UnknownDataType => {
+                ComponentName::UnknownDataType(self.name.as_str().into())
+            }
+        };
+        Component::new(name, self.multiplier).map_err(Error::new)
+    }
+}
+
+impl Descriptor<DefaultImpl> {
+    fn to_js_components(&self) -> Vec<JsComponent> {
+        self.iter().map(JsComponent::new).collect()
+    }
+
+    fn from_js_components(components: &[JsComponent]) -> Result<Self, Error> {
+        let components = components
+            .iter()
+            .map(JsComponent::to_component)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Descriptor(components))
+    }
+
+    /// Converts this descriptor into a JavaScript array of components.
+    pub fn to_js_value(&self) -> Result<JsValue, Error> {
+        let serializer = Serializer::new().serialize_missing_as_null(true);
+        self.to_js_components().serialize(&serializer)
+    }
+
+    /// Converts a JavaScript array of components into a descriptor, checking
+    /// the same invariants as the parser.
+    pub fn from_js_value(value: JsValue) -> Result<Self, Error> {
+        let components: Vec<JsComponent> = serde_wasm_bindgen::from_value(value)?;
+        Self::from_js_components(&components)
+    }
+}
+
+#[test]
+fn js_components_test() {
+    use super::parse_descriptor;
+
+    let descriptor = parse_descriptor("<length># | auto").unwrap();
+    let components = descriptor.to_js_components();
+    assert_eq!(components, [
+        JsComponent {
+            ty: JsComponentType::DataType,
+            name: "length".into(),
+            multiplier: Some(Multiplier::Comma),
+        },
+        JsComponent {
+            ty: JsComponentType::Ident,
+            name: "auto".into(),
+            multiplier: None,
+        },
+    ]);
+    assert_eq!(Descriptor::from_js_components(&components).unwrap(), descriptor);
+}